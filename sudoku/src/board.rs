@@ -1,3 +1,7 @@
+use std::{error, fmt};
+use std::str::FromStr;
+use candidates::{Candidates, MAX_CANDIDATE};
+use error::Error;
 
 pub type Pos = usize;
 //Note: we always use: (Column, Row)
@@ -9,13 +13,15 @@ const DEFAULT_BASE_NUM: usize = 3;
 // This trait allows us to call certain functions with either the index of an element,
 // or its coordinates. Custom row/column/grid functions are also implemented here for
 // faster mathematics.
-pub trait Position {
+pub trait Position: Copy {
     fn into_coord(self, width: usize) -> Coord;
     fn into_pos(self, width: usize) -> Pos;
+    /// The index of the square, if it lies within a board of this width
+    fn checked_pos(self, width: usize) -> Result<Pos, Error>;
 
     fn row_num(self, width: usize) -> usize;
     fn col_num(self, width: usize) -> usize;
-    fn grid_num(self, width: usize, base_num: usize) -> usize;
+    fn grid_num(self, width: usize, box_width: usize, box_height: usize) -> usize;
 }
 
 impl Position for Pos {
@@ -27,6 +33,17 @@ impl Position for Pos {
     fn into_pos(self, _width: usize) -> Pos {
         self
     }
+    fn checked_pos(self, width: usize) -> Result<Pos, Error> {
+        let num_squares = width * width;
+        if self < num_squares {
+            Ok(self)
+        } else {
+            Err(Error::OutOfRange {
+                pos: self,
+                num_squares,
+            })
+        }
+    }
 
 
     fn row_num(self, width: usize) -> usize {
@@ -37,8 +54,8 @@ impl Position for Pos {
         self % width
     }
 
-    fn grid_num(self, width: usize, base_num: usize) -> usize {
-        self.into_coord(width).grid_num(width, base_num)
+    fn grid_num(self, width: usize, box_width: usize, box_height: usize) -> usize {
+        self.into_coord(width).grid_num(width, box_width, box_height)
     }
 }
 
@@ -50,6 +67,17 @@ impl Position for Coord {
         let (x, y) = self;
         (width * y) + x
     }
+    fn checked_pos(self, width: usize) -> Result<Pos, Error> {
+        let (col_num, row_num) = self;
+        if col_num < width && row_num < width {
+            Ok(self.into_pos(width))
+        } else {
+            Err(Error::CoordOutOfRange {
+                coord: self,
+                side_length: width,
+            })
+        }
+    }
 
     fn row_num(self, _width: usize) -> usize {
         let (_, row_num) = self;
@@ -61,9 +89,10 @@ impl Position for Coord {
         col_num
     }
 
-    fn grid_num(self, width: usize, base_num: usize) -> usize {
+    fn grid_num(self, width: usize, box_width: usize, box_height: usize) -> usize {
         let (col_num, row_num) = self;
-        (base_num * (row_num / base_num)) + (col_num / base_num) + (col_num / width)
+        let grids_per_row = width / box_width;
+        (grids_per_row * (row_num / box_height)) + (col_num / box_width)
     }
 }
 
@@ -80,13 +109,14 @@ pub fn get_col_indices(col_num: usize, width: usize) -> Vec<Pos> {
 }
 
 // Not as elegant as the other two functions...
-pub fn get_grid_indices(grid_num: usize, width: usize) -> Vec<Pos> {
-    let base_num = (width as f64).sqrt() as usize;
+pub fn get_grid_indices(grid_num: usize, box_width: usize, box_height: usize) -> Vec<Pos> {
+    let width = box_width * box_height;
+    let grids_per_row = width / box_width;
 
     //Convert the grid num into coordinates
-    let (grid_col, grid_row) = grid_num.into_coord(base_num);
-    let (start_col, start_row) = (grid_col * base_num, grid_row * base_num);
-    let (end_col, end_row) = (start_col + base_num, start_row + base_num);
+    let (grid_col, grid_row) = grid_num.into_coord(grids_per_row);
+    let (start_col, start_row) = (grid_col * box_width, grid_row * box_height);
+    let (end_col, end_row) = (start_col + box_width, start_row + box_height);
 
     let mut indices: Vec<Pos> = Vec::new();
     for row in start_row..end_row {
@@ -98,20 +128,72 @@ pub fn get_grid_indices(grid_num: usize, width: usize) -> Vec<Pos> {
     indices
 }
 
-#[derive(Clone)]
+/// A row, column or grid - a group of squares which must all hold different values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    Grid(usize),
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Unit::Row(row_num) => write!(f, "row {}", row_num),
+            Unit::Col(col_num) => write!(f, "column {}", col_num),
+            Unit::Grid(grid_num) => write!(f, "grid {}", grid_num),
+        }
+    }
+}
+
+/// Find the most square grid dimensions, as (width, height), for a board with the given side length.
+/// Grids are never taller than they are wide.
+pub fn box_dims_for_side(side_length: usize) -> Option<(usize, usize)> {
+    if side_length == 1 {
+        return Some((1, 1));
+    }
+    let mut box_height = (side_length as f64).sqrt() as usize;
+    while box_height > 1 {
+        if side_length.is_multiple_of(box_height) {
+            return Some((side_length / box_height, box_height));
+        }
+        box_height -= 1;
+    }
+    None
+}
+
+/// Where the value in a square came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Origin {
+    /// A clue from the original puzzle, which can't be changed
+    Given,
+    /// Entered by the player
+    Player,
+    /// Deduced by one of the solvers
+    Solver,
+}
+
+#[derive(Clone, Debug)]
 pub struct Board {
     pub squares: Vec<Option<SquareType>>,
+    /// Where each value came from. Only meaningful for squares with values
+    pub origins: Vec<Origin>,
+    /// The player's own pencil marks, independent of the computed candidates
+    pub notes: Vec<Candidates>,
 
     pub num_squares: usize,
     pub side_length: usize,
     pub min_value: SquareType,
     pub max_value: SquareType,
-    pub base_num: usize,
+    /// Number of columns covered by each grid
+    pub box_width: usize,
+    /// Number of rows covered by each grid
+    pub box_height: usize,
 }
 
 impl Default for Board {
     fn default() -> Board {
-        Board::from_base_num(DEFAULT_BASE_NUM)
+        Board::from_base_num(DEFAULT_BASE_NUM).expect("The default board size is supported")
     }
 }
 
@@ -142,63 +224,203 @@ impl ToString for Board {
     }
 }
 
+/// Reasons that a string could not be turned into a `Board`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The number of values can't fill a square board
+    InvalidTokenCount(usize),
+    /// The board's side length can't be split evenly into grids
+    NonSquareSize(usize),
+    /// The board would have more values than a square's candidates can hold
+    TooLarge(usize),
+    /// A number was read, but is outside the board's range of values
+    ValueOutOfRange {
+        value: SquareType,
+        index: Pos,
+        coord: Coord,
+    },
+    /// A value could not be read as a number or a blank
+    UnknownSymbol {
+        symbol: String,
+        index: Pos,
+        coord: Coord,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::InvalidTokenCount(count) => write!(
+                f,
+                "{} values were given, which does not match any board size",
+                count
+            ),
+            ParseError::NonSquareSize(side_length) => write!(
+                f,
+                "a side length of {} can not be divided into grids",
+                side_length
+            ),
+            ParseError::TooLarge(side_length) => write!(
+                f,
+                "a side length of {} is larger than the {} supported",
+                side_length,
+                MAX_CANDIDATE
+            ),
+            ParseError::ValueOutOfRange {
+                value,
+                index,
+                coord: (col, row),
+            } => write!(
+                f,
+                "value {} at index {} (column {}, row {}) is out of range",
+                value,
+                index,
+                col,
+                row
+            ),
+            ParseError::UnknownSymbol {
+                ref symbol,
+                index,
+                coord: (col, row),
+            } => write!(
+                f,
+                "unknown symbol '{}' at index {} (column {}, row {})",
+                symbol,
+                index,
+                col,
+                row
+            ),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+impl FromStr for Board {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Board, ParseError> {
+        Board::parse(s)
+    }
+}
+
 impl Board {
-    pub fn from_base_num(base_num: usize) -> Board {
-        let squared = base_num * base_num;
-        Board::from_size(squared * squared)
+    pub fn from_base_num(base_num: usize) -> Result<Board, Error> {
+        Board::with_box_dims(base_num, base_num)
     }
 
-    pub fn from_size(num_squares: Pos) -> Board {
-        let side_length = (num_squares as f64).sqrt() as Pos;
-        let max_value = (side_length) as SquareType;
-        //TODO: Need to use from_base_num instead of this function anyways
-        let base_num = (max_value as f64).sqrt() as usize;
-        Board {
+    /// Create a board whose grids are `box_width` columns wide and `box_height` rows tall,
+    /// e.g. `Board::with_box_dims(3, 2)` for a 6x6 board.
+    ///
+    /// Fails if the board would have more than `MAX_CANDIDATE` values.
+    pub fn with_box_dims(box_width: usize, box_height: usize) -> Result<Board, Error> {
+        let side_length = box_width * box_height;
+        if side_length > MAX_CANDIDATE as usize {
+            return Err(Error::TooLarge {
+                side_length,
+                max: MAX_CANDIDATE as usize,
+            });
+        }
+        let num_squares = side_length * side_length;
+        Ok(Board {
             //Initialises NUM_SQUARES many of "None" values
             squares: vec![None; num_squares],
+            origins: vec![Origin::Player; num_squares],
+            notes: vec![Candidates::empty(); num_squares],
             side_length,
             num_squares,
             min_value: 1,
-            max_value,
-            base_num,
-        }
+            max_value: side_length as SquareType,
+            box_width,
+            box_height,
+        })
+    }
+
+    /// Create a board with the given number of squares, using the most square grids that fit.
+    ///
+    /// Panics if the squares can't be arranged into a board, and fails if the board would have
+    /// more than `MAX_CANDIDATE` values.
+    pub fn from_size(num_squares: Pos) -> Result<Board, Error> {
+        let side_length = (num_squares as f64).sqrt() as Pos;
+        assert_eq!(side_length * side_length, num_squares, "Board must be square");
+        let (box_width, box_height) =
+            box_dims_for_side(side_length).expect("Side length can not be divided into grids");
+        Board::with_box_dims(box_width, box_height)
     }
 
-    /// Create a board based on a string
+    /// Create a board based on a string.
+    ///
+    /// Panics if the string does not describe a valid board - use `Board::parse` (or `str::parse`)
+    /// to handle malformed input.
     pub fn from_string(s: &str) -> Board {
-        //TODO: Should probably make this a result
+        match Board::parse(s) {
+            Ok(board) => board,
+            Err(err) => panic!("Invalid board string: {}", err),
+        }
+    }
+
+    /// Try to create a board based on a string.
+    ///
+    /// Values may be separated by commas, or given as single characters when no commas are present.
+    /// Blank squares are written as `0`, `.` or `_`. The size of the board is taken from the number
+    /// of values, and grids are made as square as possible (so 36 values gives 3x2 grids).
+    pub fn parse(s: &str) -> Result<Board, ParseError> {
         //Strip whitespace
         let s: String = s.split_whitespace().collect::<String>().to_lowercase();
 
-        let bytes: Vec<&str> = if s.contains(",") {
+        let tokens: Vec<&str> = if s.contains(',') {
             s.split(',').collect::<Vec<&str>>()
         } else {
             //Otherwise, split all chars
             s.split("").collect::<Vec<&str>>()
         }.into_iter()
-            .filter(|x| x.len() > 0)
+            .filter(|x| !x.is_empty())
             .collect();
-        let mut game_board = Board::from_size(bytes.len());
-
-        for i in 0..game_board.squares.len() {
-            game_board.squares[i] = match bytes[i].parse::<SquareType>() {
-                Ok(num) => {
-                    if num >= game_board.min_value && num <= game_board.max_value {
-                        Some(num)
-                    } else {
-                        None
+
+        let num_tokens = tokens.len();
+        let side_length = (num_tokens as f64).sqrt() as usize;
+        if num_tokens == 0 || side_length * side_length != num_tokens {
+            return Err(ParseError::InvalidTokenCount(num_tokens));
+        }
+        let (box_width, box_height) = match box_dims_for_side(side_length) {
+            Some(dims) => dims,
+            None => return Err(ParseError::NonSquareSize(side_length)),
+        };
+
+        let mut game_board = match Board::with_box_dims(box_width, box_height) {
+            Ok(board) => board,
+            Err(_) => return Err(ParseError::TooLarge(side_length)),
+        };
+        for (index, token) in tokens.into_iter().enumerate() {
+            game_board.squares[index] = match token {
+                "0" | "." | "_" => None,
+                _ => {
+                    let coord = index.into_coord(side_length);
+                    match token.parse::<SquareType>() {
+                        Ok(num) if num >= game_board.min_value && num <= game_board.max_value => {
+                            Some(num)
+                        }
+                        Ok(num) => {
+                            return Err(ParseError::ValueOutOfRange {
+                                value: num,
+                                index,
+                                coord,
+                            });
+                        }
+                        Err(_) => {
+                            return Err(ParseError::UnknownSymbol {
+                                symbol: token.to_string(),
+                                index,
+                                coord,
+                            });
+                        }
                     }
                 }
-                Err(_) => {
-                    //Not a valid number - TODO: Maybe continue?
-                    None
-                }
             };
         }
-        game_board
+        Ok(game_board)
     }
 
-
     /// Get the row num of a square within this board
     pub fn get_row_num<T: Position>(&self, pt: T) -> Pos {
         pt.row_num(self.side_length)
@@ -211,41 +433,167 @@ impl Board {
 
     /// Get the grid num of a square within this board
     pub fn get_grid_num<T: Position>(&self, pt: T) -> Pos {
-        pt.grid_num(self.side_length, self.base_num)
+        pt.grid_num(self.side_length, self.box_width, self.box_height)
+    }
+
+    /// Get the index of a square, if it lies within this board
+    fn checked_index<T: Position>(&self, pt: T) -> Result<Pos, Error> {
+        pt.checked_pos(self.side_length)
     }
 
     /// Try to get a value
-    pub fn get_val<T: Position>(&self, pos: T) -> Result<Option<SquareType>, &'static str> {
-        let index = pos.into_pos(self.side_length);
-        if index < self.num_squares {
-            Ok(self.squares[index])
-        } else {
-            Err("Index out of range!")
-        }
+    pub fn get_val<T: Position>(&self, pos: T) -> Result<Option<SquareType>, Error> {
+        let index = self.checked_index(pos)?;
+        Ok(self.squares[index])
     }
 
-    /// Try to set a value
+    /// Try to set a value as a player entry. Givens can not be changed.
     pub fn set_val<T: Position>(
         &mut self,
         pos: T,
         val: Option<SquareType>,
-    ) -> Result<(), &'static str> {
-        let index = pos.into_pos(self.side_length);
-        self.squares[index] = {
-            if index < self.num_squares {
-                match val {
-                    Some(val) if val > self.max_value || val < self.min_value => {
-                        return Err("Invalid value!");
-                    }
-                    _ => val,
-                }
-            } else {
-                return Err("Index out of range!");
+    ) -> Result<(), Error> {
+        let index = self.check_set_val(pos, val)?;
+        self.squares[index] = val;
+        self.origins[index] = Origin::Player;
+        if let Some(val) = val {
+            self.prune_notes(index, val);
+        }
+        Ok(())
+    }
+
+    /// Try to set a value that was deduced by a solver
+    pub fn set_deduced<T: Position>(&mut self, pos: T, val: SquareType) -> Result<(), Error> {
+        let index = self.check_set_val(pos, Some(val))?;
+        self.squares[index] = Some(val);
+        self.origins[index] = Origin::Solver;
+        self.prune_notes(index, val);
+        Ok(())
+    }
+
+    /// Check that a value could be set in a square, returning the index of the square
+    pub fn check_set_val<T: Position>(
+        &self,
+        pos: T,
+        val: Option<SquareType>,
+    ) -> Result<Pos, Error> {
+        let index = self.checked_index(pos)?;
+        self.check_val(val)?;
+        if self.is_given(index) {
+            return Err(Error::GivenSquare { pos: index });
+        }
+        Ok(index)
+    }
+
+    /// Check that a value could be stored in this board
+    pub fn check_val(&self, val: Option<SquareType>) -> Result<(), Error> {
+        match val {
+            Some(val) if val > self.max_value || val < self.min_value => Err(Error::InvalidValue {
+                value: val,
+                min: self.min_value,
+                max: self.max_value,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Where the value in a square came from, or `None` if the square is blank
+    pub fn get_origin<T: Position>(&self, pos: T) -> Result<Option<Origin>, Error> {
+        let index = self.checked_index(pos)?;
+        Ok(self.squares[index].map(|_| self.origins[index]))
+    }
+
+    /// Whether the square holds a clue from the original puzzle
+    pub fn is_given<T: Position>(&self, pos: T) -> bool {
+        match self.checked_index(pos) {
+            Ok(index) => self.squares[index].is_some() && self.origins[index] == Origin::Given,
+            Err(_) => false,
+        }
+    }
+
+    /// Mark every value currently on the board as a given, so it can no longer be changed
+    pub fn lock_givens(&mut self) {
+        for index in 0..self.num_squares {
+            if self.squares[index].is_some() {
+                self.origins[index] = Origin::Given;
             }
-        };
+        }
+    }
+
+    /// Turn all givens back into player entries
+    pub fn unlock_givens(&mut self) {
+        for origin in self.origins.iter_mut() {
+            if *origin == Origin::Given {
+                *origin = Origin::Player;
+            }
+        }
+    }
+
+    /// A copy of this board with only the givens filled in
+    pub fn givens(&self) -> Board {
+        let mut board = self.clone();
+        for index in 0..self.num_squares {
+            if !self.is_given(index) {
+                board.squares[index] = None;
+                board.origins[index] = Origin::Player;
+            }
+        }
+        board.clear_all_notes();
+        board
+    }
+
+    /// The string representation of the original puzzle, without any player entries or deductions
+    pub fn to_givens_string(&self) -> String {
+        self.givens().to_string()
+    }
+
+    /// Get the player's notes for a square
+    pub fn get_notes<T: Position>(&self, pos: T) -> Result<Candidates, Error> {
+        let index = self.checked_index(pos)?;
+        Ok(self.notes[index])
+    }
+
+    /// Add a note to a square, or remove it if it is already there
+    pub fn toggle_note<T: Position>(&mut self, pos: T, val: SquareType) -> Result<(), Error> {
+        let index = self.checked_index(pos)?;
+        self.check_val(Some(val))?;
+        if self.notes[index].contains(val) {
+            self.notes[index].remove(val);
+        } else {
+            self.notes[index].insert(val);
+        }
+        Ok(())
+    }
+
+    /// Remove all notes from a square
+    pub fn clear_notes<T: Position>(&mut self, pos: T) -> Result<(), Error> {
+        let index = self.checked_index(pos)?;
+        self.notes[index] = Candidates::empty();
         Ok(())
     }
 
+    /// Remove every note from the board
+    pub fn clear_all_notes(&mut self) {
+        for notes in self.notes.iter_mut() {
+            *notes = Candidates::empty();
+        }
+    }
+
+    /// Whether any blank square has notes
+    pub fn has_notes(&self) -> bool {
+        (0..self.num_squares).any(|index| {
+            self.squares[index].is_none() && !self.notes[index].is_empty()
+        })
+    }
+
+    /// Remove a value that has just been placed from the notes of every square that shares a unit
+    /// with it
+    pub fn prune_notes(&mut self, index: Pos, val: SquareType) {
+        for peer in self.get_peer_indices(index) {
+            self.notes[peer].remove(val);
+        }
+    }
+
     /// Get all indicies of squares within a column
     pub fn get_col_indices(&self, col_num: usize) -> Vec<Pos> {
         get_col_indices(col_num, self.side_length)
@@ -275,7 +623,7 @@ impl Board {
 
     /// Get all indicies of squares within a grid
     pub fn get_grid_indices(&self, grid_num: usize) -> Vec<Pos> {
-        get_grid_indices(grid_num, self.side_length)
+        get_grid_indices(grid_num, self.box_width, self.box_height)
     }
 
     /// Gets all values in this grid
@@ -286,31 +634,98 @@ impl Board {
             .collect()
     }
 
+    /// Get all indices of squares within a unit
+    pub fn get_unit_indices(&self, unit: Unit) -> Vec<Pos> {
+        match unit {
+            Unit::Row(row_num) => self.get_row_indices(row_num),
+            Unit::Col(col_num) => self.get_col_indices(col_num),
+            Unit::Grid(grid_num) => self.get_grid_indices(grid_num),
+        }
+    }
+
+    /// Get the row, column and grid that a square belongs to
+    pub fn get_units<T: Position>(&self, pt: T) -> [Unit; 3] {
+        [
+            Unit::Row(self.get_row_num(pt)),
+            Unit::Col(self.get_col_num(pt)),
+            Unit::Grid(self.get_grid_num(pt)),
+        ]
+    }
+
+    /// Get all indices of squares that share a row, column or grid with a square, not including
+    /// the square itself
+    pub fn get_peer_indices<T: Position>(&self, pt: T) -> Vec<Pos> {
+        let (col_num, row_num) = pt.into_coord(self.side_length);
+        let index = (col_num, row_num).into_pos(self.side_length);
+        let mut indices: Vec<Pos> = self.get_row_indices(row_num)
+            .into_iter()
+            .filter(|&other| other != index)
+            .collect();
+        indices.extend(
+            self.get_col_indices(col_num)
+                .into_iter()
+                .filter(|&other| self.get_row_num(other) != row_num),
+        );
+        indices.extend(
+            self.get_grid_indices(self.get_grid_num(index))
+                .into_iter()
+                .filter(|&other| {
+                    self.get_row_num(other) != row_num && self.get_col_num(other) != col_num
+                }),
+        );
+        indices
+    }
+
+    /// Whether two different squares share a row, column or grid
+    pub fn is_peer<T: Position>(&self, first: T, second: T) -> bool {
+        let first = first.into_pos(self.side_length);
+        let second = second.into_pos(self.side_length);
+        first != second &&
+            (self.get_row_num(first) == self.get_row_num(second) ||
+                self.get_col_num(first) == self.get_col_num(second) ||
+                self.get_grid_num(first) == self.get_grid_num(second))
+    }
+
     /// Set all values within a row
-    pub fn set_row(&mut self, row_num: usize, vals: Vec<Option<SquareType>>) {
+    pub fn set_row(
+        &mut self,
+        row_num: usize,
+        vals: Vec<Option<SquareType>>,
+    ) -> Result<(), Error> {
         let row_inds = self.get_row_indices(row_num);
         for (i, ind) in row_inds.iter().enumerate() {
             let val = vals[i];
-            self.set_val(*ind, val).unwrap();
+            self.set_val(*ind, val)?;
         }
+        Ok(())
     }
 
     /// Set all values within a column
-    pub fn set_col(&mut self, col_num: usize, vals: Vec<Option<SquareType>>) {
+    pub fn set_col(
+        &mut self,
+        col_num: usize,
+        vals: Vec<Option<SquareType>>,
+    ) -> Result<(), Error> {
         let col_inds = self.get_col_indices(col_num);
         for (i, ind) in col_inds.iter().enumerate() {
             let val = vals[i];
-            self.set_val(*ind, val).unwrap();
+            self.set_val(*ind, val)?;
         }
+        Ok(())
     }
 
     /// Set all values within a grid
-    pub fn set_grid(&mut self, grid_num: usize, vals: Vec<Option<SquareType>>) {
+    pub fn set_grid(
+        &mut self,
+        grid_num: usize,
+        vals: Vec<Option<SquareType>>,
+    ) -> Result<(), Error> {
         let grid_inds = self.get_grid_indices(grid_num);
         for (i, ind) in grid_inds.iter().enumerate() {
             let val = vals[i];
-            self.set_val(*ind, val).unwrap();
+            self.set_val(*ind, val)?;
         }
+        Ok(())
     }
 
     /// Number of squares with values
@@ -379,6 +794,26 @@ pub const _UNSOLVED_16: &str = concat!(
     "0,0,3,0,0,0,14,0,0,13,0,0,4,0,0,2"
 );
 
+#[cfg(test)]
+pub const SOLVED_6_STR: &str = concat!(
+    "1,2,3,4,5,6,",
+    "4,5,6,1,2,3,",
+    "2,3,1,5,6,4,",
+    "5,6,4,2,3,1,",
+    "3,1,2,6,4,5,",
+    "6,4,5,3,1,2"
+);
+
+#[cfg(test)]
+pub const UNSOLVED_6_STR: &str = concat!(
+    "1,0,3,4,0,6,",
+    "0,5,6,0,2,3,",
+    "2,3,0,5,6,0,",
+    "0,6,4,2,0,1,",
+    "3,0,2,6,4,0,",
+    "6,4,0,0,1,2"
+);
+
 #[cfg(test)]
 pub const INVALID_PUZZLE_STR: &str = concat!(
     "8,2,7,1,5,4,3,9,6,",
@@ -500,7 +935,7 @@ mod tests {
     #[test]
     fn test_board_length() {
         let new_board = Board::default();
-        match new_board.base_num {
+        match new_board.box_width {
             2 => {
                 assert_eq!(16, new_board.squares.len());
             }
@@ -519,7 +954,7 @@ mod tests {
     #[test]
     fn test_board_cleared() {
         let new_board = Board::default();
-        for i in 0..new_board.base_num {
+        for i in 0..new_board.box_width {
             assert_eq!(None, new_board.squares[i]);
             assert_ne!(Some(0), new_board.squares[i]);
         }
@@ -563,6 +998,49 @@ mod tests {
         assert_ne!(None, new_board.squares[80]);
     }
 
+    #[test]
+    fn test_board_parse() {
+        let board: Board = SOLVED_PUZZLE_STR.parse().unwrap();
+        assert_eq!((3, 3), (board.box_width, board.box_height));
+        assert_eq!(Some(8), board.squares[0]);
+        assert_eq!(SOLVED_PUZZLE_STR, board.to_string());
+
+        let mini = Board::parse("3.1_ 0214 .... ....").unwrap();
+        assert_eq!((2, 2), (mini.box_width, mini.box_height));
+        assert_eq!(Some(3), mini.squares[0]);
+        assert_eq!(None, mini.squares[1]);
+        assert_eq!(None, mini.squares[3]);
+        assert_eq!(Some(4), mini.squares[7]);
+    }
+
+    #[test]
+    fn test_board_parse_errors() {
+        // One value short of a 9x9 board
+        let short = &UNSOLVED_PUZZLE_STR[..UNSOLVED_PUZZLE_STR.len() - 2];
+        assert_eq!(Err(ParseError::InvalidTokenCount(80)), Board::parse(short));
+        assert_eq!(Err(ParseError::InvalidTokenCount(0)), Board::parse(" , "));
+        assert_eq!(
+            Err(ParseError::NonSquareSize(3)),
+            Board::parse("1,2,3,2,3,1,3,1,2")
+        );
+        assert_eq!(
+            Err(ParseError::ValueOutOfRange {
+                value: 5,
+                index: 6,
+                coord: (2, 1),
+            }),
+            Board::parse("1,2,3,4,3,4,5,2,0,0,0,0,0,0,0,0")
+        );
+        assert_eq!(
+            Err(ParseError::UnknownSymbol {
+                symbol: String::from("x"),
+                index: 13,
+                coord: (1, 3),
+            }),
+            Board::parse("1234341200000x00")
+        );
+    }
+
     #[test]
     fn test_board_to_string() {
         let solved_board = Board::from_string(SOLVED_PUZZLE_STR);
@@ -712,6 +1190,175 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_box_dims_for_side() {
+        assert_eq!(Some((1, 1)), box_dims_for_side(1));
+        assert_eq!(Some((2, 2)), box_dims_for_side(4));
+        assert_eq!(Some((3, 2)), box_dims_for_side(6));
+        assert_eq!(Some((4, 2)), box_dims_for_side(8));
+        assert_eq!(Some((3, 3)), box_dims_for_side(9));
+        assert_eq!(Some((5, 2)), box_dims_for_side(10));
+        assert_eq!(Some((4, 3)), box_dims_for_side(12));
+        assert_eq!(None, box_dims_for_side(7));
+    }
+
+    #[test]
+    fn test_rectangular_grids() {
+        let board = Board::with_box_dims(3, 2).unwrap();
+        assert_eq!(6, board.side_length);
+        assert_eq!(36, board.num_squares);
+        assert_eq!(6, board.max_value);
+
+        assert_eq!(vec![0, 1, 2, 6, 7, 8], board.get_grid_indices(0));
+        assert_eq!(vec![3, 4, 5, 9, 10, 11], board.get_grid_indices(1));
+        assert_eq!(vec![12, 13, 14, 18, 19, 20], board.get_grid_indices(2));
+        assert_eq!(vec![27, 28, 29, 33, 34, 35], board.get_grid_indices(5));
+
+        assert_eq!(0, board.get_grid_num((2, 1)));
+        assert_eq!(1, board.get_grid_num((3, 0)));
+        assert_eq!(3, board.get_grid_num((4, 3)));
+        assert_eq!(4, board.get_grid_num(24));
+
+        let tall = Board::with_box_dims(2, 4).unwrap();
+        assert_eq!(vec![0, 1, 8, 9, 16, 17, 24, 25], tall.get_grid_indices(0));
+        assert_eq!(3, tall.get_grid_num((7, 0)));
+        assert_eq!(4, tall.get_grid_num((0, 4)));
+
+        for grid_num in 0..board.side_length {
+            for pos in board.get_grid_indices(grid_num) {
+                assert_eq!(grid_num, board.get_grid_num(pos));
+            }
+        }
+    }
+
+    #[test]
+    fn test_board_parse_rectangular() {
+        let board = Board::parse(SOLVED_6_STR).unwrap();
+        assert_eq!((3, 2), (board.box_width, board.box_height));
+        assert_eq!(SOLVED_6_STR, board.to_string());
+        assert_eq!(
+            vec![Some(2), Some(3), Some(1), Some(5), Some(6), Some(4)],
+            board.get_grid_values(2)
+        );
+    }
+
+    #[test]
+    fn test_too_large() {
+        assert_eq!(
+            Err(Error::TooLarge {
+                side_length: 144,
+                max: 128,
+            }),
+            Board::from_base_num(12)
+        );
+        let blanks = vec!["0"; 132 * 132].join(",");
+        assert_eq!(Err(ParseError::TooLarge(132)), Board::parse(&blanks));
+
+        let mut largest = Board::with_box_dims(16, 8).unwrap();
+        largest.set_val((0, 0), Some(128)).unwrap();
+        largest.set_val((1, 0), Some(1)).unwrap();
+        assert_eq!(Some(128), largest.get_val(0).unwrap());
+    }
+
+    #[test]
+    fn test_set_val_errors() {
+        let mut board = Board::default();
+        assert_eq!(
+            Err(Error::OutOfRange {
+                pos: 81,
+                num_squares: 81,
+            }),
+            board.set_val(81, Some(1))
+        );
+        assert_eq!(
+            Err(Error::CoordOutOfRange {
+                coord: (9, 0),
+                side_length: 9,
+            }),
+            board.set_val((9, 0), Some(1))
+        );
+        assert_eq!(
+            Err(Error::CoordOutOfRange {
+                coord: (9, 0),
+                side_length: 9,
+            }),
+            board.get_val((9, 0))
+        );
+        assert_eq!(
+            Err(Error::InvalidValue {
+                value: 10,
+                min: 1,
+                max: 9,
+            }),
+            board.set_val((0, 0), Some(10))
+        );
+        assert!(board.get_val((0, 9)).is_err());
+        assert!(board.set_row(9, vec![None; 9]).is_err());
+        assert_eq!(None, board.get_val((0, 0)).unwrap());
+    }
+
+    #[test]
+    fn test_givens() {
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        assert!(!board.is_given((1, 0)));
+        board.lock_givens();
+        assert!(board.is_given((1, 0)));
+        assert!(!board.is_given((4, 0)));
+        assert_eq!(Some(Origin::Given), board.get_origin((1, 0)).unwrap());
+        assert_eq!(None, board.get_origin((4, 0)).unwrap());
+
+        // Givens can't be overwritten or cleared
+        assert_eq!(
+            Err(Error::GivenSquare { pos: 1 }),
+            board.set_val((1, 0), Some(1))
+        );
+        assert_eq!(Err(Error::GivenSquare { pos: 1 }), board.set_val(1, None));
+        assert!(board.set_deduced(1, 1).is_err());
+
+        board.set_val((4, 0), Some(9)).unwrap();
+        board.set_deduced((6, 0), 3).unwrap();
+        assert_eq!(Some(Origin::Player), board.get_origin((4, 0)).unwrap());
+        assert_eq!(Some(Origin::Solver), board.get_origin((6, 0)).unwrap());
+        assert_ne!(UNSOLVED_PUZZLE_STR, board.to_string());
+        assert_eq!(UNSOLVED_PUZZLE_STR, board.to_givens_string());
+
+        board.unlock_givens();
+        board.set_val((1, 0), None).unwrap();
+        assert_eq!(None, board.get_val((1, 0)).unwrap());
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut board = Board::default();
+        board.toggle_note((0, 0), 1).unwrap();
+        board.toggle_note((0, 0), 2).unwrap();
+        board.toggle_note((8, 0), 1).unwrap();
+        board.toggle_note((4, 4), 1).unwrap();
+        assert!(board.has_notes());
+        assert_eq!(vec![1, 2], board.get_notes((0, 0)).unwrap().to_vec());
+
+        board.toggle_note((0, 0), 2).unwrap();
+        assert_eq!(vec![1], board.get_notes((0, 0)).unwrap().to_vec());
+        assert!(board.toggle_note((0, 0), 10).is_err());
+        assert!(board.toggle_note((9, 0), 1).is_err());
+
+        // Placing a value removes it from the notes of its peers only
+        board.set_val((1, 1), Some(1)).unwrap();
+        assert!(board.get_notes((0, 0)).unwrap().is_empty());
+        assert_eq!(vec![1], board.get_notes((8, 0)).unwrap().to_vec());
+        assert_eq!(vec![1], board.get_notes((4, 4)).unwrap().to_vec());
+        board.set_deduced((4, 0), 1).unwrap();
+        assert!(board.get_notes((8, 0)).unwrap().is_empty());
+
+        board.clear_notes((4, 4)).unwrap();
+        assert!(!board.has_notes());
+        assert_eq!(20, board.get_peer_indices((4, 4)).len());
+        for index in 0..board.num_squares {
+            let is_peer = board.get_peer_indices((4, 4)).contains(&index);
+            assert_eq!(is_peer, board.is_peer(40, index));
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_set_coord_invalid_0() {
@@ -0,0 +1,363 @@
+use board::*;
+use candidates::Candidates;
+use error::Error;
+use hintmap::HintMap;
+use solver::BoardStatus;
+use std::ops::Deref;
+
+/// A `Board` which keeps its candidates, the number of each value in every row/column/grid and
+/// its number of conflicts up to date as values are set and cleared. Each change only touches the
+/// squares that share a unit with it, and checking the status of the board is O(1).
+///
+/// Read-only access to the underlying `Board` is given through `Deref`, but all changes must go
+/// through `set_val` so that the counts stay in step with the squares.
+#[derive(Clone, Debug)]
+pub struct CandidateBoard {
+    board: Board,
+    candidates: Vec<Candidates>,
+    /// Number of squares holding each value, for every unit. Indexed by `unit_id * side_length + val - 1`
+    unit_counts: Vec<usize>,
+    /// Values held at least once in each unit
+    unit_used: Vec<Candidates>,
+    /// Number of repeated values across all units
+    num_conflicts: usize,
+    /// Number of blank squares with no candidates left
+    num_dead: usize,
+    num_filled: usize,
+}
+
+impl Deref for CandidateBoard {
+    type Target = Board;
+
+    fn deref(&self) -> &Board {
+        &self.board
+    }
+}
+
+impl From<Board> for CandidateBoard {
+    fn from(board: Board) -> CandidateBoard {
+        CandidateBoard::new(board)
+    }
+}
+
+impl CandidateBoard {
+    pub fn new(board: Board) -> CandidateBoard {
+        let num_groups = board.side_length;
+        let mut cboard = CandidateBoard {
+            candidates: vec![Candidates::empty(); board.num_squares],
+            unit_counts: vec![0; 3 * num_groups * num_groups],
+            unit_used: vec![Candidates::empty(); 3 * num_groups],
+            num_conflicts: 0,
+            num_dead: 0,
+            num_filled: 0,
+            board,
+        };
+
+        for index in 0..cboard.board.num_squares {
+            if let Some(val) = cboard.board.squares[index] {
+                cboard.add_value(index, val);
+            }
+        }
+        for index in 0..cboard.board.num_squares {
+            cboard.refresh(index);
+            if cboard.is_dead(index) {
+                cboard.num_dead += 1;
+            }
+        }
+        cboard
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+
+    /// Set or clear a value as a player entry, updating the candidates of every square that shares
+    /// a unit with it
+    pub fn set_val<T: Position>(&mut self, pos: T, val: Option<SquareType>) -> Result<(), Error> {
+        self.update(pos, val, Origin::Player)
+    }
+
+    /// Fill a square with a value found by a solver.
+    /// Panics if the square or the value is outside of the board, or the square is a given.
+    pub fn fill(&mut self, index: Pos, val: SquareType) {
+        self.update(index, Some(val), Origin::Solver)
+            .expect("Solved values are always within the board");
+    }
+
+    /// The values that could still go in a square. Filled squares have no candidates.
+    pub fn get_candidates<T: Position>(&self, pos: T) -> Candidates {
+        let index = pos.into_pos(self.board.side_length);
+        match self.candidates.get(index) {
+            Some(candidates) => *candidates,
+            None => Candidates::empty(),
+        }
+    }
+
+    /// Number of squares in a unit holding `val`
+    pub fn count_in_unit(&self, unit: Unit, val: SquareType) -> usize {
+        let val_offset = (val - self.board.min_value) as usize;
+        self.unit_counts[self.unit_id(unit) * self.board.side_length + val_offset]
+    }
+
+    /// Number of times a value is repeated within a row, column or grid
+    pub fn num_conflicts(&self) -> usize {
+        self.num_conflicts
+    }
+
+    pub fn num_filled(&self) -> usize {
+        self.num_filled
+    }
+
+    pub fn num_remaining(&self) -> usize {
+        self.board.num_squares - self.num_filled
+    }
+
+    /// The same result as `Board::check_status`, without needing to scan the board
+    pub fn check_status(&self) -> BoardStatus {
+        if self.num_conflicts > 0 || self.num_dead > 0 {
+            BoardStatus::Invalid
+        } else if self.num_filled == self.board.num_squares {
+            BoardStatus::Solved
+        } else {
+            BoardStatus::Valid
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.check_status() != BoardStatus::Invalid
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.check_status() == BoardStatus::Solved
+    }
+
+    /// A `HintMap` built from the current candidates, without recomputing them
+    pub fn hint_map(&self) -> HintMap {
+        HintMap {
+            candidates: self.candidates.clone(),
+            side_length: self.board.side_length,
+            box_width: self.board.box_width,
+            box_height: self.board.box_height,
+        }
+    }
+
+    fn update<T: Position>(
+        &mut self,
+        pos: T,
+        val: Option<SquareType>,
+        origin: Origin,
+    ) -> Result<(), Error> {
+        // Validate the position and value before touching any of the counts
+        let index = self.board.check_set_val(pos, val)?;
+        let old_val = self.board.squares[index];
+        self.board.origins[index] = origin;
+        if old_val == val {
+            return Ok(());
+        }
+
+        let affected = self.affected_indices(index);
+        for &other in &affected {
+            if self.is_dead(other) {
+                self.num_dead -= 1;
+            }
+        }
+
+        if let Some(old_val) = old_val {
+            self.remove_value(index, old_val);
+        }
+        self.board.squares[index] = val;
+        if let Some(val) = val {
+            self.add_value(index, val);
+            self.board.prune_notes(index, val);
+        }
+
+        for &other in &affected {
+            self.refresh(other);
+            if self.is_dead(other) {
+                self.num_dead += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn unit_id(&self, unit: Unit) -> usize {
+        let num_groups = self.board.side_length;
+        match unit {
+            Unit::Row(row_num) => row_num,
+            Unit::Col(col_num) => num_groups + col_num,
+            Unit::Grid(grid_num) => (2 * num_groups) + grid_num,
+        }
+    }
+
+    fn unit_ids(&self, index: Pos) -> [usize; 3] {
+        let units = self.board.get_units(index);
+        [
+            self.unit_id(units[0]),
+            self.unit_id(units[1]),
+            self.unit_id(units[2]),
+        ]
+    }
+
+    fn add_value(&mut self, index: Pos, val: SquareType) {
+        let val_offset = (val - self.board.min_value) as usize;
+        for &unit_id in self.unit_ids(index).iter() {
+            let count = &mut self.unit_counts[unit_id * self.board.side_length + val_offset];
+            *count += 1;
+            if *count > 1 {
+                self.num_conflicts += 1;
+            }
+            self.unit_used[unit_id].insert(val);
+        }
+        self.num_filled += 1;
+    }
+
+    fn remove_value(&mut self, index: Pos, val: SquareType) {
+        let val_offset = (val - self.board.min_value) as usize;
+        for &unit_id in self.unit_ids(index).iter() {
+            let count = &mut self.unit_counts[unit_id * self.board.side_length + val_offset];
+            if *count > 1 {
+                self.num_conflicts -= 1;
+            }
+            *count -= 1;
+            if *count == 0 {
+                self.unit_used[unit_id].remove(val);
+            }
+        }
+        self.num_filled -= 1;
+    }
+
+    /// Recompute the candidates of a single square from the values used in its units
+    fn refresh(&mut self, index: Pos) {
+        self.candidates[index] = if self.board.squares[index].is_some() {
+            Candidates::empty()
+        } else {
+            let [row_id, col_id, grid_id] = self.unit_ids(index);
+            let used = self.unit_used[row_id] | self.unit_used[col_id] | self.unit_used[grid_id];
+            Candidates::all(self.board.max_value) - used
+        };
+    }
+
+    fn is_dead(&self, index: Pos) -> bool {
+        self.board.squares[index].is_none() && self.candidates[index].is_empty()
+    }
+
+    /// The square itself and all of its peers
+    fn affected_indices(&self, index: Pos) -> Vec<Pos> {
+        let mut indices = self.board.get_peer_indices(index);
+        indices.push(index);
+        indices
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every incremental count must match one built from scratch
+    fn assert_consistent(cboard: &CandidateBoard) {
+        let fresh = CandidateBoard::new(cboard.board().clone());
+        assert_eq!(fresh.candidates, cboard.candidates);
+        assert_eq!(fresh.unit_counts, cboard.unit_counts);
+        assert_eq!(fresh.num_conflicts, cboard.num_conflicts);
+        assert_eq!(fresh.num_dead, cboard.num_dead);
+        assert_eq!(fresh.num_filled, cboard.num_filled);
+        assert_eq!(cboard.board().check_status(), cboard.check_status());
+    }
+
+    #[test]
+    fn test_candidate_board_matches_hint_map() {
+        let board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let hmap = HintMap::from_board(&board);
+        let cboard = CandidateBoard::new(board);
+        assert_eq!(hmap.candidates, cboard.candidates);
+        assert_eq!(vec![9], cboard.get_candidates(9).to_vec());
+        assert_eq!(BoardStatus::Valid, cboard.check_status());
+    }
+
+    #[test]
+    fn test_candidate_board_set_and_clear() {
+        let mut cboard = CandidateBoard::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+        let remaining = cboard.num_remaining();
+
+        cboard.set_val(9, Some(9)).unwrap();
+        assert_eq!(remaining - 1, cboard.num_remaining());
+        assert_eq!(1, cboard.count_in_unit(Unit::Col(0), 9));
+        assert!(!cboard.get_candidates(18).contains(9));
+        assert_consistent(&cboard);
+
+        // Putting a second 8 in the first column conflicts with the column and the grid
+        cboard.set_val((0, 2), Some(8)).unwrap();
+        assert_eq!(2, cboard.count_in_unit(Unit::Col(0), 8));
+        assert_eq!(2, cboard.count_in_unit(Unit::Grid(0), 8));
+        assert_eq!(1, cboard.count_in_unit(Unit::Row(2), 8));
+        assert_eq!(2, cboard.num_conflicts());
+        assert_eq!(BoardStatus::Invalid, cboard.check_status());
+        assert_consistent(&cboard);
+
+        cboard.set_val((0, 2), None).unwrap();
+        assert_eq!(0, cboard.num_conflicts());
+        assert_eq!(BoardStatus::Valid, cboard.check_status());
+        assert_consistent(&cboard);
+
+        assert!(cboard.set_val(81, Some(1)).is_err());
+        assert!(cboard.set_val(0, Some(10)).is_err());
+        assert_consistent(&cboard);
+    }
+
+    #[test]
+    fn test_candidate_board_givens() {
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        board.lock_givens();
+        let mut cboard = CandidateBoard::new(board);
+
+        assert_eq!(
+            Err(Error::GivenSquare { pos: 1 }),
+            cboard.set_val(1, None)
+        );
+        assert_consistent(&cboard);
+
+        cboard.fill(9, 9);
+        assert_eq!(Some(Origin::Solver), cboard.get_origin(9).unwrap());
+        cboard.set_val(9, None).unwrap();
+        assert_consistent(&cboard);
+    }
+
+    #[test]
+    fn test_candidate_board_solved() {
+        let solved = Board::from_string(SOLVED_PUZZLE_STR);
+        let mut cboard = CandidateBoard::new(solved.clone());
+        assert!(cboard.is_solved());
+
+        cboard.set_val(40, None).unwrap();
+        assert_eq!(BoardStatus::Valid, cboard.check_status());
+        assert_eq!(vec![1], cboard.get_candidates(40).to_vec());
+
+        cboard.set_val(40, solved.squares[40]).unwrap();
+        assert!(cboard.is_solved());
+        assert_consistent(&cboard);
+    }
+
+    #[test]
+    fn test_candidate_board_dead_square() {
+        let mut cboard = CandidateBoard::new(Board::from_base_num(2).unwrap());
+        cboard.set_val((1, 0), Some(1)).unwrap();
+        cboard.set_val((2, 0), Some(2)).unwrap();
+        cboard.set_val((0, 1), Some(3)).unwrap();
+        assert_eq!(BoardStatus::Valid, cboard.check_status());
+
+        // The top-left square can't hold anything once a 4 is in its column
+        cboard.set_val((0, 3), Some(4)).unwrap();
+        assert!(cboard.get_candidates((0, 0)).is_empty());
+        assert_eq!(BoardStatus::Invalid, cboard.check_status());
+        assert_consistent(&cboard);
+
+        cboard.set_val((0, 3), None).unwrap();
+        assert_eq!(BoardStatus::Valid, cboard.check_status());
+        assert_consistent(&cboard);
+    }
+}
@@ -0,0 +1,216 @@
+use board::SquareType;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
+/// The largest value that can be stored in `Candidates`
+pub const MAX_CANDIDATE: SquareType = 128;
+
+/// A set of possible values for a square, stored as a bitmask where bit `n` represents the value
+/// `n + 1`. A single `u128` covers every board up to 128 values (including 81x81), so membership
+/// tests, counting and set operations are all constant time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Candidates(u128);
+
+impl Candidates {
+    /// A set with no values
+    pub fn empty() -> Candidates {
+        Candidates(0)
+    }
+
+    /// A set containing every value from 1 to `max_value`
+    pub fn all(max_value: SquareType) -> Candidates {
+        assert!(
+            (0..=MAX_CANDIDATE).contains(&max_value),
+            "Candidates only support values up to {}",
+            MAX_CANDIDATE
+        );
+        if max_value == MAX_CANDIDATE {
+            Candidates(!0)
+        } else {
+            Candidates((1 << max_value) - 1)
+        }
+    }
+
+    /// A set containing just `val`
+    pub fn single(val: SquareType) -> Candidates {
+        Candidates(Candidates::bit(val))
+    }
+
+    fn bit(val: SquareType) -> u128 {
+        debug_assert!((1..=MAX_CANDIDATE).contains(&val));
+        1 << (val - 1)
+    }
+
+    pub fn contains(self, val: SquareType) -> bool {
+        (1..=MAX_CANDIDATE).contains(&val) && self.0 & Candidates::bit(val) != 0
+    }
+
+    pub fn insert(&mut self, val: SquareType) {
+        self.0 |= Candidates::bit(val);
+    }
+
+    pub fn remove(&mut self, val: SquareType) {
+        self.0 &= !Candidates::bit(val);
+    }
+
+    /// Number of values in the set
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The smallest value in the set
+    pub fn first(self) -> Option<SquareType> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as SquareType + 1)
+        }
+    }
+
+    /// The value in the set, if it is the only one
+    pub fn single_value(self) -> Option<SquareType> {
+        if self.len() == 1 {
+            self.first()
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(self) -> CandidatesIter {
+        CandidatesIter(self.0)
+    }
+
+    /// The values in the set, from smallest to largest
+    pub fn to_vec(self) -> Vec<SquareType> {
+        self.iter().collect()
+    }
+}
+
+/// Iterates over the values in a `Candidates`, from smallest to largest
+pub struct CandidatesIter(u128);
+
+impl Iterator for CandidatesIter {
+    type Item = SquareType;
+
+    fn next(&mut self) -> Option<SquareType> {
+        if self.0 == 0 {
+            return None;
+        }
+        let val = self.0.trailing_zeros() as SquareType + 1;
+        // Clear the lowest set bit
+        self.0 &= self.0 - 1;
+        Some(val)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl IntoIterator for Candidates {
+    type Item = SquareType;
+    type IntoIter = CandidatesIter;
+
+    fn into_iter(self) -> CandidatesIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<SquareType> for Candidates {
+    fn from_iter<I: IntoIterator<Item = SquareType>>(iter: I) -> Candidates {
+        let mut candidates = Candidates::empty();
+        for val in iter {
+            candidates.insert(val);
+        }
+        candidates
+    }
+}
+
+impl BitOr for Candidates {
+    type Output = Candidates;
+    fn bitor(self, other: Candidates) -> Candidates {
+        Candidates(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Candidates {
+    fn bitor_assign(&mut self, other: Candidates) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for Candidates {
+    type Output = Candidates;
+    fn bitand(self, other: Candidates) -> Candidates {
+        Candidates(self.0 & other.0)
+    }
+}
+
+impl BitAndAssign for Candidates {
+    fn bitand_assign(&mut self, other: Candidates) {
+        self.0 &= other.0;
+    }
+}
+
+/// Values in the first set that are not in the second
+impl Sub for Candidates {
+    type Output = Candidates;
+    fn sub(self, other: Candidates) -> Candidates {
+        Candidates(self.0 & !other.0)
+    }
+}
+
+impl SubAssign for Candidates {
+    fn sub_assign(&mut self, other: Candidates) {
+        self.0 &= !other.0;
+    }
+}
+
+/// Every value (up to `MAX_CANDIDATE`) not in the set
+impl Not for Candidates {
+    type Output = Candidates;
+    fn not(self) -> Candidates {
+        Candidates(!self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_all() {
+        assert_eq!(vec![1, 2, 3, 4], Candidates::all(4).to_vec());
+        assert_eq!(81, Candidates::all(81).len());
+        assert_eq!(128, Candidates::all(MAX_CANDIDATE).len());
+        assert!(Candidates::all(0).is_empty());
+    }
+
+    #[test]
+    fn test_candidates_ops() {
+        let mut cands = Candidates::empty();
+        cands.insert(3);
+        cands.insert(9);
+        cands.insert(81);
+        assert!(cands.contains(81));
+        assert!(!cands.contains(2));
+        assert!(!cands.contains(0));
+        assert_eq!(3, cands.len());
+        assert_eq!(Some(3), cands.first());
+        assert_eq!(None, cands.single_value());
+
+        cands.remove(3);
+        assert_eq!(vec![9, 81], cands.iter().collect::<Vec<_>>());
+
+        let other: Candidates = vec![9, 10].into_iter().collect();
+        assert_eq!(vec![9], (cands & other).to_vec());
+        assert_eq!(vec![9, 10, 81], (cands | other).to_vec());
+        assert_eq!(vec![81], (cands - other).to_vec());
+        assert_eq!(Some(81), (cands - other).single_value());
+    }
+}
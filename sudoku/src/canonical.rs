@@ -0,0 +1,686 @@
+use board::*;
+use std::cmp::Ordering;
+
+/// What is known of the label a value will be given
+#[derive(Clone, Copy, Debug)]
+enum Label {
+    /// Not seen in any row placed so far
+    Unseen,
+    Fixed(SquareType),
+    /// First seen in a cell of columns which can still go in any order, so labelled `first` plus
+    /// however far its column ends up from `start`
+    Pending {
+        col: usize,
+        first: SquareType,
+        start: usize,
+    },
+}
+
+/// Columns which have matched in every row placed so far, up to relabelling, so can still go in
+/// any order. They fill the last `cols.len()` positions before `end`.
+#[derive(Clone, Debug)]
+struct Cell {
+    end: usize,
+    cols: Vec<usize>,
+}
+
+impl Cell {
+    /// The first position left for the cell's columns
+    fn next_free(&self) -> usize {
+        self.end - self.cols.len()
+    }
+}
+
+/// Stacks (columns of grids) which have matched in every row placed so far, up to relabelling, so
+/// can still go in any order. They fill the blocks of columns from `first_block` on.
+#[derive(Clone, Debug)]
+struct OpenStacks {
+    first_block: usize,
+    stacks: Vec<usize>,
+}
+
+/// The parts of the column order and relabelling decided by the rows placed so far
+#[derive(Clone, Debug)]
+struct Labelling {
+    box_width: usize,
+    open: Vec<OpenStacks>,
+    cells: Vec<Cell>,
+    /// The column at each position, once decided
+    cols: Vec<Option<usize>>,
+    /// The position of each column, once decided
+    positions: Vec<Option<usize>>,
+    labels: Vec<Label>,
+    next_label: SquareType,
+}
+
+impl Labelling {
+    fn cell_of(&self, col: usize) -> Option<usize> {
+        self.cells.iter().position(|cell| cell.cols.contains(&col))
+    }
+
+    fn open_of(&self, col: usize) -> usize {
+        let stack = col / self.box_width;
+        self.open
+            .iter()
+            .position(|run| run.stacks.contains(&stack))
+            .expect("Every column without a position is in a cell or an open stack")
+    }
+
+    /// The first position left for the column, if it went before the rest of its cell or stacks
+    fn next_free(&self, col: usize) -> usize {
+        match self.cell_of(col) {
+            Some(index) => self.cells[index].next_free(),
+            None => self.open[self.open_of(col)].first_block * self.box_width,
+        }
+    }
+
+    /// Give the column the first position left in its cell, first giving its stack the first block
+    /// left if needed
+    fn place(&mut self, col: usize) {
+        let index = match self.cell_of(col) {
+            Some(index) => index,
+            None => {
+                let run = self.open_of(col);
+                let stack = col / self.box_width;
+                let block = self.open[run].first_block;
+                self.open[run].first_block += 1;
+                self.open[run].stacks.retain(|&other| other != stack);
+                if self.open[run].stacks.is_empty() {
+                    self.open.remove(run);
+                }
+                self.cells.push(Cell {
+                    end: (block + 1) * self.box_width,
+                    cols: (stack * self.box_width..(stack + 1) * self.box_width).collect(),
+                });
+                self.cells.len() - 1
+            }
+        };
+        let pos = self.cells[index].next_free();
+        self.cells[index].cols.retain(|&other| other != col);
+        if self.cells[index].cols.is_empty() {
+            self.cells.remove(index);
+        }
+        self.cols[pos] = Some(col);
+        self.positions[col] = Some(pos);
+    }
+
+    /// Move some columns of a cell into a cell of their own, in front of the others
+    fn split(&mut self, index: usize, cols: &[usize]) {
+        let end = self.cells[index].next_free() + cols.len();
+        self.cells[index].cols.retain(|col| !cols.contains(col));
+        self.cells.push(Cell {
+            end,
+            cols: cols.to_vec(),
+        });
+    }
+}
+
+/// Searches for the smallest string of values (read row by row, with blanks as 0 and values
+/// relabelled in order of appearance) over every row and column ordering of a board.
+///
+/// Columns are put in order one position at a time, taking those which give the smallest next
+/// value. Columns that can't be told apart yet (blank ones, or ones holding values not seen
+/// before) are left to later rows, so blank parts of a puzzle and full rows don't need to be
+/// searched. Orderings are dropped as soon as they give a larger value than the best one found.
+struct CanonicalSearch<'a> {
+    board: &'a Board,
+    /// Rows with the same values in the same band are interchangeable, so only the first is tried.
+    /// Values found nowhere else on the board could be relabelled to match, so are kept as 0.
+    row_keys: Vec<Vec<Option<SquareType>>>,
+    /// Likewise for bands with the same rows
+    band_keys: Vec<Vec<Vec<Option<SquareType>>>>,
+    best: Option<Vec<SquareType>>,
+    /// The values of the ordering being tried
+    prefix: Vec<SquareType>,
+    /// Where the prefix became smaller than the best ordering, if it has
+    ahead_from: Option<usize>,
+}
+
+impl<'a> CanonicalSearch<'a> {
+    fn new(board: &'a Board) -> CanonicalSearch<'a> {
+        let mut counts = vec![0; board.side_length];
+        for val in board.squares.iter().filter_map(|&val| val) {
+            counts[(val - board.min_value) as usize] += 1;
+        }
+        let row_keys: Vec<Vec<Option<SquareType>>> = (0..board.side_length)
+            .map(|row| {
+                board
+                    .get_row_values(row)
+                    .into_iter()
+                    .map(|val| match val {
+                        Some(val) if counts[(val - board.min_value) as usize] == 1 => Some(0),
+                        _ => val,
+                    })
+                    .collect()
+            })
+            .collect();
+        let band_keys = (0..board.num_bands())
+            .map(|band| {
+                let mut rows: Vec<Vec<Option<SquareType>>> = row_keys
+                    [band * board.box_height..(band + 1) * board.box_height]
+                    .to_vec();
+                rows.sort();
+                rows
+            })
+            .collect();
+        CanonicalSearch {
+            board,
+            row_keys,
+            band_keys,
+            best: None,
+            prefix: vec![],
+            ahead_from: None,
+        }
+    }
+
+    /// The smallest string, or the best one given if nothing beats it
+    fn run(mut self, best: Option<Vec<SquareType>>) -> Vec<SquareType> {
+        let width = self.board.side_length;
+        self.best = best;
+        let start = Labelling {
+            box_width: self.board.box_width,
+            open: vec![
+                OpenStacks {
+                    first_block: 0,
+                    stacks: (0..self.board.num_stacks()).collect(),
+                },
+            ],
+            cells: vec![],
+            cols: vec![None; width],
+            positions: vec![None; width],
+            labels: vec![Label::Unseen; width],
+            next_label: 1,
+        };
+        self.search(&mut vec![], start);
+        self.best.expect("There is always at least one ordering")
+    }
+
+    fn value(&self, col: usize, row: usize) -> Option<SquareType> {
+        self.board.squares[(col, row).into_pos(self.board.side_length)]
+    }
+
+    fn label_index(&self, val: SquareType) -> usize {
+        (val - self.board.min_value) as usize
+    }
+
+    /// Add the next value, unless the ordering can then no longer beat the best one
+    fn push(&mut self, value: SquareType) -> bool {
+        if self.ahead_from.is_none() {
+            if let Some(ref best) = self.best {
+                match value.cmp(&best[self.prefix.len()]) {
+                    Ordering::Greater => return false,
+                    Ordering::Less => self.ahead_from = Some(self.prefix.len()),
+                    Ordering::Equal => {}
+                }
+            }
+        }
+        self.prefix.push(value);
+        true
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.prefix.truncate(len);
+        if self.ahead_from.is_some_and(|from| from >= len) {
+            self.ahead_from = None;
+        }
+    }
+
+    /// Try each of the rows which could be placed next
+    fn search(&mut self, placed: &mut Vec<usize>, labelling: Labelling) {
+        if placed.len() == self.board.side_length {
+            // Anything worse would have been dropped, so this is the best so far
+            self.best = Some(self.prefix.clone());
+            self.ahead_from = None;
+            return;
+        }
+        for row in self.candidate_rows(placed) {
+            placed.push(row);
+            let len = self.prefix.len();
+            self.place_row(placed, labelling.clone(), row, 0);
+            self.truncate(len);
+            placed.pop();
+        }
+    }
+
+    /// The rows which could go next, keeping each band's rows together. Rows with more blanks
+    /// are tried first, as they tend to give smaller values.
+    fn candidate_rows(&self, placed: &[usize]) -> Vec<usize> {
+        let height = self.board.box_height;
+        let mut candidates: Vec<usize> = vec![];
+        if placed.len().is_multiple_of(height) {
+            let used_bands: Vec<usize> = placed.iter().map(|row| row / height).collect();
+            let mut tried: Vec<&Vec<Vec<Option<SquareType>>>> = vec![];
+            for band in 0..self.board.num_bands() {
+                if used_bands.contains(&band) || tried.contains(&&self.band_keys[band]) {
+                    continue;
+                }
+                tried.push(&self.band_keys[band]);
+                candidates.extend(band * height..(band + 1) * height);
+            }
+        } else {
+            let band = placed[placed.len() - 1] / height;
+            candidates.extend(band * height..(band + 1) * height);
+        }
+
+        let mut tried: Vec<(usize, &Vec<Option<SquareType>>)> = vec![];
+        let mut candidates: Vec<usize> = candidates
+            .into_iter()
+            .filter(|row| !placed.contains(row))
+            .filter(|&row| {
+                let key = (row / height, &self.row_keys[row]);
+                if tried.contains(&key) {
+                    false
+                } else {
+                    tried.push(key);
+                    true
+                }
+            })
+            .collect();
+        candidates.sort_by_key(|&row| {
+            self.row_keys[row].iter().filter(|val| val.is_some()).count()
+        });
+        candidates
+    }
+
+    /// The columns of a stack, in their original order
+    fn stack_cols(&self, stack: usize) -> Vec<usize> {
+        let box_width = self.board.box_width;
+        (stack * box_width..(stack + 1) * box_width).collect()
+    }
+
+    /// The label the column's value would get if the column went at the position, or 0 if it's
+    /// blank. A pending label's column is taken to go as early as it can.
+    fn label_at(&self, labelling: &Labelling, col: usize, row: usize, pos: usize) -> SquareType {
+        let val = match self.value(col, row) {
+            Some(val) => val,
+            None => return 0,
+        };
+        match labelling.labels[self.label_index(val)] {
+            Label::Unseen => labelling.next_label,
+            Label::Fixed(label) => label,
+            Label::Pending {
+                col: first_col,
+                first,
+                start,
+            } => {
+                let first_pos = if first_col == col {
+                    pos
+                } else if let Some(first_pos) = labelling.positions[first_col] {
+                    first_pos
+                } else if labelling.cell_of(first_col) == labelling.cell_of(col) {
+                    pos + 1
+                } else {
+                    labelling.next_free(first_col)
+                };
+                first + (first_pos - start) as SquareType
+            }
+        }
+    }
+
+    /// Decide the label of the value of a column that has just been given a position, placing the
+    /// column it was first seen in if needed
+    fn settle(&self, labelling: &mut Labelling, col: usize, row: usize) -> SquareType {
+        let val = match self.value(col, row) {
+            Some(val) => val,
+            None => return 0,
+        };
+        let index = self.label_index(val);
+        let label = match labelling.labels[index] {
+            Label::Unseen => {
+                labelling.next_label += 1;
+                labelling.next_label - 1
+            }
+            Label::Fixed(label) => label,
+            Label::Pending {
+                col: first_col,
+                first,
+                start,
+            } => {
+                if labelling.positions[first_col].is_none() {
+                    labelling.place(first_col);
+                }
+                let first_pos = labelling.positions[first_col].expect("The column was just placed");
+                first + (first_pos - start) as SquareType
+            }
+        };
+        labelling.labels[index] = Label::Fixed(label);
+        label
+    }
+
+    /// Place the row from the position on, trying each ordering of the columns which gives the
+    /// smallest values, then go on to the next row
+    fn place_row(
+        &mut self,
+        placed: &mut Vec<usize>,
+        mut labelling: Labelling,
+        row: usize,
+        mut pos: usize,
+    ) {
+        let width = self.board.side_length;
+        let box_width = self.board.box_width;
+        while pos < width {
+            let open = labelling
+                .open
+                .iter()
+                .position(|run| run.first_block * box_width == pos);
+            if let Some(index) = open {
+                let OpenStacks {
+                    first_block,
+                    mut stacks,
+                } = labelling.open.remove(index);
+                let blank: Vec<usize> = stacks
+                    .iter()
+                    .cloned()
+                    .filter(|&stack| {
+                        self.stack_cols(stack)
+                            .into_iter()
+                            .all(|col| self.value(col, row).is_none())
+                    })
+                    .collect();
+                if !blank.is_empty() {
+                    // Blank stacks go first, and can still go in any order
+                    stacks.retain(|stack| !blank.contains(stack));
+                    if !stacks.is_empty() {
+                        labelling.open.push(OpenStacks {
+                            first_block: first_block + blank.len(),
+                            stacks,
+                        });
+                    }
+                    for _ in 0..blank.len() * box_width {
+                        if !self.push(0) {
+                            return;
+                        }
+                    }
+                    pos += blank.len() * box_width;
+                    labelling.open.push(OpenStacks {
+                        first_block,
+                        stacks: blank,
+                    });
+                    continue;
+                }
+
+                // Stacks only holding values not seen before can't give the smallest values
+                // unless they all do, in which case they can still go in any order
+                let unseen: Vec<usize> = stacks
+                    .iter()
+                    .cloned()
+                    .filter(|&stack| {
+                        self.stack_cols(stack).into_iter().all(|col| match self.value(col, row) {
+                            Some(val) => {
+                                matches!(labelling.labels[self.label_index(val)], Label::Unseen)
+                            }
+                            None => false,
+                        })
+                    })
+                    .collect();
+                if unseen.len() == stacks.len() {
+                    for col in stacks.iter().flat_map(|&stack| self.stack_cols(stack)) {
+                        let val = self.value(col, row).expect("The stacks are full");
+                        let index = self.label_index(val);
+                        labelling.labels[index] = Label::Pending {
+                            col,
+                            first: labelling.next_label,
+                            start: pos,
+                        };
+                    }
+                    for _ in 0..stacks.len() * box_width {
+                        labelling.next_label += 1;
+                        let label = labelling.next_label - 1;
+                        if !self.push(label) {
+                            return;
+                        }
+                    }
+                    pos += stacks.len() * box_width;
+                    labelling.open.push(OpenStacks {
+                        first_block,
+                        stacks,
+                    });
+                    continue;
+                }
+
+                // Try each of the other stacks in the first block
+                for &stack in stacks.iter().filter(|stack| !unseen.contains(stack)) {
+                    let mut branch = labelling.clone();
+                    let rest: Vec<usize> = stacks.iter().cloned().filter(|&s| s != stack).collect();
+                    if !rest.is_empty() {
+                        branch.open.push(OpenStacks {
+                            first_block: first_block + 1,
+                            stacks: rest,
+                        });
+                    }
+                    branch.cells.push(Cell {
+                        end: (first_block + 1) * box_width,
+                        cols: self.stack_cols(stack),
+                    });
+                    let len = self.prefix.len();
+                    self.place_row(placed, branch, row, pos);
+                    self.truncate(len);
+                }
+                return;
+            }
+
+            if let Some(col) = labelling.cols[pos] {
+                let label = self.settle(&mut labelling, col, row);
+                if !self.push(label) {
+                    return;
+                }
+                pos += 1;
+                continue;
+            }
+
+            let index = labelling
+                .cells
+                .iter()
+                .position(|cell| cell.next_free() == pos)
+                .expect("Every free position outside the open stacks is in a cell");
+            let labels: Vec<(SquareType, usize)> = labelling.cells[index]
+                .cols
+                .iter()
+                .map(|&col| (self.label_at(&labelling, col, row, pos), col))
+                .collect();
+            let smallest = labels
+                .iter()
+                .map(|&(label, _)| label)
+                .min()
+                .expect("Cells are never empty");
+            let tied: Vec<usize> = labels
+                .into_iter()
+                .filter(|&(label, _)| label == smallest)
+                .map(|(_, col)| col)
+                .collect();
+
+            if smallest == 0 || (smallest == labelling.next_label && tied.len() > 1) {
+                // Blanks, or values not seen before, can still go in any order
+                if tied.len() < labelling.cells[index].cols.len() {
+                    labelling.split(index, &tied);
+                }
+                for &col in &tied {
+                    let label = if smallest == 0 {
+                        0
+                    } else {
+                        let val = self.value(col, row).expect("New values aren't blank");
+                        let index = self.label_index(val);
+                        labelling.labels[index] = Label::Pending {
+                            col,
+                            first: smallest,
+                            start: pos,
+                        };
+                        labelling.next_label += 1;
+                        labelling.next_label - 1
+                    };
+                    if !self.push(label) {
+                        return;
+                    }
+                }
+                pos += tied.len();
+                continue;
+            }
+
+            if tied.len() == 1 {
+                labelling.place(tied[0]);
+                let label = self.settle(&mut labelling, tied[0], row);
+                if !self.push(label) {
+                    return;
+                }
+                pos += 1;
+                continue;
+            }
+
+            // Otherwise try each column that could go here
+            for &col in &tied {
+                let mut branch = labelling.clone();
+                branch.place(col);
+                let label = self.settle(&mut branch, col, row);
+                let len = self.prefix.len();
+                if self.push(label) {
+                    self.place_row(placed, branch, row, pos + 1);
+                }
+                self.truncate(len);
+            }
+            return;
+        }
+        self.search(placed, labelling);
+    }
+}
+
+impl Board {
+    /// The smallest form of this puzzle (reading the values row by row, with blanks as 0) that
+    /// can be reached by relabelling the values, reordering rows within bands and columns within
+    /// stacks, reordering bands and stacks, and transposing.
+    ///
+    /// Two puzzles are equivalent exactly when they have the same canonical form. Boards with
+    /// rectangular grids are turned so that their grids are at least as wide as they are tall.
+    /// Only the values are kept, so the result has no notes and no givens.
+    pub fn canonical_form(&self) -> Board {
+        let mut orientations = vec![self.clone()];
+        if self.box_width < self.box_height {
+            orientations[0].transpose();
+        } else if self.box_width == self.box_height {
+            let mut transposed = self.clone();
+            transposed.transpose();
+            orientations.push(transposed);
+        }
+
+        let mut canonical =
+            Board::with_box_dims(orientations[0].box_width, orientations[0].box_height)
+                .expect("An existing board's size is always supported");
+        let values = orientations
+            .iter()
+            .fold(None, |best, board| Some(CanonicalSearch::new(board).run(best)))
+            .expect("There is always at least one orientation");
+        for (index, val) in values.into_iter().enumerate() {
+            canonical.squares[index] = if val == 0 { None } else { Some(val) };
+        }
+        canonical
+    }
+
+    /// A hash of the canonical form, which is the same for every equivalent puzzle and stays the
+    /// same between runs and versions
+    pub fn fingerprint(&self) -> u64 {
+        // 64-bit FNV-1a
+        let canonical = self.canonical_form();
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let dims = [canonical.box_width as u64, canonical.box_height as u64];
+        let values = canonical.squares.iter().map(|o| o.unwrap_or(0) as u64);
+        for value in dims.iter().cloned().chain(values) {
+            for byte in 0..8 {
+                hash ^= (value >> (byte * 8)) & 0xff;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash
+    }
+
+    /// Whether the other puzzle can be turned into this one by relabelling, reordering and
+    /// transposing
+    pub fn is_equivalent(&self, other: &Board) -> bool {
+        if self.side_length != other.side_length {
+            return false;
+        }
+        let canonical = self.canonical_form();
+        let other_canonical = other.canonical_form();
+        canonical.box_width == other_canonical.box_width && canonical == other_canonical
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, XorShiftRng};
+
+    #[test]
+    fn test_canonical_form_small() {
+        let board = Board::from_string("1,0,0,0,0,0,3,0,0,4,0,0,0,0,0,2");
+        let canonical = board.canonical_form();
+        assert_eq!("0,0,0,1,0,2,0,0,0,0,3,0,4,0,0,0", canonical.to_string());
+        assert_eq!(canonical, canonical.canonical_form());
+
+        let empty = Board::from_base_num(2).unwrap();
+        assert_eq!(empty, empty.canonical_form());
+    }
+
+    #[test]
+    fn test_equivalent_isomorphs() {
+        let mut rng = XorShiftRng::from_seed([4, 3, 2, 1]);
+        let puzzles = [
+            "1,0,0,0,0,0,3,0,0,4,0,0,0,0,0,2",
+            UNSOLVED_PUZZLE_STR,
+            HARD_PUZZLE_STR,
+            UNSOLVED_6_STR,
+            _UNSOLVED_16,
+        ];
+        for puzzle in puzzles.iter() {
+            let board = Board::from_string(puzzle);
+            let fingerprint = board.fingerprint();
+            for _ in 0..3 {
+                let mut isomorph = board.random_isomorph(&mut rng);
+                isomorph.rotate_clockwise();
+                assert!(board.is_equivalent(&isomorph));
+                assert_eq!(fingerprint, isomorph.fingerprint());
+            }
+        }
+    }
+
+    #[test]
+    fn test_not_equivalent() {
+        let board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let mut changed = board.clone();
+        changed.set_val((4, 0), Some(5)).unwrap();
+        assert!(!board.is_equivalent(&changed));
+        assert_ne!(board.fingerprint(), changed.fingerprint());
+        assert!(!board.is_equivalent(&Board::from_string(UNSOLVED_6_STR)));
+    }
+
+    #[test]
+    fn test_canonical_solved() {
+        let board = Board::from_string(SOLVED_PUZZLE_STR);
+        let canonical = board.canonical_form();
+        // A full grid always starts with every value in order
+        assert_eq!(
+            (1..10).map(Some).collect::<Vec<_>>(),
+            canonical.get_row_values(0)
+        );
+        let mut swapped = board.clone();
+        swapped.swap_bands(0, 2).unwrap();
+        swapped.permute_digits(&[2, 3, 4, 5, 6, 7, 8, 9, 1]).unwrap();
+        assert_eq!(canonical, swapped.canonical_form());
+    }
+
+    #[test]
+    fn test_canonical_solved_16() {
+        let mut board = Board::from_string(_UNSOLVED_16);
+        assert!(board.solve_search_parallel());
+        let canonical = board.canonical_form();
+        assert_eq!(
+            (1..17).map(Some).collect::<Vec<_>>(),
+            canonical.get_row_values(0)
+        );
+
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let mut isomorph = board.random_isomorph(&mut rng);
+        isomorph.rotate_clockwise();
+        assert_eq!(canonical, isomorph.canonical_form());
+        assert_eq!(board.fingerprint(), isomorph.fingerprint());
+    }
+}
@@ -0,0 +1,101 @@
+use board::{Coord, ParseError, Pos, SquareType};
+use std::{error, fmt, result};
+
+/// Errors that can be returned from operations on a `Board`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The board would have more values than a square's candidates can hold
+    TooLarge { side_length: usize, max: usize },
+    /// The position is not within the board
+    OutOfRange { pos: Pos, num_squares: usize },
+    /// The coordinates are not within the board
+    CoordOutOfRange { coord: Coord, side_length: usize },
+    /// The value is outside of the board's range of values
+    InvalidValue {
+        value: SquareType,
+        min: SquareType,
+        max: SquareType,
+    },
+    /// The square holds a given, which can't be changed
+    GivenSquare { pos: Pos },
+    /// Rows, columns, bands or stacks that can't be swapped without breaking the board's grids
+    InvalidSwap { first: usize, second: usize },
+    /// The values can't be relabelled, as the mapping isn't a permutation of the board's values
+    InvalidPermutation,
+    /// A new board could not be generated
+    GenerationFailed,
+    /// There is no solving algorithm with this name
+    UnknownAlgorithm(String),
+    /// There is no solving technique with this name
+    UnknownTechnique(String),
+    /// A board could not be read from a string
+    Parse(ParseError),
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::TooLarge { side_length, max } => write!(
+                f,
+                "a side length of {} is too large, at most {} is supported",
+                side_length,
+                max
+            ),
+            Error::OutOfRange { pos, num_squares } => write!(
+                f,
+                "position {} is out of range for a board of {} squares",
+                pos,
+                num_squares
+            ),
+            Error::CoordOutOfRange {
+                coord: (col, row),
+                side_length,
+            } => write!(
+                f,
+                "coordinates ({}, {}) are out of range for a {}x{} board",
+                col,
+                row,
+                side_length,
+                side_length
+            ),
+            Error::InvalidValue { value, min, max } => write!(
+                f,
+                "value {} is invalid, must be between {} and {}",
+                value,
+                min,
+                max
+            ),
+            Error::GivenSquare { pos } => {
+                write!(f, "square {} is a given and can not be changed", pos)
+            }
+            Error::InvalidSwap { first, second } => write!(
+                f,
+                "unable to swap {} and {} without breaking the grids",
+                first,
+                second
+            ),
+            Error::InvalidPermutation => write!(f, "mapping is not a permutation of the values"),
+            Error::GenerationFailed => write!(f, "unable to generate a board"),
+            Error::UnknownAlgorithm(ref name) => write!(f, "unknown solving algorithm {}", name),
+            Error::UnknownTechnique(ref name) => write!(f, "unknown solving technique {}", name),
+            Error::Parse(ref err) => write!(f, "unable to parse board: {}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Parse(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Error {
+        Error::Parse(err)
+    }
+}
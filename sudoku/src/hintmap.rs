@@ -1,113 +1,111 @@
 use board::*;
-use std::collections::HashMap;
+use candidates::Candidates;
 
 
+/// The candidate values for every square on a board, stored densely by index.
+/// Squares that already have a value have no candidates.
 pub struct HintMap {
-    pub hints: HashMap<usize, Vec<SquareType>>,
+    pub candidates: Vec<Candidates>,
     pub side_length: usize,
+    pub box_width: usize,
+    pub box_height: usize,
 }
 
 
+impl Board {
+    /// Replace the player's notes with the candidates of every blank square
+    pub fn fill_notes(&mut self) {
+        self.notes = HintMap::from_board(self).candidates;
+    }
+}
+
 impl HintMap {
     pub fn from_board(board: &Board) -> HintMap {
-        let mut hints: HashMap<usize, Vec<SquareType>> = HashMap::new();
         let num_groups = board.side_length;
 
-        //Store the computed values in order to save time
-        let mut rows = HashMap::new();
-        let mut cols = HashMap::new();
-        let mut grids = HashMap::new();
-
-        let vals: Vec<SquareType> = (board.min_value..board.max_value + 1).collect();
-
-        'outer: for (pt, val) in board.squares.iter().enumerate() {
-            if val.is_some() {
-                continue 'outer;
-            }
-            //Create a vector full of all values
-            let mut vals = vals.clone();
-
-            let row_num = board.get_row_num(pt);
-            let col_num = board.get_col_num(pt);
-            let grid_num = board.get_grid_num(pt);
-
-            if !rows.contains_key(&row_num) {
-                rows.insert(row_num, board.get_row_values(row_num));
-            }
-
-            if !cols.contains_key(&col_num) {
-                cols.insert(col_num, board.get_col_values(col_num));
-            }
-
-            if !grids.contains_key(&grid_num) {
-                grids.insert(grid_num, board.get_grid_values(grid_num));
-            }
+        //Collect the values already used in each row, column and grid
+        let mut rows = vec![Candidates::empty(); num_groups];
+        let mut cols = vec![Candidates::empty(); num_groups];
+        let mut grids = vec![Candidates::empty(); num_groups];
+        let grid_nums: Vec<usize> = (0..board.num_squares)
+            .map(|pt| board.get_grid_num(pt))
+            .collect();
 
-            let row_vals = rows.get(&row_num).unwrap();
-            let col_vals = cols.get(&col_num).unwrap();
-            let grid_vals = grids.get(&grid_num).unwrap();
-
-            'forloop: for i in 0..num_groups {
-                if vals.len() == 0 {
-                    break 'forloop;
-                }
-                if let Some(val) = row_vals[i] {
-                    vals.retain(|&o| o != val);
-                }
-                if let Some(val) = col_vals[i] {
-                    vals.retain(|&o| o != val);
-                }
-                if let Some(val) = grid_vals[i] {
-                    vals.retain(|&o| o != val);
-                }
+        for (pt, val) in board.squares.iter().enumerate() {
+            if let Some(val) = *val {
+                rows[board.get_row_num(pt)].insert(val);
+                cols[board.get_col_num(pt)].insert(val);
+                grids[grid_nums[pt]].insert(val);
             }
-            hints.insert(pt, vals);
         }
 
+        let all_vals = Candidates::all(board.max_value);
+        let candidates = board
+            .squares
+            .iter()
+            .enumerate()
+            .map(|(pt, val)| if val.is_some() {
+                Candidates::empty()
+            } else {
+                let used = rows[board.get_row_num(pt)] | cols[board.get_col_num(pt)] |
+                    grids[grid_nums[pt]];
+                all_vals - used
+            })
+            .collect();
+
         HintMap {
-            hints,
+            candidates,
             side_length: board.side_length,
+            box_width: board.box_width,
+            box_height: board.box_height,
         }
     }
 
 
-    pub fn get_hints<T: Position>(&self, pt: T) -> Vec<SquareType> {
+    pub fn get_candidates<T: Position>(&self, pt: T) -> Candidates {
         let pt = pt.into_pos(self.side_length); //Make sure is index, not coords
-        if let Some(hints) = self.hints.get(&pt) {
-            hints.clone()
-        } else {
-            vec![]
+        match self.candidates.get(pt) {
+            Some(candidates) => *candidates,
+            None => Candidates::empty(),
         }
     }
 
-    pub fn get_ordered(&self) -> Vec<(Pos, Vec<SquareType>)> {
-        let mut retval: Vec<(Pos, Vec<SquareType>)> = self.hints
+    pub fn get_hints<T: Position>(&self, pt: T) -> Vec<SquareType> {
+        self.get_candidates(pt).to_vec()
+    }
+
+    /// All squares with candidates, from the fewest candidates to the most
+    pub fn get_ordered(&self) -> Vec<(Pos, Candidates)> {
+        let mut retval: Vec<(Pos, Candidates)> = self.candidates
             .iter()
-            .map(|(key, value)| (*key, value.clone()))
-            .filter(|&(_, ref value)| value.len() > 0)
+            .enumerate()
+            .map(|(pos, candidates)| (pos, *candidates))
+            .filter(|&(_, candidates)| !candidates.is_empty())
             .collect();
-        retval.sort_by(|&(_, ref a1), &(_, ref a2)| a1.len().cmp(&a2.len()));
+        retval.sort_by_key(|&(_, candidates)| candidates.len());
 
         retval
     }
 
-    pub fn find_square_answer(&self, square_index: Pos) -> Option<SquareType> {
-        let square_hints = self.hints.get(&square_index);
-
-        if square_hints.is_none() {
-            return None;
+    /// Removes the candidates of every other square in `indices` from `hints`, leaving the values
+    /// which can only go in `square_index`.
+    fn hidden_in(&self, square_index: Pos, hints: Candidates, indices: Vec<Pos>) -> Candidates {
+        let mut hints = hints;
+        for other_index in indices {
+            if square_index != other_index {
+                hints -= self.candidates[other_index];
+            }
         }
+        hints
+    }
 
-        let square_hints = square_hints.unwrap();
-
-        // This would also indicate that the board is invalid
-        if square_hints.len() == 0 {
-            return None;
-        }
+    pub fn find_square_answer(&self, square_index: Pos) -> Option<SquareType> {
+        let square_hints = self.get_candidates(square_index);
 
-        // There is a single hint - this is the only possible value
-        if square_hints.len() == 1 {
-            return Some(square_hints[0]);
+        // An empty set would also indicate that the board is invalid
+        if square_hints.len() < 2 {
+            // There is a single hint - this is the only possible value
+            return square_hints.single_value();
         }
 
         let side_length = self.side_length;
@@ -115,58 +113,27 @@ impl HintMap {
         // Solve Hidden Singles
 
         // Row
-        let mut hints = square_hints.clone();
         let row_num = square_index.row_num(side_length);
         let row_indices = get_row_indices(row_num, side_length);
-        for other_index in row_indices {
-            if square_index != other_index {
-                let other_hints = self.get_hints(other_index);
-                for other_hint in other_hints {
-                    if let Ok(hint_index) = hints.binary_search(&other_hint) {
-                        hints.remove(hint_index);
-                    }
-                }
-            }
-        }
-        if hints.len() == 1 {
-            return Some(hints[0]);
+        let hints = self.hidden_in(square_index, square_hints, row_indices);
+        if let Some(val) = hints.single_value() {
+            return Some(val);
         }
 
         // Column
-        let mut hints = square_hints.clone();
         let col_num = square_index.col_num(side_length);
         let col_indices = get_col_indices(col_num, side_length);
-        for other_index in col_indices {
-            if square_index != other_index {
-                let other_hints = self.get_hints(other_index);
-                for other_hint in other_hints {
-                    if let Ok(hint_index) = hints.binary_search(&other_hint) {
-                        hints.remove(hint_index);
-                    }
-                }
-            }
-        }
-        if hints.len() == 1 {
-            return Some(hints[0]);
+        let hints = self.hidden_in(square_index, square_hints, col_indices);
+        if let Some(val) = hints.single_value() {
+            return Some(val);
         }
 
         // Grid
-        let base_num = (side_length as f64).sqrt() as usize;
-        let mut hints = square_hints.clone();
-        let grid_num = square_index.grid_num(side_length, base_num);
-        let grid_indices = get_grid_indices(grid_num, side_length);
-        for other_index in grid_indices {
-            if square_index != other_index {
-                let other_hints = self.get_hints(other_index);
-                for other_hint in other_hints {
-                    if let Ok(hint_index) = hints.binary_search(&other_hint) {
-                        hints.remove(hint_index);
-                    }
-                }
-            }
-        }
-        if hints.len() == 1 {
-            return Some(hints[0]);
+        let grid_num = square_index.grid_num(side_length, self.box_width, self.box_height);
+        let grid_indices = get_grid_indices(grid_num, self.box_width, self.box_height);
+        let hints = self.hidden_in(square_index, square_hints, grid_indices);
+        if let Some(val) = hints.single_value() {
+            return Some(val);
         }
 
         None
@@ -184,6 +151,36 @@ mod tests {
         let hmap = HintMap::from_board(&unsolved);
         assert_eq!(vec![9], hmap.get_hints(9));
     }
+
+    #[test]
+    fn test_fill_notes() {
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        board.fill_notes();
+        assert_eq!(vec![9], board.get_notes(9).unwrap().to_vec());
+        assert!(board.get_notes(0).unwrap().is_empty());
+
+        board.toggle_note(9, 9).unwrap();
+        assert!(board.get_notes(9).unwrap().is_empty());
+        board.fill_notes();
+        assert_eq!(HintMap::from_board(&board).candidates, board.notes);
+    }
+
+    #[test]
+    fn test_hint_map_81() {
+        let board = Board::from_string(include_str!("../../puzzles/81hard.txt"));
+        let hmap = HintMap::from_board(&board);
+        for index in 0..board.num_squares {
+            let candidates = hmap.get_candidates(index);
+            if board.squares[index].is_some() {
+                assert!(candidates.is_empty());
+                continue;
+            }
+            let row_num = board.get_row_num(index);
+            for val in board.get_row_values(row_num).into_iter().flatten() {
+                assert!(!candidates.contains(val));
+            }
+        }
+    }
 }
 
 
@@ -201,7 +198,30 @@ mod bench {
 
     #[bench]
     fn bench_hint_map_09_blank(b: &mut Bencher) {
-        let _unsolved = Board::from_base_num(3);
+        let _unsolved = Board::from_base_num(3).unwrap();
+        b.iter(|| { let _hmap = HintMap::from_board(&_unsolved); });
+    }
+
+    #[bench]
+    fn bench_hint_map_25_unsolved(b: &mut Bencher) {
+        let _unsolved = Board::from_string(include_str!("../../puzzles/25u.txt"));
+        b.iter(|| { let _hmap = HintMap::from_board(&_unsolved); });
+    }
+
+    #[bench]
+    fn bench_hint_map_36_unsolved(b: &mut Bencher) {
+        let _unsolved = Board::from_string(include_str!("../../puzzles/36u.txt"));
         b.iter(|| { let _hmap = HintMap::from_board(&_unsolved); });
     }
+
+    #[bench]
+    fn bench_find_square_answer_25_unsolved(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../puzzles/25u.txt"));
+        let hmap = HintMap::from_board(&unsolved);
+        b.iter(|| {
+            (0..unsolved.num_squares)
+                .filter_map(|index| hmap.find_square_answer(index))
+                .count()
+        });
+    }
 }
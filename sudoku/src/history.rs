@@ -0,0 +1,307 @@
+use board::*;
+use candidates::Candidates;
+use std::ops::Deref;
+
+/// The kind of change that a move made to the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveKind {
+    Set,
+    Clear,
+    Note,
+    Solve,
+    Generate,
+    Transform,
+    /// Replacing the board with a different puzzle, e.g. loading one from a file
+    Load,
+    /// Any other change, such as locking the givens or randomising a row
+    Edit,
+}
+
+/// Everything stored about a single square
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SquareState {
+    val: Option<SquareType>,
+    origin: Origin,
+    notes: Candidates,
+}
+
+impl SquareState {
+    fn from_board(board: &Board, index: Pos) -> SquareState {
+        SquareState {
+            val: board.squares[index],
+            origin: board.origins[index],
+            notes: board.notes[index],
+        }
+    }
+
+    fn apply(&self, board: &mut Board, index: Pos) {
+        board.squares[index] = self.val;
+        board.origins[index] = self.origin;
+        board.notes[index] = self.notes;
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Change {
+    /// The index of each changed square, with its state before and after the move
+    Squares(Vec<(Pos, SquareState, SquareState)>),
+    /// The whole board was replaced by one with different dimensions
+    Board { before: Box<Board>, after: Box<Board> },
+}
+
+/// A single reversible change to the board
+#[derive(Debug, Clone)]
+pub struct Move {
+    pub kind: MoveKind,
+    change: Change,
+}
+
+impl Move {
+    /// Work out the move that turns one board into another, if anything changed
+    fn between(kind: MoveKind, before: &Board, after: &Board) -> Option<Move> {
+        let change = if before.box_width != after.box_width ||
+            before.box_height != after.box_height
+        {
+            Change::Board {
+                before: Box::new(before.clone()),
+                after: Box::new(after.clone()),
+            }
+        } else {
+            let changes: Vec<(Pos, SquareState, SquareState)> = (0..before.num_squares)
+                .map(|index| {
+                    (
+                        index,
+                        SquareState::from_board(before, index),
+                        SquareState::from_board(after, index),
+                    )
+                })
+                .filter(|change| change.1 != change.2)
+                .collect();
+            if changes.is_empty() {
+                return None;
+            }
+            Change::Squares(changes)
+        };
+        Some(Move { kind, change })
+    }
+
+    /// Number of squares changed by this move
+    pub fn num_changed(&self) -> usize {
+        match self.change {
+            Change::Squares(ref changes) => changes.len(),
+            Change::Board { ref after, .. } => after.num_squares,
+        }
+    }
+
+    fn undo(&self, board: &mut Board) {
+        match self.change {
+            Change::Squares(ref changes) => for &(index, ref old, _) in changes {
+                old.apply(board, index);
+            },
+            Change::Board { ref before, .. } => *board = (**before).clone(),
+        }
+    }
+
+    fn redo(&self, board: &mut Board) {
+        match self.change {
+            Change::Squares(ref changes) => for &(index, _, ref new) in changes {
+                new.apply(board, index);
+            },
+            Change::Board { ref after, .. } => *board = (**after).clone(),
+        }
+    }
+}
+
+/// A `Board` along with the history of moves made on it, which can be undone and redone.
+///
+/// Read-only access to the board is given through `Deref`. Changes are made with `apply`, which
+/// records whatever the change did to the board as a single move.
+#[derive(Clone, Debug)]
+pub struct GameSession {
+    board: Board,
+    moves: Vec<Move>,
+    /// Number of moves currently applied to the board. Moves after this can be redone.
+    position: usize,
+}
+
+impl Default for GameSession {
+    fn default() -> GameSession {
+        GameSession::new(Board::default())
+    }
+}
+
+impl Deref for GameSession {
+    type Target = Board;
+
+    fn deref(&self) -> &Board {
+        &self.board
+    }
+}
+
+impl From<Board> for GameSession {
+    fn from(board: Board) -> GameSession {
+        GameSession::new(board)
+    }
+}
+
+impl GameSession {
+    pub fn new(board: Board) -> GameSession {
+        GameSession {
+            board,
+            moves: vec![],
+            position: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+
+    /// Make a change to the board, recording it as a move if anything changed.
+    /// Any moves that had been undone can no longer be redone.
+    pub fn apply<R, F: FnOnce(&mut Board) -> R>(&mut self, kind: MoveKind, change: F) -> R {
+        let before = self.board.clone();
+        let result = change(&mut self.board);
+        if let Some(new_move) = Move::between(kind, &before, &self.board) {
+            self.moves.truncate(self.position);
+            self.moves.push(new_move);
+            self.position += 1;
+        }
+        result
+    }
+
+    /// Replace the board with a different one
+    pub fn replace(&mut self, kind: MoveKind, board: Board) {
+        self.apply(kind, |old_board| *old_board = board);
+    }
+
+    /// All recorded moves, including any that have been undone
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Number of moves currently applied to the board
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position < self.moves.len()
+    }
+
+    /// Undo the last move, returning it if there was one
+    pub fn undo(&mut self) -> Option<MoveKind> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.position -= 1;
+        self.moves[self.position].undo(&mut self.board);
+        Some(self.moves[self.position].kind)
+    }
+
+    /// Redo the last undone move, returning it if there was one
+    pub fn redo(&mut self) -> Option<MoveKind> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.moves[self.position].redo(&mut self.board);
+        self.position += 1;
+        Some(self.moves[self.position - 1].kind)
+    }
+
+    /// Undo or redo moves until `position` moves are applied.
+    /// Returns false if there aren't that many moves.
+    pub fn jump_to(&mut self, position: usize) -> bool {
+        if position > self.moves.len() {
+            return false;
+        }
+        while self.position > position {
+            self.undo();
+        }
+        while self.position < position {
+            self.redo();
+        }
+        true
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut session = GameSession::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+        assert!(!session.can_undo());
+        assert_eq!(None, session.undo());
+
+        session
+            .apply(MoveKind::Set, |board| board.set_val((4, 0), Some(9)))
+            .unwrap();
+        session.apply(MoveKind::Note, |board| board.toggle_note((6, 0), 3)).unwrap();
+        assert_eq!(2, session.moves().len());
+        assert_eq!(1, session.moves()[0].num_changed());
+
+        assert_eq!(Some(MoveKind::Note), session.undo());
+        assert!(session.get_notes((6, 0)).unwrap().is_empty());
+        assert_eq!(Some(MoveKind::Set), session.undo());
+        assert_eq!(UNSOLVED_PUZZLE_STR, session.to_string());
+
+        assert_eq!(Some(MoveKind::Set), session.redo());
+        assert_eq!(Some(9), session.get_val((4, 0)).unwrap());
+        assert_eq!(Some(MoveKind::Note), session.redo());
+        assert_eq!(None, session.redo());
+
+        // A new move drops the moves that could have been redone
+        session.undo();
+        session.apply(MoveKind::Clear, |board| board.set_val((4, 0), None)).unwrap();
+        assert_eq!(2, session.moves().len());
+        assert!(!session.can_redo());
+    }
+
+    #[test]
+    fn test_unchanged_not_recorded() {
+        let mut session = GameSession::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+        assert!(session.apply(MoveKind::Set, |board| board.set_val(81, Some(1))).is_err());
+        session.apply(MoveKind::Solve, |board| board.check_status());
+        assert!(session.moves().is_empty());
+    }
+
+    #[test]
+    fn test_undo_restores_pruned_notes() {
+        let mut session = GameSession::default();
+        session.apply(MoveKind::Note, |board| board.toggle_note((0, 1), 2)).unwrap();
+        session.apply(MoveKind::Set, |board| board.set_val((0, 0), Some(2))).unwrap();
+        assert!(session.get_notes((0, 1)).unwrap().is_empty());
+        session.undo();
+        assert_eq!(vec![2], session.get_notes((0, 1)).unwrap().to_vec());
+    }
+
+    #[test]
+    fn test_jump_to() {
+        let mut session = GameSession::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+        session.apply(MoveKind::Solve, |board| board.solve_standard());
+        session.replace(MoveKind::Load, Board::from_base_num(2).unwrap());
+        session.apply(MoveKind::Set, |board| board.set_val(0, Some(1))).unwrap();
+        assert_eq!(16, session.num_squares);
+
+        assert!(session.jump_to(1));
+        assert!(session.check_status().is_solved());
+        assert_eq!(SOLVED_PUZZLE_STR, session.to_string());
+        assert!(session.jump_to(0));
+        assert_eq!(UNSOLVED_PUZZLE_STR, session.to_string());
+        assert!(session.jump_to(3));
+        assert_eq!(Some(1), session.get_val(0).unwrap());
+        assert!(!session.jump_to(4));
+        assert_eq!(3, session.position());
+    }
+}
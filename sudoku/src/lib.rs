@@ -6,7 +6,14 @@ extern crate test;
 pub mod board;
 pub mod solver;
 pub mod hintmap;
+pub mod candidates;
+pub mod candidate_board;
 pub mod generator;
+pub mod history;
+pub mod transform;
+pub mod canonical;
+pub mod error;
 extern crate rand;
 extern crate scoped_threadpool;
 
+pub use error::{Error, Result};
@@ -0,0 +1,524 @@
+use board::*;
+use error::Error;
+use solver::BoardStatus;
+use solver::budget::{AbortReason, Budget, CancelToken};
+use solver::dfs::{DfsStatus, SquareOrder, ValueOrder};
+use solver::parallel::default_num_threads;
+use solver::stats::SolveStats;
+use solver::technique::Technique;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Settings shared by every `Solver`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveOptions {
+    /// Check that the board has only one solution, giving `MultipleSolutions` if it doesn't
+    pub check_unique: bool,
+    /// How many guesses deep a search can go before giving up. Only used by `Algorithm::Dfs`.
+    pub max_depth: Option<usize>,
+    /// How `Algorithm::Dfs` picks the square to guess next
+    pub square_order: SquareOrder,
+    /// How `Algorithm::Dfs` orders the values it guesses
+    pub value_order: ValueOrder,
+    /// Number of worker threads for the parallel algorithms, or `None` for one per core
+    pub num_threads: Option<usize>,
+    /// Give up once this much time has passed
+    pub timeout: Option<Duration>,
+    /// Give up after visiting this many nodes of a search
+    pub max_nodes: Option<usize>,
+    /// Give up once this token is cancelled
+    pub cancel: Option<CancelToken>,
+    /// Techniques `Algorithm::Standard` can use, in the order to try them. `None` keeps to the
+    /// singles of `Board::solve_standard`.
+    pub techniques: Option<Vec<Technique>>,
+    /// Longest chain, in links, the chaining techniques look for. `None` uses
+    /// `DEFAULT_MAX_CHAIN_LENGTH`.
+    pub max_chain_length: Option<usize>,
+    /// Most squares in an almost locked set the ALS techniques look for. `None` uses
+    /// `DEFAULT_MAX_ALS_SIZE`.
+    pub max_als_size: Option<usize>,
+}
+
+/// The result of running a `Solver` over a board
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveOutcome {
+    Solved(Board),
+    /// The solver couldn't finish the board. Holds the values it managed to fill in.
+    Stuck(Board),
+    /// The board breaks the rules, or can't be filled in without breaking them
+    NoSolution,
+    /// The board has more than one solution, so isn't a proper puzzle
+    MultipleSolutions,
+    /// The solver was stopped before it finished. Holds the board with the most squares filled in
+    /// that it had reached, which may include guesses that turn out to be wrong.
+    Aborted { reason: AbortReason, best: Board },
+}
+
+impl SolveOutcome {
+    pub fn is_solved(&self) -> bool {
+        matches!(*self, SolveOutcome::Solved(_))
+    }
+
+    /// The solved or partly solved board, if there is one
+    pub fn board(&self) -> Option<&Board> {
+        match *self {
+            SolveOutcome::Solved(ref board) | SolveOutcome::Stuck(ref board) => Some(board),
+            _ => None,
+        }
+    }
+
+    pub fn into_board(self) -> Option<Board> {
+        match self {
+            SolveOutcome::Solved(board) | SolveOutcome::Stuck(board) => Some(board),
+            _ => None,
+        }
+    }
+
+    /// Turn the status left by one of the `Board::solve_*` methods into an outcome
+    fn from_status(status: BoardStatus, board: Board) -> SolveOutcome {
+        match status {
+            BoardStatus::Solved => SolveOutcome::Solved(board),
+            BoardStatus::Valid => SolveOutcome::Stuck(board),
+            BoardStatus::Invalid => SolveOutcome::NoSolution,
+        }
+    }
+}
+
+impl fmt::Display for SolveOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            SolveOutcome::Solved(_) => "solved",
+            SolveOutcome::Stuck(_) => "unable to solve any further",
+            SolveOutcome::NoSolution => "no solution",
+            SolveOutcome::MultipleSolutions => "more than one solution",
+            SolveOutcome::Aborted { reason, .. } => {
+                return write!(f, "stopped early, {}", reason)
+            }
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// A way of solving boards
+pub trait Solver {
+    /// Short name for the solver, as used to choose it from the command line
+    fn name(&self) -> &str;
+
+    /// Solve a copy of the board, along with what the solver did to get there
+    fn solve_with_stats(
+        &self,
+        board: &Board,
+        options: &SolveOptions,
+    ) -> (SolveOutcome, SolveStats);
+
+    /// Solve a copy of the board
+    fn solve(&self, board: &Board, options: &SolveOptions) -> SolveOutcome {
+        self.solve_with_stats(board, options).0
+    }
+}
+
+/// Every solving algorithm provided by `Board`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// `Board::solve_standard`
+    Standard,
+    /// `Board::solve_ordered`
+    Ordered,
+    /// `Board::solve_parallel`
+    Parallel,
+    /// `Board::solve_search`
+    Search,
+    /// `Board::solve_search_parallel_with`
+    SearchParallel,
+    /// `Board::solve_dfs`
+    Dfs,
+    /// `Board::solve_dlx`
+    Dlx,
+}
+
+impl Algorithm {
+    pub fn all() -> &'static [Algorithm] {
+        &[
+            Algorithm::Standard,
+            Algorithm::Ordered,
+            Algorithm::Parallel,
+            Algorithm::Search,
+            Algorithm::SearchParallel,
+            Algorithm::Dfs,
+            Algorithm::Dlx,
+        ]
+    }
+
+    pub fn description(&self) -> &'static str {
+        match *self {
+            Algorithm::Standard => "Fill in values with known techniques.",
+            Algorithm::Ordered => "Known techniques, trying the squares with fewest hints first.",
+            Algorithm::Parallel => "Known techniques (multithreaded).",
+            Algorithm::Search => "Recursively guess values, using known techniques after each.",
+            Algorithm::SearchParallel => "Recursive search (multithreaded).",
+            Algorithm::Dfs => "Depth first search, backtracking out of wrong guesses.",
+            Algorithm::Dlx => "Solve as an exact cover problem with Dancing Links.",
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Algorithm, Error> {
+        Algorithm::all()
+            .iter()
+            .find(|algorithm| algorithm.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownAlgorithm(name.to_string()))
+    }
+}
+
+impl Solver for Algorithm {
+    fn name(&self) -> &str {
+        match *self {
+            Algorithm::Standard => "standard",
+            Algorithm::Ordered => "ordered",
+            Algorithm::Parallel => "parallel",
+            Algorithm::Search => "search",
+            Algorithm::SearchParallel => "search-parallel",
+            Algorithm::Dfs => "dfs",
+            Algorithm::Dlx => "dlx",
+        }
+    }
+
+    fn solve_with_stats(
+        &self,
+        board: &Board,
+        options: &SolveOptions,
+    ) -> (SolveOutcome, SolveStats) {
+        let start = Instant::now();
+        let budget = Budget::new(options);
+        let num_threads = match *self {
+            Algorithm::Parallel | Algorithm::SearchParallel => {
+                options.num_threads.unwrap_or_else(default_num_threads)
+            }
+            _ => 1,
+        };
+        let outcome = self.solve_within(board, options, &budget, num_threads);
+        let stats = SolveStats {
+            elapsed: start.elapsed(),
+            num_threads,
+            ..budget.stats()
+        };
+        (outcome, stats)
+    }
+}
+
+impl Algorithm {
+    fn solve_within(
+        &self,
+        board: &Board,
+        options: &SolveOptions,
+        budget: &Budget,
+        num_threads: usize,
+    ) -> SolveOutcome {
+        if board.check_status() == BoardStatus::Invalid {
+            return SolveOutcome::NoSolution;
+        }
+
+        let mut working = board.clone();
+        let mut reason = None;
+        let outcome = match *self {
+            Algorithm::Standard => {
+                let status = match options.techniques {
+                    Some(ref techniques) => {
+                        working.solve_standard_with_within(techniques, options, budget)
+                    }
+                    None => working.solve_standard_within(budget),
+                };
+                SolveOutcome::from_status(status, working)
+            }
+            Algorithm::Ordered => {
+                SolveOutcome::from_status(working.solve_ordered_within(budget), working)
+            }
+            Algorithm::Parallel => {
+                let status = working.solve_parallel_within(num_threads, budget);
+                SolveOutcome::from_status(status, working)
+            }
+            Algorithm::Search | Algorithm::SearchParallel | Algorithm::Dlx => {
+                let solved = match *self {
+                    Algorithm::Search => working.solve_search_within(budget),
+                    Algorithm::SearchParallel => {
+                        working.solve_search_parallel_within(num_threads, budget)
+                    }
+                    _ => working.solve_dlx_within(budget),
+                };
+                if solved {
+                    SolveOutcome::Solved(working)
+                } else {
+                    SolveOutcome::NoSolution
+                }
+            }
+            Algorithm::Dfs => match working.solve_dfs_within(options, budget) {
+                DfsStatus::Solved => SolveOutcome::Solved(working),
+                DfsStatus::NoSolution => SolveOutcome::NoSolution,
+                DfsStatus::DepthLimitReached => {
+                    reason = Some(AbortReason::DepthLimit);
+                    SolveOutcome::NoSolution
+                }
+                DfsStatus::Aborted(_) => SolveOutcome::NoSolution,
+            },
+        };
+
+        // Anything short of a solution doesn't count once the solver has been stopped
+        if let Some(reason) = reason.or_else(|| budget.reason()) {
+            if !outcome.is_solved() {
+                let best = budget.best().unwrap_or_else(|| board.clone());
+                return SolveOutcome::Aborted { reason, best };
+            }
+        }
+
+        if options.check_unique {
+            if let Some(solved) = outcome.board() {
+                let num_solutions = board.count_solutions_dlx_within(2, budget);
+                if let Some(reason) = budget.reason() {
+                    return SolveOutcome::Aborted {
+                        reason,
+                        best: solved.clone(),
+                    };
+                }
+                if num_solutions > 1 {
+                    return SolveOutcome::MultipleSolutions;
+                }
+            }
+        }
+        outcome
+    }
+}
+
+impl Board {
+    /// Solve the board with any `Solver`, keeping whatever values it managed to fill in
+    pub fn solve_with<S: Solver + ?Sized>(
+        &mut self,
+        solver: &S,
+        options: &SolveOptions,
+    ) -> SolveOutcome {
+        self.solve_with_stats(solver, options).0
+    }
+
+    /// `solve_with`, also returning what the solver did
+    pub fn solve_with_stats<S: Solver + ?Sized>(
+        &mut self,
+        solver: &S,
+        options: &SolveOptions,
+    ) -> (SolveOutcome, SolveStats) {
+        let (outcome, stats) = solver.solve_with_stats(self, options);
+        if let Some(board) = outcome.board() {
+            *self = board.clone();
+        }
+        (outcome, stats)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solver::technique::Technique;
+
+    #[test]
+    fn test_algorithms_solve() {
+        let board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let solved = Board::from_string(SOLVED_PUZZLE_STR);
+        let options = SolveOptions::default();
+        for algorithm in Algorithm::all() {
+            assert_eq!(SolveOutcome::Solved(solved.clone()), algorithm.solve(&board, &options));
+            assert_eq!(Ok(*algorithm), algorithm.name().parse());
+        }
+        assert!("quantum".parse::<Algorithm>().is_err());
+    }
+
+    #[test]
+    fn test_solve_outcomes() {
+        let options = SolveOptions::default();
+        let bad_mini = Board::from_string("4,2,0,0,1,3,0,0,0,0,3,2,0,0,1,4");
+        assert_eq!(SolveOutcome::NoSolution, Algorithm::Dlx.solve(&bad_mini, &options));
+
+        let mut conflicting = Board::default();
+        conflicting.set_val(0, Some(1)).unwrap();
+        conflicting.set_val(1, Some(1)).unwrap();
+        for algorithm in Algorithm::all() {
+            assert_eq!(SolveOutcome::NoSolution, algorithm.solve(&conflicting, &options));
+        }
+
+        // Known techniques can't get anywhere on a blank board
+        match Algorithm::Standard.solve(&Board::default(), &options) {
+            SolveOutcome::Stuck(board) => assert_eq!(Board::default(), board),
+            outcome => panic!("Expected to get stuck, got {:?}", outcome),
+        }
+
+        let unique = SolveOptions {
+            check_unique: true,
+            ..SolveOptions::default()
+        };
+        let hard = Board::from_string(HARD_PUZZLE_STR);
+        assert!(Algorithm::Dlx.solve(&hard, &options).is_solved());
+        let shallow = SolveOptions {
+            max_depth: Some(0),
+            ..SolveOptions::default()
+        };
+        assert_eq!(
+            SolveOutcome::Aborted {
+                reason: AbortReason::DepthLimit,
+                best: hard.clone(),
+            },
+            Algorithm::Dfs.solve(&hard, &shallow)
+        );
+        assert_eq!(SolveOutcome::MultipleSolutions, Algorithm::Dlx.solve(&hard, &unique));
+        let board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        assert!(Algorithm::Search.solve(&board, &unique).is_solved());
+    }
+
+    #[test]
+    fn test_solve_aborted() {
+        let hard = Board::from_string(include_str!("../../../puzzles/16hard.txt"));
+        let few_nodes = SolveOptions {
+            max_nodes: Some(1),
+            num_threads: Some(2),
+            ..SolveOptions::default()
+        };
+        let searches = [
+            Algorithm::Search,
+            Algorithm::SearchParallel,
+            Algorithm::Dfs,
+            Algorithm::Dlx,
+        ];
+        for algorithm in searches.iter() {
+            match algorithm.solve(&hard, &few_nodes) {
+                SolveOutcome::Aborted { reason, best } => {
+                    assert_eq!(AbortReason::NodeLimit, reason);
+                    assert!(best.num_filled() >= hard.num_filled());
+                    assert!(best.check_status().is_valid());
+                }
+                outcome => panic!("{} wasn't stopped, got {:?}", algorithm, outcome),
+            }
+        }
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let cancelled = SolveOptions {
+            cancel: Some(cancel),
+            ..SolveOptions::default()
+        };
+        let timed_out = SolveOptions {
+            timeout: Some(Duration::from_secs(0)),
+            ..SolveOptions::default()
+        };
+        for algorithm in Algorithm::all() {
+            let expected = SolveOutcome::Aborted {
+                reason: AbortReason::Cancelled,
+                best: hard.clone(),
+            };
+            assert_eq!(expected, algorithm.solve(&hard, &cancelled));
+            let expected = SolveOutcome::Aborted {
+                reason: AbortReason::TimedOut,
+                best: hard.clone(),
+            };
+            assert_eq!(expected, algorithm.solve(&hard, &timed_out));
+        }
+
+        // Checking that the solution is unique counts against the limits too
+        let board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let just_enough = (1..)
+            .find(|&num_nodes| {
+                let options = SolveOptions {
+                    max_nodes: Some(num_nodes),
+                    ..SolveOptions::default()
+                };
+                Algorithm::Dlx.solve(&board, &options).is_solved()
+            })
+            .expect("The puzzle can be solved");
+        let unique = SolveOptions {
+            check_unique: true,
+            max_nodes: Some(just_enough),
+            ..SolveOptions::default()
+        };
+        let expected = SolveOutcome::Aborted {
+            reason: AbortReason::NodeLimit,
+            best: Board::from_string(SOLVED_PUZZLE_STR),
+        };
+        assert_eq!(expected, Algorithm::Dlx.solve(&board, &unique));
+        let uniqueness = SolveOptions {
+            techniques: Some(Technique::all().to_vec()),
+            max_nodes: Some(0),
+            ..SolveOptions::default()
+        };
+        let expected = SolveOutcome::Aborted {
+            reason: AbortReason::NodeLimit,
+            best: board.clone(),
+        };
+        assert_eq!(expected, Algorithm::Standard.solve(&board, &uniqueness));
+
+        // An easy puzzle is solved well within the limits
+        let board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let limited = SolveOptions {
+            timeout: Some(Duration::from_secs(60)),
+            max_nodes: Some(1000),
+            cancel: Some(CancelToken::new()),
+            ..SolveOptions::default()
+        };
+        for algorithm in Algorithm::all() {
+            assert!(algorithm.solve(&board, &limited).is_solved());
+        }
+    }
+
+    #[test]
+    fn test_solve_stats() {
+        let options = SolveOptions {
+            num_threads: Some(2),
+            ..SolveOptions::default()
+        };
+        let board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let hard = Board::from_string(HARD_PUZZLE_STR);
+        for algorithm in Algorithm::all() {
+            // The easy puzzle only needs singles, so never has to guess
+            let (outcome, stats) = algorithm.solve_with_stats(&board, &options);
+            assert!(outcome.is_solved());
+            assert_eq!(board.num_remaining(), stats.num_placements());
+            assert_eq!(None, stats.placements.get(&Technique::Guess));
+            assert_eq!(0, stats.num_backtracks);
+            assert_eq!(0, stats.max_depth);
+            let expected_threads = match *algorithm {
+                Algorithm::Parallel | Algorithm::SearchParallel => 2,
+                _ => 1,
+            };
+            assert_eq!(expected_threads, stats.num_threads);
+        }
+
+        for algorithm in [Algorithm::Search, Algorithm::Dfs, Algorithm::Dlx].iter() {
+            let (outcome, stats) = algorithm.solve_with_stats(&hard, &options);
+            assert!(outcome.is_solved());
+            assert!(stats.num_nodes > 0);
+            assert!(stats.max_depth > 0);
+            assert!(stats.placements[&Technique::Guess] > 0);
+            assert!(stats.num_placements() >= hard.num_remaining());
+        }
+
+        // Known techniques can't get anywhere on a blank board, so fill nothing in
+        let (_, stats) = Algorithm::Standard.solve_with_stats(&Board::default(), &options);
+        assert_eq!(0, stats.num_placements());
+        assert_eq!(0, stats.num_nodes);
+    }
+
+    #[test]
+    fn test_solve_with() {
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let solvers: Vec<Box<dyn Solver>> = vec![Box::new(Algorithm::Dlx)];
+        let outcome = board.solve_with(&*solvers[0], &SolveOptions::default());
+        assert!(outcome.is_solved());
+        assert_eq!(SOLVED_PUZZLE_STR, board.to_string());
+    }
+}
@@ -0,0 +1,294 @@
+use board::Board;
+use solver::SolveOptions;
+use solver::stats::SolveStats;
+use solver::technique::Technique;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// Why a solver stopped before it finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbortReason {
+    /// The search needed to go deeper than `SolveOptions::max_depth`
+    DepthLimit,
+    /// `SolveOptions::timeout` passed
+    TimedOut,
+    /// The search visited `SolveOptions::max_nodes` nodes
+    NodeLimit,
+    /// The `SolveOptions::cancel` token was cancelled
+    Cancelled,
+}
+
+impl fmt::Display for AbortReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            AbortReason::DepthLimit => "depth limit reached",
+            AbortReason::TimedOut => "timed out",
+            AbortReason::NodeLimit => "node limit reached",
+            AbortReason::Cancelled => "cancelled",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+/// A flag that can be shared with a running solver, e.g. from another thread, to stop it
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Tokens are only equal if they are clones of each other
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &CancelToken) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Keeps track of a single solve against the limits in its `SolveOptions`, counting what the
+/// solver does along the way.
+///
+/// Solvers call `visit` at each node of their search, or `is_exhausted` wherever else they can
+/// stop, and give up once either says so. The budget can be shared between threads.
+#[derive(Debug)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    max_nodes: Option<usize>,
+    cancel: Option<CancelToken>,
+    num_nodes: AtomicUsize,
+    /// Set once there is a reason to stop, so that it can be checked without locking
+    stopped: AtomicBool,
+    /// The first reason found to stop
+    reason: Mutex<Option<AbortReason>>,
+    /// The board with the most squares filled in that a solver has passed to `record`
+    best: Mutex<Option<Board>>,
+    best_filled: AtomicUsize,
+    num_backtracks: AtomicUsize,
+    max_depth: AtomicUsize,
+    /// Counts for each technique, indexed by `Technique::index`
+    placements: Vec<AtomicUsize>,
+    eliminations: Vec<AtomicUsize>,
+}
+
+impl Budget {
+    pub fn new(options: &SolveOptions) -> Budget {
+        Budget {
+            deadline: options.timeout.map(|timeout| Instant::now() + timeout),
+            max_nodes: options.max_nodes,
+            cancel: options.cancel.clone(),
+            num_nodes: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+            reason: Mutex::new(None),
+            best: Mutex::new(None),
+            best_filled: AtomicUsize::new(0),
+            num_backtracks: AtomicUsize::new(0),
+            max_depth: AtomicUsize::new(0),
+            placements: Technique::all().iter().map(|_| AtomicUsize::new(0)).collect(),
+            eliminations: Technique::all().iter().map(|_| AtomicUsize::new(0)).collect(),
+        }
+    }
+
+    /// A budget which never runs out
+    pub fn unlimited() -> Budget {
+        Budget::new(&SolveOptions::default())
+    }
+
+    /// Count a node of the search. Returns false if the solver should stop.
+    pub fn visit(&self) -> bool {
+        let num_nodes = self.num_nodes.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(max_nodes) = self.max_nodes {
+            if num_nodes > max_nodes {
+                self.abort(AbortReason::NodeLimit);
+                return false;
+            }
+        }
+        !self.is_exhausted()
+    }
+
+    /// Whether the solver should stop, without counting a node
+    pub fn is_exhausted(&self) -> bool {
+        if self.stopped.load(Ordering::SeqCst) {
+            return true;
+        }
+        if let Some(ref cancel) = self.cancel {
+            if cancel.is_cancelled() {
+                self.abort(AbortReason::Cancelled);
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                self.abort(AbortReason::TimedOut);
+            }
+        }
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Stop the solve, unless it has already been stopped for another reason
+    pub fn abort(&self, reason: AbortReason) {
+        let mut current = self.reason.lock().expect("Unable to read the abort reason");
+        if current.is_none() {
+            *current = Some(reason);
+        }
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Why the solve was stopped, if it was
+    pub fn reason(&self) -> Option<AbortReason> {
+        *self.reason.lock().expect("Unable to read the abort reason")
+    }
+
+    /// Number of nodes visited so far
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes.load(Ordering::Relaxed)
+    }
+
+    /// Keep the board if it has more squares filled in than any recorded so far
+    pub fn record(&self, board: &Board) {
+        let num_filled = board.num_filled();
+        if num_filled <= self.best_filled.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut best = self.best.lock().expect("Unable to store the best board");
+        if num_filled > self.best_filled.load(Ordering::SeqCst) {
+            self.best_filled.store(num_filled, Ordering::SeqCst);
+            *best = Some(board.clone());
+        }
+    }
+
+    /// The board with the most squares filled in, of those recorded
+    pub fn best(&self) -> Option<Board> {
+        self.best.lock().expect("Unable to read the best board").clone()
+    }
+
+    /// Count a guess that had to be undone
+    pub fn backtrack(&self) {
+        self.num_backtracks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Note that the search has gone `depth` guesses deep
+    pub fn reach_depth(&self, depth: usize) {
+        self.max_depth.fetch_max(depth, Ordering::Relaxed);
+    }
+
+    /// Count a square filled in by the technique
+    pub fn place(&self, technique: Technique) {
+        self.placements[technique.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count candidates ruled out by the technique
+    pub fn eliminate(&self, technique: Technique, num_eliminated: usize) {
+        self.eliminations[technique.index()].fetch_add(num_eliminated, Ordering::Relaxed);
+    }
+
+    /// Everything counted so far. The time taken and number of threads are left for the caller
+    /// to fill in.
+    pub fn stats(&self) -> SolveStats {
+        let counts = |counts: &[AtomicUsize]| {
+            Technique::all()
+                .iter()
+                .map(|&technique| (technique, counts[technique.index()].load(Ordering::Relaxed)))
+                .filter(|&(_, count)| count > 0)
+                .collect()
+        };
+        SolveStats {
+            num_nodes: self.num_nodes(),
+            num_backtracks: self.num_backtracks.load(Ordering::Relaxed),
+            max_depth: self.max_depth.load(Ordering::Relaxed),
+            placements: counts(&self.placements),
+            eliminations: counts(&self.eliminations),
+            ..SolveStats::default()
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_budget_limits() {
+        let budget = Budget::unlimited();
+        for _ in 0..1000 {
+            assert!(budget.visit());
+        }
+        assert_eq!(1000, budget.num_nodes());
+        assert_eq!(None, budget.reason());
+
+        let options = SolveOptions {
+            max_nodes: Some(2),
+            ..SolveOptions::default()
+        };
+        let budget = Budget::new(&options);
+        assert!(budget.visit());
+        assert!(budget.visit());
+        assert!(!budget.visit());
+        assert_eq!(Some(AbortReason::NodeLimit), budget.reason());
+
+        let options = SolveOptions {
+            timeout: Some(Duration::from_secs(0)),
+            ..SolveOptions::default()
+        };
+        let budget = Budget::new(&options);
+        assert!(budget.is_exhausted());
+        assert_eq!(Some(AbortReason::TimedOut), budget.reason());
+
+        let cancel = CancelToken::new();
+        let options = SolveOptions {
+            cancel: Some(cancel.clone()),
+            ..SolveOptions::default()
+        };
+        let budget = Budget::new(&options);
+        assert!(!budget.is_exhausted());
+        cancel.cancel();
+        assert!(!budget.visit());
+        assert_eq!(Some(AbortReason::Cancelled), budget.reason());
+        // The first reason is kept
+        budget.abort(AbortReason::TimedOut);
+        assert_eq!(Some(AbortReason::Cancelled), budget.reason());
+    }
+
+    #[test]
+    fn test_budget_best() {
+        let budget = Budget::unlimited();
+        assert_eq!(None, budget.best());
+        let mut board = Board::default();
+        board.set_val(0, Some(1)).unwrap();
+        budget.record(&board);
+        board.set_val(1, Some(2)).unwrap();
+        budget.record(&board);
+        budget.record(&Board::default());
+        assert_eq!(Some(board), budget.best());
+    }
+
+    #[test]
+    fn test_budget_stats() {
+        let budget = Budget::unlimited();
+        budget.visit();
+        budget.reach_depth(3);
+        budget.reach_depth(1);
+        budget.backtrack();
+        budget.place(Technique::HiddenSingle);
+        budget.place(Technique::HiddenSingle);
+        budget.eliminate(Technique::Guess, 0);
+        let stats = budget.stats();
+        assert_eq!(1, stats.num_nodes);
+        assert_eq!(3, stats.max_depth);
+        assert_eq!(1, stats.num_backtracks);
+        assert_eq!(Some(&2), stats.placements.get(&Technique::HiddenSingle));
+        assert!(stats.eliminations.is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+use board::*;
+use candidate_board::CandidateBoard;
+use solver::{BoardStatus, SolveOptions};
+use solver::budget::{AbortReason, Budget};
+use solver::technique::Technique;
+
+/// How a depth first search picks the next square to fill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SquareOrder {
+    /// The square with the fewest candidates (minimum remaining values)
+    #[default]
+    Mrv,
+    /// The square with the fewest candidates, breaking ties by the most blank peers (degree)
+    MrvDegree,
+}
+
+/// The order a depth first search tries a square's candidates in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ValueOrder {
+    #[default]
+    Ascending,
+    /// Values which rule out the fewest candidates from the square's peers come first
+    LeastConstraining,
+}
+
+/// How a depth first search finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfsStatus {
+    Solved,
+    /// Every branch was tried without finding a solution
+    NoSolution,
+    /// No solution was found, but some branches were cut off by the maximum depth
+    DepthLimitReached,
+    /// The search was stopped by the timeout, node limit or cancel token in the options
+    Aborted(AbortReason),
+}
+
+impl DfsStatus {
+    pub fn is_solved(&self) -> bool {
+        *self == DfsStatus::Solved
+    }
+}
+
+/// State kept across the whole search
+struct Dfs<'a> {
+    options: &'a SolveOptions,
+    budget: &'a Budget,
+    /// The peers of each square, when a heuristic needs them
+    peers: Vec<Vec<Pos>>,
+    depth_limit_reached: bool,
+}
+
+impl<'a> Dfs<'a> {
+    fn new(board: &Board, options: &'a SolveOptions, budget: &'a Budget) -> Dfs<'a> {
+        let needs_peers = options.square_order == SquareOrder::MrvDegree ||
+            options.value_order == ValueOrder::LeastConstraining;
+        let peers = if needs_peers {
+            (0..board.num_squares)
+                .map(|index| board.get_peer_indices(index))
+                .collect()
+        } else {
+            vec![]
+        };
+        Dfs {
+            options,
+            budget,
+            peers,
+            depth_limit_reached: false,
+        }
+    }
+
+    /// Fill in the board, returning true if it was solved. The board is left as it was otherwise.
+    /// `depth` is the number of guesses made so far; filling a square with only one candidate
+    /// isn't a guess. If the budget runs out the board is left where the search stopped.
+    fn search(&mut self, cboard: &mut CandidateBoard, depth: usize) -> bool {
+        match cboard.check_status() {
+            BoardStatus::Solved => return true,
+            BoardStatus::Invalid => return false,
+            BoardStatus::Valid => {}
+        }
+        if !self.budget.visit() {
+            return false;
+        }
+
+        let index = self.choose_square(cboard);
+        let vals = self.order_values(cboard, index);
+        let technique = if vals.len() > 1 { Technique::Guess } else { Technique::NakedSingle };
+        let depth = if vals.len() > 1 { depth + 1 } else { depth };
+        if let Some(max_depth) = self.options.max_depth {
+            if depth > max_depth {
+                self.depth_limit_reached = true;
+                return false;
+            }
+        }
+        self.budget.reach_depth(depth);
+
+        for val in vals {
+            cboard.fill(index, val);
+            self.budget.place(technique);
+            if self.search(cboard, depth) {
+                return true;
+            }
+            if self.budget.is_exhausted() {
+                return false;
+            }
+            self.budget.backtrack();
+        }
+        cboard
+            .set_val(index, None)
+            .expect("A square filled by the search can be cleared");
+        false
+    }
+
+    fn choose_square(&self, cboard: &CandidateBoard) -> Pos {
+        let blanks = (0..cboard.num_squares).filter(|&index| cboard.squares[index].is_none());
+        let chosen = match self.options.square_order {
+            SquareOrder::Mrv => blanks.min_by_key(|&index| cboard.get_candidates(index).len()),
+            SquareOrder::MrvDegree => blanks.min_by_key(|&index| {
+                let num_blank_peers = self.peers[index]
+                    .iter()
+                    .filter(|&&peer| cboard.squares[peer].is_none())
+                    .count();
+                // Reversed, so that more blank peers comes first
+                (cboard.get_candidates(index).len(), cboard.num_squares - num_blank_peers)
+            }),
+        };
+        chosen.expect("A valid unsolved board has a blank square")
+    }
+
+    fn order_values(&self, cboard: &CandidateBoard, index: Pos) -> Vec<SquareType> {
+        let mut vals = cboard.get_candidates(index).to_vec();
+        if self.options.value_order == ValueOrder::LeastConstraining {
+            vals.sort_by_key(|&val| {
+                self.peers[index]
+                    .iter()
+                    .filter(|&&peer| cboard.get_candidates(peer).contains(val))
+                    .count()
+            });
+        }
+        vals
+    }
+}
+
+impl Board {
+    /// Depth first search, with the default heuristics. Goes at most `max_depth` guesses deep.
+    pub fn solve_search_dfs(&mut self, max_depth: Option<usize>) -> DfsStatus {
+        let options = SolveOptions {
+            max_depth,
+            ..SolveOptions::default()
+        };
+        self.solve_dfs(&options)
+    }
+
+    /// Depth first search, branching on one square at a time and backtracking out of any
+    /// guesses that break the rules. Uses the maximum depth and heuristics from the options.
+    /// The board is only changed if it is solved.
+    pub fn solve_dfs(&mut self, options: &SolveOptions) -> DfsStatus {
+        self.solve_dfs_within(options, &Budget::new(options))
+    }
+
+    /// `solve_dfs`, keeping to a budget which may be shared with other solvers
+    pub(crate) fn solve_dfs_within(
+        &mut self,
+        options: &SolveOptions,
+        budget: &Budget,
+    ) -> DfsStatus {
+        let mut cboard = CandidateBoard::new(self.clone());
+        let mut dfs = Dfs::new(self, options, budget);
+        if dfs.search(&mut cboard, 0) {
+            *self = cboard.into_board();
+            DfsStatus::Solved
+        } else if let Some(reason) = budget.reason() {
+            budget.record(&cboard);
+            DfsStatus::Aborted(reason)
+        } else if dfs.depth_limit_reached {
+            DfsStatus::DepthLimitReached
+        } else {
+            DfsStatus::NoSolution
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hintmap::HintMap;
+
+    fn all_options() -> Vec<SolveOptions> {
+        let mut all = vec![];
+        for &square_order in [SquareOrder::Mrv, SquareOrder::MrvDegree].iter() {
+            for &value_order in [ValueOrder::Ascending, ValueOrder::LeastConstraining].iter() {
+                all.push(SolveOptions {
+                    square_order,
+                    value_order,
+                    ..SolveOptions::default()
+                });
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn test_dfs_heuristics() {
+        let puzzles = [
+            UNSOLVED_PUZZLE_STR,
+            HARD_PUZZLE_STR,
+            UNSOLVED_6_STR,
+            _UNSOLVED_16,
+            include_str!("../../../puzzles/16hard.txt"),
+        ];
+        for options in all_options() {
+            for puzzle in puzzles.iter() {
+                let original = Board::from_string(puzzle);
+                let mut board = original.clone();
+                assert_eq!(DfsStatus::Solved, board.solve_dfs(&options));
+                assert!(board.check_status().is_solved());
+                // Keeps the values it started with
+                for index in 0..original.num_squares {
+                    if original.squares[index].is_some() {
+                        assert_eq!(original.squares[index], board.squares[index]);
+                    }
+                }
+            }
+        }
+
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        assert!(board.solve_search_dfs(None).is_solved());
+        assert_eq!(SOLVED_PUZZLE_STR, board.to_string());
+    }
+
+    #[test]
+    fn test_dfs_no_solution() {
+        let puzzle = "4,2,0,0,1,3,0,0,0,0,3,2,0,0,1,4";
+        for options in all_options() {
+            let mut board = Board::from_string(puzzle);
+            assert_eq!(DfsStatus::NoSolution, board.solve_dfs(&options));
+            assert_eq!(puzzle, board.to_string());
+        }
+
+        // Filling in a square of the hard puzzle with one of its candidates, so the puzzle only
+        // breaks the rules further into the search
+        let hard = Board::from_string(HARD_PUZZLE_STR);
+        let hints = HintMap::from_board(&hard);
+        let unsolvable = (0..hard.num_squares)
+            .flat_map(|index| hints.get_candidates(index).into_iter().map(move |val| (index, val)))
+            .map(|(index, val)| {
+                let mut board = hard.clone();
+                board.set_val(index, Some(val)).unwrap();
+                board
+            })
+            .find(|board| board.count_solutions_dlx(1) == 0)
+            .expect("Not every candidate is part of a solution");
+        assert_eq!(BoardStatus::Valid, unsolvable.check_status());
+        for options in all_options() {
+            assert_eq!(DfsStatus::NoSolution, unsolvable.clone().solve_dfs(&options));
+        }
+    }
+
+    #[test]
+    fn test_dfs_depth_limit() {
+        // The easy puzzle doesn't need any guesses
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        assert_eq!(DfsStatus::Solved, board.solve_search_dfs(Some(0)));
+
+        let mut board = Board::from_string(HARD_PUZZLE_STR);
+        assert_eq!(DfsStatus::DepthLimitReached, board.solve_search_dfs(Some(0)));
+        assert_eq!(HARD_PUZZLE_STR, board.to_string());
+        assert_eq!(DfsStatus::Solved, board.solve_search_dfs(Some(100)));
+
+        let mut board = Board::default();
+        assert_eq!(DfsStatus::DepthLimitReached, board.solve_search_dfs(Some(1)));
+        assert_eq!(DfsStatus::Solved, board.solve_search_dfs(None));
+    }
+
+    #[test]
+    fn test_dfs_budget() {
+        let options = SolveOptions {
+            max_nodes: Some(5),
+            ..SolveOptions::default()
+        };
+        let mut board = Board::from_string(HARD_PUZZLE_STR);
+        assert_eq!(DfsStatus::Aborted(AbortReason::NodeLimit), board.solve_dfs(&options));
+        assert_eq!(HARD_PUZZLE_STR, board.to_string());
+
+        // The board the search stopped on is kept as the best one so far
+        let budget = Budget::new(&options);
+        board.solve_dfs_within(&options, &budget);
+        let best = budget.best().expect("The search records where it stopped");
+        assert!(best.num_filled() > board.num_filled());
+        assert_eq!(5 + 1, budget.num_nodes());
+    }
+}
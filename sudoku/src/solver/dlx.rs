@@ -0,0 +1,447 @@
+//! Solving boards as an exact cover problem, with Knuth's Dancing Links.
+//!
+//! Each placement of a value in a square is a row of the matrix, and covers four constraints:
+//! the square is filled, and the value appears in the square's row, column and grid.
+//! Values already on the board are left out of the matrix, along with every constraint they cover.
+
+use board::*;
+use solver::BoardStatus;
+use solver::budget::Budget;
+use solver::technique::Technique;
+
+/// The header that every column header is linked from
+const ROOT: usize = 0;
+
+/// An exact cover matrix, stored as circular doubly linked lists of nodes.
+///
+/// Node 0 is the root, followed by a header for each column and then the nodes of each row.
+/// Covering a column unlinks it and every row that intersects it, and uncovering relinks them in
+/// the reverse order, so the search never has to copy the matrix.
+#[derive(Debug)]
+struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// The column header of each node
+    column: Vec<usize>,
+    /// The index of the placement that each node belongs to
+    row: Vec<usize>,
+    /// Number of rows still linked into each column
+    size: Vec<usize>,
+    /// The square and value of every row of the matrix
+    placements: Vec<(Pos, SquareType)>,
+    /// Rows making up the partial solution
+    chosen: Vec<usize>,
+    /// The first solution found
+    solution: Option<Vec<(Pos, SquareType)>>,
+    num_found: usize,
+    limit: usize,
+    /// The partial solution at the point the budget ran out
+    stopped_at: Option<Vec<(Pos, SquareType)>>,
+    /// Headers `1..=num_cell_headers` are for the constraints that each square is filled
+    num_cell_headers: usize,
+}
+
+impl Dlx {
+    fn from_board(board: &Board, limit: usize) -> Dlx {
+        let width = board.side_length;
+        let num_constraints = 4 * width * width;
+        let constraints = |index: Pos, val: SquareType| -> [usize; 4] {
+            let (col, row) = index.into_coord(width);
+            let offset = (val - board.min_value) as usize;
+            [
+                index,
+                width * width + row * width + offset,
+                2 * width * width + col * width + offset,
+                3 * width * width + board.get_grid_num(index) * width + offset,
+            ]
+        };
+
+        let mut satisfied = vec![false; num_constraints];
+        for index in 0..board.num_squares {
+            if let Some(val) = board.squares[index] {
+                for &constraint in constraints(index, val).iter() {
+                    satisfied[constraint] = true;
+                }
+            }
+        }
+
+        let mut dlx = Dlx {
+            left: vec![ROOT],
+            right: vec![ROOT],
+            up: vec![ROOT],
+            down: vec![ROOT],
+            column: vec![ROOT],
+            row: vec![0],
+            size: vec![0],
+            placements: vec![],
+            chosen: vec![],
+            solution: None,
+            num_found: 0,
+            limit,
+            stopped_at: None,
+            num_cell_headers: 0,
+        };
+
+        // Add a header for each constraint that still needs to be covered
+        let mut headers = vec![ROOT; num_constraints];
+        for constraint in 0..num_constraints {
+            if satisfied[constraint] {
+                continue;
+            }
+            let header = dlx.new_node(ROOT, 0);
+            dlx.column[header] = header;
+            dlx.link_left_of(ROOT, header);
+            headers[constraint] = header;
+            if constraint < width * width {
+                dlx.num_cell_headers += 1;
+            }
+        }
+
+        for index in 0..board.num_squares {
+            if board.squares[index].is_some() {
+                continue;
+            }
+            for val in board.min_value..board.max_value + 1 {
+                let row_constraints = constraints(index, val);
+                if row_constraints.iter().any(|&constraint| satisfied[constraint]) {
+                    continue;
+                }
+                let row = dlx.placements.len();
+                dlx.placements.push((index, val));
+
+                let mut first = None;
+                for &constraint in row_constraints.iter() {
+                    let header = headers[constraint];
+                    let node = dlx.new_node(header, row);
+                    // Add to the bottom of the column
+                    let bottom = dlx.up[header];
+                    dlx.up[node] = bottom;
+                    dlx.down[node] = header;
+                    dlx.down[bottom] = node;
+                    dlx.up[header] = node;
+                    dlx.size[header] += 1;
+                    match first {
+                        Some(first) => dlx.link_left_of(first, node),
+                        None => first = Some(node),
+                    }
+                }
+            }
+        }
+        dlx
+    }
+
+    /// Add a node linked only to itself
+    fn new_node(&mut self, column: usize, row: usize) -> usize {
+        let node = self.left.len();
+        self.left.push(node);
+        self.right.push(node);
+        self.up.push(node);
+        self.down.push(node);
+        self.column.push(column);
+        self.row.push(row);
+        self.size.push(0);
+        node
+    }
+
+    /// Link a node into a row, just to the left of `next`
+    fn link_left_of(&mut self, next: usize, node: usize) {
+        let prev = self.left[next];
+        self.left[node] = prev;
+        self.right[node] = next;
+        self.right[prev] = node;
+        self.left[next] = node;
+    }
+
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+
+        let mut row_node = self.down[header];
+        while row_node != header {
+            let mut node = self.right[row_node];
+            while node != row_node {
+                let (up, down) = (self.up[node], self.down[node]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row_node = self.down[row_node];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut row_node = self.up[header];
+        while row_node != header {
+            let mut node = self.left[row_node];
+            while node != row_node {
+                let (up, down) = (self.up[node], self.down[node]);
+                self.down[up] = node;
+                self.up[down] = node;
+                self.size[self.column[node]] += 1;
+                node = self.left[node];
+            }
+            row_node = self.up[row_node];
+        }
+
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+    }
+
+    fn chosen_placements(&self) -> Vec<(Pos, SquareType)> {
+        self.chosen
+            .iter()
+            .map(|&node| self.placements[self.row[node]])
+            .collect()
+    }
+
+    /// Algorithm X, always branching on the column with the fewest rows left.
+    /// Unwinds without finishing once the budget runs out. `depth` is the number of columns with
+    /// more than one row that have been branched on.
+    fn search(&mut self, budget: &Budget, depth: usize) {
+        if self.right[ROOT] == ROOT {
+            if self.solution.is_none() {
+                self.solution = Some(self.chosen_placements());
+            }
+            self.num_found += 1;
+            return;
+        }
+        if !budget.visit() {
+            self.stopped_at = Some(self.chosen_placements());
+            return;
+        }
+
+        let mut header = self.right[ROOT];
+        let mut best = header;
+        while header != ROOT {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+        if self.size[best] == 0 {
+            return;
+        }
+
+        // A column with a single row is a placement that is forced, by the square only having one
+        // candidate or by the value only fitting in one square of a unit
+        let technique = match self.size[best] {
+            1 if best <= self.num_cell_headers => Technique::NakedSingle,
+            1 => Technique::HiddenSingle,
+            _ => Technique::Guess,
+        };
+        let depth = if technique == Technique::Guess { depth + 1 } else { depth };
+        budget.reach_depth(depth);
+
+        self.cover(best);
+        let mut row_node = self.down[best];
+        while row_node != best && self.num_found < self.limit && self.stopped_at.is_none() {
+            self.chosen.push(row_node);
+            budget.place(technique);
+            let mut node = self.right[row_node];
+            while node != row_node {
+                let column = self.column[node];
+                self.cover(column);
+                node = self.right[node];
+            }
+
+            self.search(budget, depth);
+            if self.num_found < self.limit && self.stopped_at.is_none() {
+                budget.backtrack();
+            }
+
+            let mut node = self.left[row_node];
+            while node != row_node {
+                let column = self.column[node];
+                self.uncover(column);
+                node = self.left[node];
+            }
+            self.chosen.pop();
+            row_node = self.down[row_node];
+        }
+        self.uncover(best);
+    }
+}
+
+impl Board {
+    /// Solve the board with Dancing Links, returning true if a solution was found.
+    /// If the board has more than one solution, the first one found is used.
+    pub fn solve_dlx(&mut self) -> bool {
+        self.solve_dlx_within(&Budget::unlimited())
+    }
+
+    /// `solve_dlx`, giving up if the budget runs out
+    pub(crate) fn solve_dlx_within(&mut self, budget: &Budget) -> bool {
+        match self.check_status() {
+            BoardStatus::Solved => return true,
+            BoardStatus::Invalid => return false,
+            BoardStatus::Valid => {}
+        }
+
+        let mut dlx = Dlx::from_board(self, 1);
+        dlx.search(budget, 0);
+        if let Some(placements) = dlx.stopped_at {
+            let mut partial = self.clone();
+            for (index, val) in placements {
+                partial
+                    .set_deduced(index, val)
+                    .expect("Placements are always within the board");
+            }
+            budget.record(&partial);
+        }
+        match dlx.solution {
+            Some(placements) => {
+                for (index, val) in placements {
+                    self.set_deduced(index, val)
+                        .expect("Placements are always within the board");
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Count the solutions of the board with Dancing Links, stopping once `limit` have been found
+    pub fn count_solutions_dlx(&self, limit: usize) -> usize {
+        self.count_solutions_dlx_within(limit, &Budget::unlimited())
+    }
+
+    /// `count_solutions_dlx`, giving up if the budget runs out. The count is then only of the
+    /// solutions found so far.
+    pub(crate) fn count_solutions_dlx_within(&self, limit: usize, budget: &Budget) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        match self.check_status() {
+            BoardStatus::Solved => 1,
+            BoardStatus::Invalid => 0,
+            BoardStatus::Valid => {
+                let mut dlx = Dlx::from_board(self, limit);
+                dlx.search(budget, 0);
+                dlx.num_found
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+
+    #[test]
+    fn test_solve_dlx() {
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        assert!(board.solve_dlx());
+        assert_eq!(SOLVED_PUZZLE_STR, board.to_string());
+        assert_eq!(Some(Origin::Solver), board.get_origin((4, 0)).unwrap());
+
+        let mut board = Board::from_string(UNSOLVED_6_STR);
+        assert!(board.solve_dlx());
+        assert_eq!(SOLVED_6_STR, board.to_string());
+
+        let mut board = Board::from_string(include_str!("../../../puzzles/16hard.txt"));
+        assert!(board.solve_dlx());
+        assert!(board.check_status().is_solved());
+
+        let mut board = Board::from_base_num(4).unwrap();
+        assert!(board.solve_dlx());
+        assert!(board.check_status().is_solved());
+    }
+
+    #[test]
+    fn test_solve_dlx_invalid() {
+        let puzzle = "4,2,0,0,1,3,0,0,0,0,3,2,0,0,1,4";
+        let mut board = Board::from_string(puzzle);
+        assert!(!board.solve_dlx());
+        assert_eq!(puzzle, board.to_string());
+    }
+
+    #[test]
+    fn test_count_solutions_dlx() {
+        assert_eq!(288, Board::from_base_num(2).unwrap().count_solutions_dlx(1000));
+        assert_eq!(6, Board::from_string(HARD_PUZZLE_STR).count_solutions_dlx(10));
+        assert_eq!(1, Board::from_string(SOLVED_PUZZLE_STR).count_solutions_dlx(10));
+        assert_eq!(10, Board::default().count_solutions_dlx(10));
+        let bad_mini = Board::from_string("4,2,0,0,1,3,0,0,0,0,3,2,0,0,1,4");
+        assert_eq!(0, bad_mini.count_solutions_dlx(10));
+    }
+}
+
+#[cfg(all(feature = "bench", test))]
+mod bench {
+    use board::*;
+    use test::Bencher;
+
+    #[bench]
+    fn bench_09_solve_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(UNSOLVED_PUZZLE_STR);
+        b.iter(|| unsolved.clone().solve_dlx());
+    }
+
+    #[bench]
+    fn bench_09_hard_solve_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(HARD_PUZZLE_STR);
+        b.iter(|| unsolved.clone().solve_dlx());
+    }
+
+    #[bench]
+    fn bench_09_hard_count_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(HARD_PUZZLE_STR);
+        b.iter(|| unsolved.count_solutions_dlx(100));
+    }
+
+    #[bench]
+    fn bench_09_hard_count_solutions(b: &mut Bencher) {
+        let unsolved = Board::from_string(HARD_PUZZLE_STR);
+        b.iter(|| unsolved.count_solutions(100));
+    }
+
+    #[bench]
+    fn bench_16_solve_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(_UNSOLVED_16);
+        b.iter(|| unsolved.clone().solve_dlx());
+    }
+
+    #[bench]
+    fn bench_16_hard_solve_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/16hard.txt"));
+        b.iter(|| unsolved.clone().solve_dlx());
+    }
+
+    #[bench]
+    fn bench_25_puzzle_solve_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/25u.txt"));
+        b.iter(|| unsolved.clone().solve_dlx());
+    }
+
+    #[bench]
+    fn bench_36_puzzle_solve_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/36u.txt"));
+        b.iter(|| unsolved.clone().solve_dlx());
+    }
+
+    #[bench]
+    fn bench_36_puzzle_solve_search(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/36u.txt"));
+        b.iter(|| unsolved.clone().solve_search());
+    }
+
+    #[ignore]
+    #[bench]
+    fn bench_81_hard_solve_dlx(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/81hard.txt"));
+        b.iter(|| unsolved.clone().solve_dlx());
+    }
+
+    #[ignore]
+    #[bench]
+    fn bench_81_hard_solve_search(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/81hard.txt"));
+        b.iter(|| unsolved.clone().solve_search());
+    }
+}
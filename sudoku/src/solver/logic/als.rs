@@ -0,0 +1,369 @@
+use board::*;
+use candidates::Candidates;
+use solver::logic::{combinations, AlmostLockedSet, Deduction, LogicBoard, Pattern};
+use solver::technique::Technique;
+use std::cmp;
+use std::collections::HashSet;
+
+/// N squares of a unit with N + 1 candidates between them
+struct Als {
+    unit: Unit,
+    cells: Vec<Pos>,
+    values: Candidates,
+}
+
+impl Als {
+    fn to_set(&self) -> AlmostLockedSet {
+        AlmostLockedSet {
+            unit: self.unit,
+            cells: self.cells.clone(),
+            values: self.values.to_vec(),
+        }
+    }
+
+    fn overlaps(&self, other: &Als) -> bool {
+        self.cells.iter().any(|index| other.cells.contains(index))
+    }
+}
+
+impl LogicBoard {
+    /// Every almost locked set of up to `max_als_size` squares. Sets in more than one unit are
+    /// only given once, for the first of their units.
+    fn almost_locked_sets(&self) -> Vec<Als> {
+        let mut sets: Vec<Als> = vec![];
+        let mut seen: HashSet<Vec<Pos>> = HashSet::new();
+        for &(unit, ref indices) in self.units() {
+            let blanks: Vec<Pos> = indices
+                .iter()
+                .cloned()
+                .filter(|&index| !self.get_candidates(index).is_empty())
+                .collect();
+            // Every blank square of a unit together only has as many candidates as squares
+            let max_size = cmp::min(self.max_als_size, blanks.len().saturating_sub(1));
+            for size in 1..max_size + 1 {
+                for chosen in combinations(blanks.len(), size) {
+                    let cells: Vec<Pos> = chosen.iter().map(|&i| blanks[i]).collect();
+                    let values = cells.iter().fold(Candidates::empty(), |values, &index| {
+                        values | self.get_candidates(index)
+                    });
+                    if values.len() != size + 1 {
+                        continue;
+                    }
+                    let mut key = cells.clone();
+                    key.sort();
+                    if !seen.insert(key) {
+                        continue;
+                    }
+                    sets.push(Als {
+                        unit,
+                        cells,
+                        values,
+                    });
+                }
+            }
+        }
+        sets
+    }
+
+    /// The squares of the set which can hold the value
+    fn holding(&self, cells: &[Pos], val: SquareType) -> Vec<Pos> {
+        cells
+            .iter()
+            .cloned()
+            .filter(|&index| self.get_candidates(index).contains(val))
+            .collect()
+    }
+
+    /// Values both sets have, where every square of one holding it sees every square of the
+    /// other that does. At most one of the sets can hold each of them.
+    fn restricted_common(&self, first: &Als, second: &Als) -> Candidates {
+        (first.values & second.values)
+            .into_iter()
+            .filter(|&val| {
+                let others = self.holding(&second.cells, val);
+                self.holding(&first.cells, val).into_iter().all(|index| {
+                    others.iter().all(|&other| self.is_peer(index, other))
+                })
+            })
+            .collect()
+    }
+
+    /// Squares seeing every square of the sets that can hold the value
+    fn seen_by_sets(&self, sets: &[&Als], val: SquareType) -> Vec<(Pos, SquareType)> {
+        let cells: Vec<Pos> = sets.iter()
+            .flat_map(|set| self.holding(&set.cells, val))
+            .collect();
+        self.seen_by_all(&cells, val)
+    }
+
+    /// Pairs of sets which don't overlap and are joined by at least one restricted common value,
+    /// listed for each set
+    fn als_links(&self, sets: &[Als]) -> Vec<Vec<(usize, Candidates)>> {
+        let mut links = vec![vec![]; sets.len()];
+        for (i, first) in sets.iter().enumerate() {
+            for (j, second) in sets.iter().enumerate().skip(i + 1) {
+                if first.overlaps(second) {
+                    continue;
+                }
+                let restricted = self.restricted_common(first, second);
+                if !restricted.is_empty() {
+                    links[i].push((j, restricted));
+                    links[j].push((i, restricted));
+                }
+            }
+        }
+        links
+    }
+
+    /// Two almost locked sets joined by a restricted common value X. One of them doesn't hold X,
+    /// so is locked, and any other value Z they share must be in one of them.
+    ///
+    /// If they're joined by two values, neither set can hold both, so both are locked. Each of
+    /// their values is then ruled out from squares seeing every place it has in the sets.
+    pub(super) fn find_als_xz(&self) -> Option<Deduction> {
+        let sets = self.almost_locked_sets();
+        for (i, links) in self.als_links(&sets).into_iter().enumerate() {
+            let first = &sets[i];
+            for (j, restricted) in links {
+                if j < i {
+                    continue;
+                }
+                let second = &sets[j];
+                let mut eliminations: Vec<(Pos, SquareType)> = vec![];
+                for val in (first.values & second.values) - restricted {
+                    eliminations.extend(self.seen_by_sets(&[first, second], val));
+                }
+                if restricted.len() > 1 {
+                    for val in restricted {
+                        eliminations.extend(self.seen_by_sets(&[first, second], val));
+                    }
+                    for set in &[first, second] {
+                        for val in set.values - restricted {
+                            eliminations.extend(self.seen_by_sets(&[*set], val));
+                        }
+                    }
+                }
+                eliminations.sort();
+                eliminations.dedup();
+                if !eliminations.is_empty() {
+                    return Some(Deduction {
+                        technique: Technique::AlsXZ,
+                        pattern: Pattern::Als {
+                            sets: vec![first.to_set(), second.to_set()],
+                            restricted: restricted.to_vec(),
+                        },
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// A pivot set joined to a set A by a restricted common value X, and to a set B by another, Y.
+    /// If neither A nor B held a value Z they share, both would be locked and hold X and Y,
+    /// leaving the pivot without enough values. So Z must be in one of them.
+    pub(super) fn find_als_xy_wing(&self) -> Option<Deduction> {
+        let sets = self.almost_locked_sets();
+        for (pivot, links) in self.als_links(&sets).into_iter().enumerate() {
+            for (k, &(a, a_restricted)) in links.iter().enumerate() {
+                for &(b, b_restricted) in &links[k + 1..] {
+                    let (first, second) = (&sets[a], &sets[b]);
+                    if first.overlaps(second) {
+                        continue;
+                    }
+                    for x in a_restricted {
+                        for y in b_restricted.into_iter().filter(|&y| y != x) {
+                            let mut eliminations: Vec<(Pos, SquareType)> = vec![];
+                            for val in first.values & second.values {
+                                if val != x && val != y {
+                                    eliminations.extend(self.seen_by_sets(&[first, second], val));
+                                }
+                            }
+                            eliminations.sort();
+                            if !eliminations.is_empty() {
+                                return Some(Deduction {
+                                    technique: Technique::AlsXYWing,
+                                    pattern: Pattern::Als {
+                                        sets: vec![
+                                            sets[pivot].to_set(),
+                                            first.to_set(),
+                                            second.to_set(),
+                                        ],
+                                        restricted: vec![x, y],
+                                    },
+                                    placements: vec![],
+                                    eliminations,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+    #[test]
+    fn test_almost_locked_sets() {
+        // A square with two candidates is a set in its row, column and grid, but only given once
+        let lboard = with_candidates(Board::default(), &[((4, 4), &[1, 2])]);
+        let sets = lboard.almost_locked_sets();
+        assert_eq!(1, sets.len());
+        assert_eq!(Unit::Row(4), sets[0].unit);
+        assert_eq!(vec![40], sets[0].cells);
+    }
+
+    #[test]
+    fn test_als_xz() {
+        // The first square and three squares of the fifth row, joined by 1 in the first column.
+        // One of them must hold 2.
+        let mut lboard = with_candidates(
+            Board::default(),
+            &[
+                ((0, 0), &[1, 2]),
+                ((0, 4), &[1, 3]),
+                ((4, 4), &[3, 4]),
+                ((7, 4), &[2, 4]),
+            ],
+        );
+        let deduction = lboard.find(Technique::AlsXZ).expect("There is an ALS-XZ");
+        assert_eq!(
+            Pattern::Als {
+                sets: vec![
+                    AlmostLockedSet {
+                        unit: Unit::Row(0),
+                        cells: vec![0],
+                        values: vec![1, 2],
+                    },
+                    AlmostLockedSet {
+                        unit: Unit::Row(4),
+                        cells: vec![36, 40, 43],
+                        values: vec![1, 2, 3, 4],
+                    },
+                ],
+                restricted: vec![1],
+            },
+            deduction.pattern
+        );
+        assert_eq!(vec![(7, 2)], deduction.eliminations);
+        assert_eq!(vec![0, 36, 40, 43], deduction.cells());
+        assert_eq!(
+            concat!(
+                "ALS-XZ: 1, 2 in row 0 at (0, 0); 1, 2, 3, 4 in row 4 at (0, 4) (4, 4) (7, 4), ",
+                "restricted 1, removing 2 from (7, 0)"
+            ),
+            deduction.explain(&lboard)
+        );
+
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::AlsXZ));
+    }
+
+    #[test]
+    fn test_als_xz_doubly_linked() {
+        // The first square and two of the second row, joined by both 1 and 2 in the first grid.
+        // Neither can hold both, so both sets are locked.
+        let lboard = with_candidates(
+            Board::default(),
+            &[
+                ((0, 0), &[1, 2]),
+                ((1, 1), &[1, 3]),
+                ((2, 1), &[2, 3]),
+            ],
+        );
+        let deduction = lboard.find(Technique::AlsXZ).expect("There is an ALS-XZ");
+        match deduction.pattern {
+            Pattern::Als { ref restricted, .. } => assert_eq!(vec![1, 2], *restricted),
+            ref pattern => panic!("Expected almost locked sets, got {:?}", pattern),
+        }
+        // 3 is locked into the second row, and all three values into the first grid
+        let row: Vec<(Pos, SquareType)> = (12..18).map(|index| (index, 3)).collect();
+        assert!(row.iter().all(|elimination| deduction.eliminations.contains(elimination)));
+        assert_eq!(6 + 6 * 3, deduction.eliminations.len());
+    }
+
+    #[test]
+    fn test_als_xy_wing() {
+        // A pivot in the fifth row joined by 1 to two squares of the first row, and by 2 to the
+        // first square of its own row. One of those sets holds 3.
+        let lboard = with_candidates(
+            Board::default(),
+            &[
+                ((4, 4), &[1, 2]),
+                ((4, 0), &[1, 3, 5]),
+                ((6, 0), &[3, 5]),
+                ((0, 4), &[2, 3]),
+            ],
+        );
+        let deduction = lboard.find(Technique::AlsXYWing).expect("There is an ALS-XY-Wing");
+        match deduction.pattern {
+            Pattern::Als {
+                ref sets,
+                ref restricted,
+            } => {
+                let cells: Vec<Vec<Pos>> = sets.iter().map(|set| set.cells.clone()).collect();
+                assert_eq!(vec![vec![40], vec![4, 6], vec![36]], cells);
+                assert_eq!(vec![1, 2], *restricted);
+            }
+            ref pattern => panic!("Expected almost locked sets, got {:?}", pattern),
+        }
+        assert_eq!(vec![(0, 3)], deduction.eliminations);
+    }
+
+    #[test]
+    fn test_max_als_size() {
+        // On a 16x16 board, the first square and four squares of the ninth row, joined by 1 in
+        // the first column. Splitting them differently still needs a set of three squares.
+        let mut lboard = with_candidates(
+            Board::from_base_num(4).unwrap(),
+            &[
+                ((0, 0), &[1, 2]),
+                ((0, 8), &[1, 3]),
+                ((4, 8), &[3, 4]),
+                ((9, 8), &[4, 5]),
+                ((13, 8), &[5, 2]),
+            ],
+        );
+        let deduction = lboard.find(Technique::AlsXZ).expect("There is an ALS-XZ");
+        assert_eq!(vec![(13, 2)], deduction.eliminations);
+
+        lboard.set_max_als_size(2);
+        assert_eq!(None, lboard.find(Technique::AlsXZ));
+    }
+
+    #[test]
+    fn test_solve_als() {
+        // Singles get stuck on these puzzles, but either ALS technique can finish them
+        let als_xz = concat!(
+            "7,0,4,0,0,8,0,0,0,0,1,0,0,0,0,0,0,0,0,6,9,2,0,4,0,0,0,0,0,0,6,0,0,0,3,0,",
+            "5,3,0,7,0,1,4,0,0,0,9,7,5,0,0,0,1,0,0,0,3,4,0,7,6,5,0,0,7,0,0,0,0,0,4,1,",
+            "0,0,2,0,1,5,0,8,0"
+        );
+        let als_xy_wing = concat!(
+            "1,0,5,3,0,0,0,4,0,0,0,0,4,7,1,5,0,6,4,0,0,2,0,0,8,0,0,0,0,2,5,4,7,3,0,0,",
+            "0,8,9,0,3,0,4,0,5,0,0,0,0,0,0,0,2,1,0,0,7,0,0,8,0,0,0,5,0,0,6,2,3,0,0,0,",
+            "0,0,0,0,5,4,0,0,2"
+        );
+        for &(puzzle, technique) in
+            [(als_xz, Technique::AlsXZ), (als_xy_wing, Technique::AlsXYWing)].iter()
+        {
+            let techniques = [Technique::NakedSingle, Technique::HiddenSingle, technique];
+            let mut board = Board::from_string(puzzle);
+            assert_eq!(BoardStatus::Valid, board.clone().solve_standard_with(&techniques[..2]));
+            assert_eq!(BoardStatus::Solved, board.solve_standard_with(&techniques));
+
+            let mut solution = Board::from_string(puzzle);
+            assert_eq!(BoardStatus::Solved, solution.solve_standard_with(Technique::logical()));
+            assert_eq!(solution, board);
+        }
+    }
+}
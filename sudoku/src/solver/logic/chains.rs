@@ -0,0 +1,430 @@
+use board::*;
+use solver::logic::{Deduction, Link, LogicBoard, Pattern};
+use solver::technique::Technique;
+use std::collections::HashSet;
+
+/// A candidate: a square and a value it might hold
+type Node = (Pos, SquareType);
+
+/// Where links between candidates can be found
+#[derive(Debug, Clone, Copy)]
+struct Links {
+    /// Between the candidates of one square
+    in_square: bool,
+    /// Between the squares of a unit that can hold the same value
+    in_unit: bool,
+}
+
+/// The links a kind of chain is built from
+#[derive(Debug, Clone, Copy)]
+struct ChainRules {
+    strong: Links,
+    weak: Links,
+    /// Only go through squares with two candidates
+    bivalue_only: bool,
+}
+
+impl ChainRules {
+    fn for_technique(technique: Technique) -> ChainRules {
+        let (squares, units, both) = (
+            Links {
+                in_square: true,
+                in_unit: false,
+            },
+            Links {
+                in_square: false,
+                in_unit: true,
+            },
+            Links {
+                in_square: true,
+                in_unit: true,
+            },
+        );
+        match technique {
+            Technique::XChain => ChainRules {
+                strong: units,
+                weak: units,
+                bivalue_only: false,
+            },
+            Technique::XYChain => ChainRules {
+                strong: squares,
+                weak: units,
+                bivalue_only: true,
+            },
+            _ => ChainRules {
+                strong: both,
+                weak: both,
+                bivalue_only: false,
+            },
+        }
+    }
+}
+
+impl LogicBoard {
+    /// The candidates linked to a candidate. Strong links are to the other candidate of a square
+    /// with two, or the other square of a unit with only two places for the value. Weak links are
+    /// to any other candidate of the square, or the same value in any square seeing it.
+    fn linked(&self, (index, val): Node, strong: bool, rules: ChainRules) -> Vec<Node> {
+        let links = if strong { rules.strong } else { rules.weak };
+        let mut linked = vec![];
+        if links.in_square {
+            let mut others = self.get_candidates(index);
+            others.remove(val);
+            if !strong || others.len() == 1 {
+                linked.extend(others.into_iter().map(|other| (index, other)));
+            }
+        }
+        if links.in_unit {
+            for &unit in self.get_units(index).iter() {
+                let places: Vec<Pos> = self.get_unit_indices(unit)
+                    .into_iter()
+                    .filter(|&other| other != index && self.get_candidates(other).contains(val))
+                    .collect();
+                if !strong || places.len() == 1 {
+                    linked.extend(places.into_iter().map(|other| (other, val)));
+                }
+            }
+        }
+        linked.sort();
+        linked.dedup();
+        linked.retain(|&(other, _)| !rules.bivalue_only || self.get_candidates(other).len() == 2);
+        linked
+    }
+
+    /// What can be ruled out, knowing at least one of two candidates is true
+    fn either_eliminations(&self, (first, x): Node, (second, y): Node) -> Vec<(Pos, SquareType)> {
+        if x == y {
+            if first == second {
+                return vec![];
+            }
+            return self.seen_by_all(&[first, second], x);
+        }
+        let mut eliminations = vec![];
+        if first == second {
+            // The square holds one or the other
+            for val in self.get_candidates(first) {
+                if val != x && val != y {
+                    eliminations.push((first, val));
+                }
+            }
+        } else if self.is_peer(first, second) {
+            // If one of them is false, the other is true, so neither square can hold the
+            // other's value
+            if self.get_candidates(first).contains(y) {
+                eliminations.push((first, y));
+            }
+            if self.get_candidates(second).contains(x) {
+                eliminations.push((second, x));
+            }
+            eliminations.sort();
+        }
+        eliminations
+    }
+
+    /// The shortest chain of the given kind, up to the maximum chain length, which rules out
+    /// anything. Each candidate is searched from breadth first, so that chains are found in order
+    /// of length.
+    pub(super) fn find_chain(&self, technique: Technique) -> Option<Deduction> {
+        let rules = ChainRules::for_technique(technique);
+        let starts: Vec<Node> = (0..self.num_squares)
+            .filter(|&index| !rules.bivalue_only || self.get_candidates(index).len() == 2)
+            .flat_map(|index| self.get_candidates(index).into_iter().map(move |val| (index, val)))
+            .collect();
+
+        // The chain and what it rules out
+        let mut best: Option<(Vec<Node>, Vec<Node>)> = None;
+        for &start in &starts {
+            // Chains start and end with strong links, so are at least three links long
+            let max_length = match best {
+                Some((ref chain, _)) if chain.len() <= 4 => break,
+                Some((ref chain, _)) => chain.len() - 3,
+                None => self.max_chain_length,
+            };
+            // Each node holds a candidate and the node it was reached from
+            let mut nodes: Vec<(Node, Option<usize>)> = vec![(start, None)];
+            let mut reached: HashSet<(Node, bool)> = HashSet::new();
+            let mut frontier = vec![0];
+            let mut num_links = 0;
+            'search: while !frontier.is_empty() && num_links < max_length {
+                let strong = num_links % 2 == 0;
+                num_links += 1;
+                let mut next = vec![];
+                for &node in &frontier {
+                    for candidate in self.linked(nodes[node].0, strong, rules) {
+                        if !reached.insert((candidate, strong)) {
+                            continue;
+                        }
+                        nodes.push((candidate, Some(node)));
+                        let chain = chain_to(&nodes, nodes.len() - 1);
+                        let mut unique = chain.clone();
+                        unique.sort();
+                        unique.dedup();
+                        if unique.len() < chain.len() {
+                            continue;
+                        }
+                        next.push(nodes.len() - 1);
+
+                        if !strong || num_links < 3 {
+                            continue;
+                        }
+                        let eliminations = self.either_eliminations(start, candidate);
+                        if !eliminations.is_empty() {
+                            best = Some((chain, eliminations));
+                            break 'search;
+                        }
+                    }
+                }
+                frontier = next;
+            }
+        }
+
+        best.map(|(chain, eliminations)| {
+            let links = chain
+                .windows(2)
+                .enumerate()
+                .map(|(i, pair)| {
+                    Link {
+                        from: pair[0],
+                        to: pair[1],
+                        strong: i % 2 == 0,
+                    }
+                })
+                .collect();
+            Deduction {
+                technique,
+                pattern: Pattern::Chain { links },
+                placements: vec![],
+                eliminations,
+            }
+        })
+    }
+
+    /// Colour the squares of each value joined by strong links in alternating colours. If two
+    /// squares of one colour see each other, that colour is false. Otherwise, a square seeing
+    /// both colours can't hold the value.
+    pub(super) fn find_simple_colouring(&self) -> Option<Deduction> {
+        let rules = ChainRules::for_technique(Technique::XChain);
+        for val in self.min_value..self.max_value + 1 {
+            let cells: Vec<Pos> = (0..self.num_squares)
+                .filter(|&index| self.get_candidates(index).contains(val))
+                .collect();
+            let mut coloured = HashSet::new();
+            for &start in &cells {
+                if !coloured.insert(start) {
+                    continue;
+                }
+                let mut colours = [vec![start], vec![]];
+                let mut to_visit = vec![(start, 0)];
+                while let Some((index, colour)) = to_visit.pop() {
+                    for (other, _) in self.linked((index, val), true, rules) {
+                        if coloured.insert(other) {
+                            colours[1 - colour].push(other);
+                            to_visit.push((other, 1 - colour));
+                        }
+                    }
+                }
+                if colours[1].is_empty() {
+                    continue;
+                }
+                colours[0].sort();
+                colours[1].sort();
+
+                let wrapped = (0..2).find(|&colour| {
+                    let squares = &colours[colour];
+                    squares.iter().enumerate().any(|(i, &first)| {
+                        squares[i + 1..].iter().any(|&second| self.is_peer(first, second))
+                    })
+                });
+                let eliminations: Vec<(Pos, SquareType)> = match wrapped {
+                    Some(colour) => colours[colour].iter().map(|&index| (index, val)).collect(),
+                    None => cells
+                        .iter()
+                        .cloned()
+                        .filter(|index| !colours[0].contains(index) && !colours[1].contains(index))
+                        .filter(|&index| {
+                            colours.iter().all(|squares| {
+                                squares.iter().any(|&square| self.is_peer(square, index))
+                            })
+                        })
+                        .map(|index| (index, val))
+                        .collect(),
+                };
+                if !eliminations.is_empty() {
+                    return Some(Deduction {
+                        technique: Technique::SimpleColouring,
+                        pattern: Pattern::Colouring {
+                            value: val,
+                            colours,
+                        },
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The candidates along the chain to a node, starting from the first
+fn chain_to(nodes: &[(Node, Option<usize>)], last: usize) -> Vec<Node> {
+    let mut chain = vec![];
+    let mut current = Some(last);
+    while let Some(node) = current {
+        chain.push(nodes[node].0);
+        current = nodes[node].1;
+    }
+    chain.reverse();
+    chain
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+
+    fn links(nodes: &[((usize, usize), SquareType)]) -> Vec<Link> {
+        nodes
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                Link {
+                    from: ((pair[0].0).into_pos(9), pair[0].1),
+                    to: ((pair[1].0).into_pos(9), pair[1].1),
+                    strong: i % 2 == 0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_colour_trap() {
+        // 1 joined from (0, 0) to (7, 6) by the first grid, the third row and the eighth column
+        let mut lboard = LogicBoard::new(Board::default());
+        restrict(&mut lboard, 1, &[Unit::Grid(0)], &[(0, 0), (1, 2)]);
+        restrict(&mut lboard, 1, &[Unit::Row(2)], &[(1, 2), (7, 2)]);
+        restrict(&mut lboard, 1, &[Unit::Col(7)], &[(7, 2), (7, 6)]);
+        let deduction = lboard.find(Technique::SimpleColouring).expect("There is a colour trap");
+        let colours = [vec![(0, 0), (7, 2)], vec![(1, 2), (7, 6)]];
+        assert_eq!(
+            Pattern::Colouring {
+                value: 1,
+                colours: [
+                    colours[0].iter().map(|&coord| coord.into_pos(9)).collect(),
+                    colours[1].iter().map(|&coord| coord.into_pos(9)).collect(),
+                ],
+            },
+            deduction.pattern
+        );
+        // Sees (0, 0) and (7, 6), one of which must be 1
+        assert_eq!(vec![((0, 6).into_pos(9), 1)], deduction.eliminations);
+    }
+
+    #[test]
+    fn test_colour_wrap() {
+        // A loop of 1s from (0, 0) back round to (1, 1), which is the same colour but in the same
+        // grid
+        let mut lboard = LogicBoard::new(Board::default());
+        restrict(&mut lboard, 1, &[Unit::Col(0)], &[(0, 0), (0, 4)]);
+        restrict(&mut lboard, 1, &[Unit::Row(4)], &[(0, 4), (4, 4)]);
+        restrict(&mut lboard, 1, &[Unit::Col(4)], &[(4, 4), (4, 1)]);
+        restrict(&mut lboard, 1, &[Unit::Row(1)], &[(4, 1), (1, 1)]);
+        let deduction = lboard.find(Technique::SimpleColouring).expect("There is a colour wrap");
+        let expected: Vec<(Pos, SquareType)> = vec![(0, 1), (10, 1), (40, 1)];
+        assert_eq!(expected, deduction.eliminations);
+    }
+
+    #[test]
+    fn test_x_chain() {
+        // A skyscraper: 5 in the first and sixth columns can only go in two places, and the
+        // bottom of each is in the same row
+        let mut lboard = LogicBoard::new(Board::default());
+        restrict(&mut lboard, 5, &[Unit::Col(0)], &[(0, 0), (0, 6)]);
+        restrict(&mut lboard, 5, &[Unit::Col(5)], &[(5, 2), (5, 6)]);
+        let deduction = lboard.find(Technique::XChain).expect("There is an X-Chain");
+        assert_eq!(
+            Pattern::Chain {
+                links: links(&[((0, 0), 5), ((0, 6), 5), ((5, 6), 5), ((5, 2), 5)]),
+            },
+            deduction.pattern
+        );
+        let expected: Vec<(Pos, SquareType)> = vec![(3, 5), (4, 5), (19, 5), (20, 5)];
+        assert_eq!(expected, deduction.eliminations);
+        assert_eq!(
+            concat!(
+                "X-Chain: 5 in (0, 0) = 5 in (0, 6) - 5 in (5, 6) = 5 in (5, 2), removing 5 from ",
+                "(3, 0), 5 from (4, 0), 5 from (1, 2), 5 from (2, 2)"
+            ),
+            deduction.explain(&lboard)
+        );
+    }
+
+    #[test]
+    fn test_xy_chain() {
+        let mut lboard = with_candidates(
+            Board::default(),
+            &[
+                ((0, 0), &[1, 2]),
+                ((4, 0), &[2, 3]),
+                ((4, 4), &[3, 4]),
+                ((0, 4), &[4, 1]),
+            ],
+        );
+        assert_eq!(None, lboard.find(Technique::XYWing));
+        let deduction = lboard.find(Technique::XYChain).expect("There is an XY-Chain");
+        let nodes = [
+            ((0, 0), 1),
+            ((0, 0), 2),
+            ((4, 0), 2),
+            ((4, 0), 3),
+            ((4, 4), 3),
+            ((4, 4), 4),
+            ((0, 4), 4),
+            ((0, 4), 1),
+        ];
+        assert_eq!(Pattern::Chain { links: links(&nodes) }, deduction.pattern);
+        // Either end is 1, so the rest of the first column isn't
+        let expected: Vec<(Pos, SquareType)> = [1, 2, 3, 5, 6, 7, 8]
+            .iter()
+            .map(|&row| ((0, row).into_pos(9), 1))
+            .collect();
+        assert_eq!(expected, deduction.eliminations);
+        assert_eq!(vec![0, 4, 40, 36], deduction.cells());
+
+        // Too long to be found with shorter chains
+        lboard.set_max_chain_length(5);
+        assert_eq!(None, lboard.find(Technique::XYChain));
+    }
+
+    #[test]
+    fn test_aic() {
+        // A chain through a square with two candidates, and units where 7 and 5 can only go in
+        // two places
+        let mut lboard = with_candidates(
+            Board::default(),
+            &[
+                ((0, 0), &[5, 7]),
+            ],
+        );
+        restrict(&mut lboard, 7, &[Unit::Col(3)], &[(3, 0), (3, 4)]);
+        restrict(&mut lboard, 5, &[Unit::Row(4)], &[(0, 4), (3, 4)]);
+        assert_eq!(None, lboard.find(Technique::XYChain));
+        let deduction = lboard.find(Technique::AIC).expect("There is an AIC");
+        let nodes = [
+            ((0, 0), 5),
+            ((0, 0), 7),
+            ((3, 0), 7),
+            ((3, 4), 7),
+            ((3, 4), 5),
+            ((0, 4), 5),
+        ];
+        assert_eq!(Pattern::Chain { links: links(&nodes) }, deduction.pattern);
+        let expected: Vec<(Pos, SquareType)> = [1, 2, 3, 5, 6, 7, 8]
+            .iter()
+            .map(|&row| ((0, row).into_pos(9), 5))
+            .collect();
+        assert_eq!(expected, deduction.eliminations);
+    }
+}
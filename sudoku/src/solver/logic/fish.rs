@@ -0,0 +1,310 @@
+use board::*;
+use solver::logic::{combinations, Deduction, LogicBoard, Pattern};
+use solver::technique::Technique;
+use std::cmp;
+
+impl LogicBoard {
+    /// A value which can only go in `size` columns of `size` rows, or the other way around. Each
+    /// of those columns must hold it in one of the rows, so it can be ruled out from the rest of
+    /// the columns.
+    ///
+    /// A finned fish also lets the value go in a few squares outside of the columns, as long as
+    /// they're all in one grid. Either the fish or one of the fins holds the value, so it can only
+    /// be ruled out from squares of the columns that are in the same grid as the fins.
+    pub(super) fn find_fish(&self, size: usize, finned: bool) -> Option<Deduction> {
+        let technique = match (size, finned) {
+            (2, false) => Technique::XWing,
+            (3, false) => Technique::Swordfish,
+            (_, false) => Technique::Jellyfish,
+            (2, true) => Technique::FinnedXWing,
+            (3, true) => Technique::FinnedSwordfish,
+            (_, true) => Technique::FinnedJellyfish,
+        };
+        let side_length = self.side_length;
+        // Fins share a grid, so they take up no more extra lines than a grid is wide
+        let max_fins = if finned {
+            cmp::max(self.box_width, self.box_height)
+        } else {
+            0
+        };
+
+        for val in self.min_value..self.max_value + 1 {
+            for &by_rows in [true, false].iter() {
+                let square = |line: usize, cross: usize| if by_rows {
+                    (cross, line).into_pos(side_length)
+                } else {
+                    (line, cross).into_pos(side_length)
+                };
+                let line_unit = |line: usize, is_base: bool| if by_rows == is_base {
+                    Unit::Row(line)
+                } else {
+                    Unit::Col(line)
+                };
+
+                // The places along each line the value can go. Lines with only one are left to
+                // the singles.
+                let lines: Vec<(usize, Vec<usize>)> = (0..side_length)
+                    .map(|line| {
+                        let crosses = (0..side_length)
+                            .filter(|&cross| self.get_candidates(square(line, cross)).contains(val))
+                            .collect::<Vec<usize>>();
+                        (line, crosses)
+                    })
+                    .filter(|(_, crosses)| {
+                        crosses.len() > 1 && crosses.len() <= size + max_fins
+                    })
+                    .collect();
+                if lines.len() < size {
+                    continue;
+                }
+
+                for chosen in combinations(lines.len(), size) {
+                    let mut crosses: Vec<usize> = chosen
+                        .iter()
+                        .flat_map(|&i| lines[i].1.iter().cloned())
+                        .collect();
+                    crosses.sort();
+                    crosses.dedup();
+                    let num_fins = crosses.len().saturating_sub(size);
+                    if crosses.len() < size || num_fins > max_fins || finned == (num_fins == 0) {
+                        continue;
+                    }
+                    let base_lines: Vec<usize> = chosen.iter().map(|&i| lines[i].0).collect();
+
+                    for covering in combinations(crosses.len(), size) {
+                        let cover_lines: Vec<usize> =
+                            covering.iter().map(|&i| crosses[i]).collect();
+                        let fins: Vec<Pos> = chosen
+                            .iter()
+                            .flat_map(|&i| {
+                                let line = lines[i].0;
+                                lines[i]
+                                    .1
+                                    .iter()
+                                    .filter(|cross| !cover_lines.contains(cross))
+                                    .map(move |&cross| square(line, cross))
+                                    .collect::<Vec<Pos>>()
+                            })
+                            .collect();
+                        let fin_grid = fins.first().map(|&fin| self.get_grid_num(fin));
+                        if fins.iter().any(|&fin| Some(self.get_grid_num(fin)) != fin_grid) {
+                            continue;
+                        }
+
+                        let mut eliminations: Vec<(Pos, SquareType)> = cover_lines
+                            .iter()
+                            .flat_map(|&cross| {
+                                (0..side_length)
+                                    .filter(|line| !base_lines.contains(line))
+                                    .map(move |line| square(line, cross))
+                            })
+                            .filter(|&index| self.get_candidates(index).contains(val))
+                            .filter(|&index| match fin_grid {
+                                Some(grid) => self.get_grid_num(index) == grid,
+                                None => true,
+                            })
+                            .map(|index| (index, val))
+                            .collect();
+                        if eliminations.is_empty() {
+                            continue;
+                        }
+                        eliminations.sort();
+
+                        let sashimi = chosen.iter().any(|&i| {
+                            lines[i]
+                                .1
+                                .iter()
+                                .filter(|cross| cover_lines.contains(cross))
+                                .count() < 2
+                        });
+                        let base = base_lines.iter().map(|&line| line_unit(line, true));
+                        let cover = cover_lines.iter().map(|&line| line_unit(line, false));
+                        return Some(Deduction {
+                            technique,
+                            pattern: Pattern::Fish {
+                                value: val,
+                                base: base.collect(),
+                                cover: cover.collect(),
+                                fins,
+                                sashimi,
+                            },
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+    #[test]
+    fn test_x_wing() {
+        // 4 can only go in the second and eighth columns of the third and seventh rows
+        let mut lboard = LogicBoard::new(Board::default());
+        let rows = [Unit::Row(2), Unit::Row(6)];
+        restrict(&mut lboard, 4, &rows, &[(1, 2), (7, 2), (1, 6), (7, 6)]);
+        assert_eq!(None, lboard.find(Technique::FinnedXWing));
+        let deduction = lboard.find(Technique::XWing).expect("There is an X-Wing");
+        assert_eq!(
+            Pattern::Fish {
+                value: 4,
+                base: rows.to_vec(),
+                cover: vec![Unit::Col(1), Unit::Col(7)],
+                fins: vec![],
+                sashimi: false,
+            },
+            deduction.pattern
+        );
+        assert_eq!(2 * 7, deduction.eliminations.len());
+        assert!(deduction.eliminations.iter().all(|&(index, val)| {
+            let (col, row) = index.into_coord(9);
+            val == 4 && (col == 1 || col == 7) && row != 2 && row != 6
+        }));
+
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::XWing));
+    }
+
+    #[test]
+    fn test_swordfish() {
+        // On a 16x16 board, 9 can only go in three rows of the first, sixth and eleventh columns
+        let mut lboard = LogicBoard::new(Board::from_base_num(4).unwrap());
+        let cols = [Unit::Col(0), Unit::Col(5), Unit::Col(10)];
+        restrict(&mut lboard, 9, &cols, &[(0, 3), (0, 8), (5, 8), (5, 13), (10, 3), (10, 13)]);
+        assert_eq!(None, lboard.find(Technique::XWing));
+        let deduction = lboard.find(Technique::Swordfish).expect("There is a swordfish");
+        assert_eq!(
+            Pattern::Fish {
+                value: 9,
+                base: cols.to_vec(),
+                cover: vec![Unit::Row(3), Unit::Row(8), Unit::Row(13)],
+                fins: vec![],
+                sashimi: false,
+            },
+            deduction.pattern
+        );
+        assert_eq!(3 * 13, deduction.eliminations.len());
+    }
+
+    #[test]
+    fn test_jellyfish() {
+        // On a 25x25 board, 20 can only go in four columns of four rows
+        let mut lboard = LogicBoard::new(Board::from_base_num(5).unwrap());
+        let rows = [Unit::Row(1), Unit::Row(7), Unit::Row(13), Unit::Row(19)];
+        let keep = [(2, 1), (9, 1), (9, 7), (16, 7), (16, 13), (23, 13), (23, 19), (2, 19)];
+        restrict(&mut lboard, 20, &rows, &keep);
+        assert_eq!(None, lboard.find(Technique::Swordfish));
+        let deduction = lboard.find(Technique::Jellyfish).expect("There is a jellyfish");
+        match deduction.pattern {
+            Pattern::Fish {
+                value,
+                ref base,
+                ref cover,
+                ..
+            } => {
+                assert_eq!(20, value);
+                assert_eq!(rows.to_vec(), *base);
+                assert_eq!(vec![Unit::Col(2), Unit::Col(9), Unit::Col(16), Unit::Col(23)], *cover);
+            }
+            ref pattern => panic!("Expected a fish, got {:?}", pattern),
+        }
+        assert_eq!(4 * 21, deduction.eliminations.len());
+    }
+
+    #[test]
+    fn test_finned_x_wing() {
+        // An X-Wing on 4 in the second and eighth columns, except the first row can also hold it
+        // in the ninth column
+        let mut lboard = LogicBoard::new(Board::default());
+        let rows = [Unit::Row(0), Unit::Row(4)];
+        restrict(&mut lboard, 4, &rows, &[(1, 0), (7, 0), (8, 0), (1, 4), (7, 4)]);
+        assert_eq!(None, lboard.find(Technique::XWing));
+        let deduction = lboard.find(Technique::FinnedXWing).expect("There is a finned X-Wing");
+        assert_eq!(
+            Pattern::Fish {
+                value: 4,
+                base: rows.to_vec(),
+                cover: vec![Unit::Col(1), Unit::Col(7)],
+                fins: vec![8],
+                sashimi: false,
+            },
+            deduction.pattern
+        );
+        // Only the squares of the eighth column that see the fin
+        assert_eq!(vec![(16, 4), (25, 4)], deduction.eliminations);
+    }
+
+    #[test]
+    fn test_sashimi_x_wing() {
+        // Like the finned X-Wing, but the first row can't hold 4 in the eighth column
+        let mut lboard = LogicBoard::new(Board::default());
+        let rows = [Unit::Row(0), Unit::Row(4)];
+        restrict(&mut lboard, 4, &rows, &[(1, 0), (8, 0), (1, 4), (7, 4)]);
+        let deduction = lboard.find(Technique::FinnedXWing).expect("There is a sashimi X-Wing");
+        assert_eq!(
+            Pattern::Fish {
+                value: 4,
+                base: rows.to_vec(),
+                cover: vec![Unit::Col(1), Unit::Col(7)],
+                fins: vec![8],
+                sashimi: true,
+            },
+            deduction.pattern
+        );
+        assert_eq!(vec![(16, 4), (25, 4)], deduction.eliminations);
+        assert_eq!(
+            concat!(
+                "Sashimi X-Wing on 4: row 0, row 4 covered by column 1, column 7 ",
+                "with fins (8, 0), removing 4 from (7, 1), 4 from (7, 2)"
+            ),
+            deduction.explain(&lboard)
+        );
+    }
+
+    #[test]
+    fn test_finned_jellyfish_large() {
+        // On a 36x36 board, a jellyfish on 1 in four columns, with two fins in the top grid of
+        // the last column
+        let mut lboard = LogicBoard::new(Board::from_base_num(6).unwrap());
+        let cols = [Unit::Col(0), Unit::Col(12), Unit::Col(24), Unit::Col(35)];
+        let keep = [
+            (0, 10),
+            (0, 20),
+            (12, 20),
+            (12, 30),
+            (24, 30),
+            (24, 0),
+            (35, 0),
+            (35, 10),
+            (35, 2),
+            (35, 3),
+        ];
+        restrict(&mut lboard, 1, &cols, &keep);
+        assert_eq!(None, lboard.find(Technique::Jellyfish));
+        let deduction = lboard.find(Technique::FinnedJellyfish).expect("There is a finned fish");
+        match deduction.pattern {
+            Pattern::Fish {
+                ref cover,
+                ref fins,
+                sashimi,
+                ..
+            } => {
+                assert_eq!(vec![Unit::Row(0), Unit::Row(10), Unit::Row(20), Unit::Row(30)], *cover);
+                assert_eq!(vec![(35, 2).into_pos(36), (35, 3).into_pos(36)], *fins);
+                assert!(!sashimi);
+            }
+            ref pattern => panic!("Expected a fish, got {:?}", pattern),
+        }
+        // The rest of the first row, in the same grid as the fins
+        let expected: Vec<(Pos, SquareType)> = (30..35).map(|col| (col, 1)).collect();
+        assert_eq!(expected, deduction.eliminations);
+    }
+}
@@ -0,0 +1,181 @@
+use board::*;
+use solver::logic::{Deduction, LogicBoard, Pattern};
+use solver::technique::Technique;
+
+impl LogicBoard {
+    /// A value which can only go in one row or column of a grid. It must go in that grid, so can
+    /// be ruled out from the rest of the row or column.
+    pub(super) fn find_pointing(&self) -> Option<Deduction> {
+        self.find_intersection(Technique::Pointing)
+    }
+
+    /// A value which can only go in one grid of a row or column. It must go in that row or
+    /// column, so can be ruled out from the rest of the grid.
+    pub(super) fn find_claiming(&self) -> Option<Deduction> {
+        self.find_intersection(Technique::Claiming)
+    }
+
+    /// Pointing looks from grids to lines, and claiming from lines to grids
+    fn find_intersection(&self, technique: Technique) -> Option<Deduction> {
+        for &(base, ref indices) in self.units() {
+            match (technique, base) {
+                (Technique::Pointing, Unit::Grid(_)) |
+                (Technique::Claiming, Unit::Row(_)) |
+                (Technique::Claiming, Unit::Col(_)) => {}
+                _ => continue,
+            }
+
+            for val in self.min_value..self.max_value + 1 {
+                // Values with only one place are left to the singles
+                let cells: Vec<Pos> = indices
+                    .iter()
+                    .cloned()
+                    .filter(|&index| self.get_candidates(index).contains(val))
+                    .collect();
+                if cells.len() < 2 {
+                    continue;
+                }
+                let covers = match base {
+                    Unit::Grid(_) => vec![
+                        Unit::Row(self.get_row_num(cells[0])),
+                        Unit::Col(self.get_col_num(cells[0])),
+                    ],
+                    _ => vec![Unit::Grid(self.get_grid_num(cells[0]))],
+                };
+
+                for cover in covers {
+                    if !cells.iter().all(|&index| self.get_units(index).contains(&cover)) {
+                        continue;
+                    }
+                    let eliminations: Vec<(Pos, SquareType)> = self.get_unit_indices(cover)
+                        .into_iter()
+                        .filter(|index| !cells.contains(index))
+                        .filter(|&index| self.get_candidates(index).contains(val))
+                        .map(|index| (index, val))
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(Deduction {
+                            technique,
+                            pattern: Pattern::Intersection {
+                                base,
+                                cover,
+                                value: val,
+                                cells,
+                            },
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+    #[test]
+    fn test_pointing() {
+        // 5 can only go in the first row of the first grid
+        let mut lboard = LogicBoard::new(Board::default());
+        for row in 1..3 {
+            for col in 0..3 {
+                lboard.eliminate((col, row).into_pos(9), 5);
+            }
+        }
+        assert_eq!(None, lboard.find(Technique::Claiming));
+        let deduction = lboard.find(Technique::Pointing).expect("There is a pointing triple");
+        assert_eq!(
+            Pattern::Intersection {
+                base: Unit::Grid(0),
+                cover: Unit::Row(0),
+                value: 5,
+                cells: vec![0, 1, 2],
+            },
+            deduction.pattern
+        );
+        let expected: Vec<(Pos, SquareType)> = (3..9).map(|index| (index, 5)).collect();
+        assert_eq!(expected, deduction.eliminations);
+
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::Pointing));
+    }
+
+    #[test]
+    fn test_claiming() {
+        // 5 can only go in the first grid of the first row
+        let mut lboard = LogicBoard::new(Board::default());
+        for index in 3..9 {
+            lboard.eliminate(index, 5);
+        }
+        assert_eq!(None, lboard.find(Technique::Pointing));
+        let deduction = lboard.find(Technique::Claiming).expect("There is a claiming triple");
+        assert_eq!(
+            Pattern::Intersection {
+                base: Unit::Row(0),
+                cover: Unit::Grid(0),
+                value: 5,
+                cells: vec![0, 1, 2],
+            },
+            deduction.pattern
+        );
+        let expected = vec![(9, 5), (10, 5), (11, 5), (18, 5), (19, 5), (20, 5)];
+        assert_eq!(expected, deduction.eliminations);
+
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::Claiming));
+    }
+
+    #[test]
+    fn test_pointing_pair_large() {
+        // On a 25x25 board, 7 can only go in two squares of the fourth column of the last grid
+        let mut lboard = LogicBoard::new(Board::from_base_num(5).unwrap());
+        let grid = lboard.get_grid_indices(24);
+        let pair = [(23, 21).into_pos(25), (23, 24).into_pos(25)];
+        for &index in &grid {
+            if !pair.contains(&index) {
+                lboard.eliminate(index, 7);
+            }
+        }
+        let deduction = lboard.find(Technique::Pointing).expect("There is a pointing pair");
+        assert_eq!(
+            Pattern::Intersection {
+                base: Unit::Grid(24),
+                cover: Unit::Col(23),
+                value: 7,
+                cells: pair.to_vec(),
+            },
+            deduction.pattern
+        );
+        assert_eq!(20, deduction.eliminations.len());
+        assert!(deduction.eliminations.iter().all(|&(index, val)| {
+            val == 7 && lboard.get_col_num(index) == 23 && lboard.get_row_num(index) < 20
+        }));
+    }
+
+    #[test]
+    fn test_claiming_large() {
+        // On a 16x16 board, 16 can only go in the second grid of the sixth column
+        let mut lboard = LogicBoard::new(Board::from_base_num(4).unwrap());
+        for row in (0..4).chain(8..16) {
+            lboard.eliminate((5, row).into_pos(16), 16);
+        }
+        let deduction = lboard.find(Technique::Claiming).expect("There is a claiming quad");
+        match deduction.pattern {
+            Pattern::Intersection {
+                base, cover, value, ..
+            } => {
+                assert_eq!(Unit::Col(5), base);
+                assert_eq!(Unit::Grid(5), cover);
+                assert_eq!(16, value);
+            }
+            pattern => panic!("Expected an intersection, got {:?}", pattern),
+        }
+        assert_eq!(3 * 4, deduction.eliminations.len());
+    }
+}
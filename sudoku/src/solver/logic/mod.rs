@@ -0,0 +1,849 @@
+//! Solving boards the way a person would, one deduction at a time.
+//!
+//! A `LogicBoard` keeps the candidates of every blank square. Each technique looks for a pattern
+//! in the candidates, and either fills in squares or rules out candidates. Candidates which have
+//! been ruled out stay ruled out, so later techniques can build on earlier ones.
+
+use board::*;
+use candidates::Candidates;
+use hintmap::HintMap;
+use solver::{BoardStatus, SolveOptions};
+use solver::budget::Budget;
+use solver::technique::Technique;
+use std::ops::Deref;
+
+mod als;
+mod chains;
+mod fish;
+mod intersections;
+mod singles;
+mod subsets;
+mod uniqueness;
+mod wings;
+
+/// Longest chain, in links, the chaining techniques look for unless told otherwise
+pub const DEFAULT_MAX_CHAIN_LENGTH: usize = 12;
+
+/// Most squares in an almost locked set the ALS techniques look for unless told otherwise
+pub const DEFAULT_MAX_ALS_SIZE: usize = 4;
+
+/// A link between two candidates of a chain, each a square and a value it might hold. If the
+/// link is strong at least one of them is true, and if it's weak at most one of them is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Link {
+    pub from: (Pos, SquareType),
+    pub to: (Pos, SquareType),
+    pub strong: bool,
+}
+
+/// Squares of a unit with one more candidate between them than there are squares. If any one of
+/// the candidates is ruled out, the rest must all go in the squares.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlmostLockedSet {
+    pub unit: Unit,
+    pub cells: Vec<Pos>,
+    pub values: Vec<SquareType>,
+}
+
+/// The squares and units a deduction was found from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// The square has one candidate left or, if there is a unit, is the only place the value can
+    /// go in it
+    Single { unit: Option<Unit> },
+    /// `cells` are the only squares of `base` that can hold `value`, and they are all in `cover`
+    Intersection {
+        base: Unit,
+        cover: Unit,
+        value: SquareType,
+        cells: Vec<Pos>,
+    },
+    /// `value` can only go in the `cover` lines of the `base` lines, apart from the `fins`, which
+    /// are all in one grid. A sashimi fish has a base line with at most one square in the cover.
+    Fish {
+        value: SquareType,
+        base: Vec<Unit>,
+        cover: Vec<Unit>,
+        fins: Vec<Pos>,
+        sashimi: bool,
+    },
+    /// One of the `pincers` must hold `value`, whichever value the `pivot` holds. An XY-Wing or
+    /// XYZ-Wing pivots on one square, and a W-Wing on the two ends of the link between its pincers.
+    Wing {
+        value: SquareType,
+        pivot: Vec<Pos>,
+        pincers: Vec<Pos>,
+    },
+    /// The squares that can hold `value`, split into two colours by the units it can only go in
+    /// two of. All the squares of one colour hold it, and none of the other.
+    Colouring {
+        value: SquareType,
+        colours: [Vec<Pos>; 2],
+    },
+    /// A chain of candidates, joined by links which alternate between strong and weak, starting
+    /// and ending with strong ones. One of the two ends must be true.
+    Chain { links: Vec<Link> },
+    /// Four squares in two rows, columns and grids which can all hold `values`. The `floor` only
+    /// has those two candidates, and the `roof` has others. Any other squares the deduction
+    /// needed, such as the rest of a naked subset, are in `cells`.
+    Rectangle {
+        values: Vec<SquareType>,
+        floor: Vec<Pos>,
+        roof: Vec<Pos>,
+        cells: Vec<Pos>,
+    },
+    /// Almost locked sets joined by `restricted` values, which can only be in one of the sets they
+    /// join, as every square of one holding the value sees every square of the other that does.
+    /// An ALS-XZ has two sets joined by one or two values, and an ALS-XY-Wing has a pivot, given
+    /// first, joined to each of the other two by a different value.
+    Als {
+        sets: Vec<AlmostLockedSet>,
+        restricted: Vec<SquareType>,
+    },
+    /// `cells` of `unit` are the only ones that can hold `values`, and there are as many of each
+    Subset {
+        unit: Unit,
+        cells: Vec<Pos>,
+        values: Vec<SquareType>,
+    },
+}
+
+/// A step towards solving a board, found by a single technique
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deduction {
+    pub technique: Technique,
+    pub pattern: Pattern,
+    /// Squares which can be filled in, with their values
+    pub placements: Vec<(Pos, SquareType)>,
+    /// Candidates which can be ruled out, with their squares
+    pub eliminations: Vec<(Pos, SquareType)>,
+}
+
+impl Deduction {
+    /// The squares the deduction was found from, so they can be highlighted. Fish only give their
+    /// fins, as the rest of the fish is spread across whole lines.
+    pub fn cells(&self) -> Vec<Pos> {
+        match self.pattern {
+            Pattern::Single { .. } => self.placements.iter().map(|&(index, _)| index).collect(),
+            Pattern::Intersection { ref cells, .. } | Pattern::Subset { ref cells, .. } => {
+                cells.clone()
+            }
+            Pattern::Fish { ref fins, .. } => fins.clone(),
+            Pattern::Wing {
+                ref pivot,
+                ref pincers,
+                ..
+            } => pivot.iter().chain(pincers).cloned().collect(),
+            Pattern::Rectangle {
+                ref floor,
+                ref roof,
+                ref cells,
+                ..
+            } => floor.iter().chain(roof).chain(cells).cloned().collect(),
+            Pattern::Colouring { ref colours, .. } => {
+                colours[0].iter().chain(&colours[1]).cloned().collect()
+            }
+            Pattern::Chain { ref links } => {
+                let mut cells: Vec<Pos> = vec![];
+                for link in links.iter() {
+                    for &(index, _) in &[link.from, link.to] {
+                        if !cells.contains(&index) {
+                            cells.push(index);
+                        }
+                    }
+                }
+                cells
+            }
+            Pattern::Als { ref sets, .. } => {
+                sets.iter().flat_map(|set| set.cells.iter().cloned()).collect()
+            }
+        }
+    }
+
+    /// Describe the deduction, with squares given as coordinates on the board
+    pub fn explain(&self, board: &Board) -> String {
+        let coords = |cells: &[Pos]| {
+            let coords: Vec<String> = cells
+                .iter()
+                .map(|&index| {
+                    let (col, row) = index.into_coord(board.side_length);
+                    format!("({}, {})", col, row)
+                })
+                .collect();
+            coords.join(" ")
+        };
+        let units = |units: &[Unit]| {
+            let units: Vec<String> = units.iter().map(|unit| unit.to_string()).collect();
+            units.join(", ")
+        };
+        let list = |values: &[SquareType]| {
+            let values: Vec<String> = values.iter().map(|val| val.to_string()).collect();
+            values.join(", ")
+        };
+
+        let mut text = match self.pattern {
+            Pattern::Single { unit: None } => format!("{}", self.technique),
+            Pattern::Single { unit: Some(unit) } => format!("{} in {}", self.technique, unit),
+            Pattern::Intersection {
+                base,
+                cover,
+                value,
+                ref cells,
+            } => format!(
+                "{} in {}: {} only in {}, all in {}",
+                self.technique,
+                base,
+                value,
+                coords(cells),
+                cover
+            ),
+            Pattern::Fish {
+                value,
+                ref base,
+                ref cover,
+                ref fins,
+                sashimi,
+            } => {
+                let mut name = self.technique.to_string();
+                if sashimi {
+                    name = name.replace("Finned", "Sashimi");
+                }
+                let mut text = format!(
+                    "{} on {}: {} covered by {}",
+                    name,
+                    value,
+                    units(base),
+                    units(cover)
+                );
+                if !fins.is_empty() {
+                    text.push_str(&format!(" with fins {}", coords(fins)));
+                }
+                text
+            }
+            Pattern::Wing {
+                value,
+                ref pivot,
+                ref pincers,
+            } => format!(
+                "{} on {}: pivot {}, pincers {}",
+                self.technique,
+                value,
+                coords(pivot),
+                coords(pincers)
+            ),
+            Pattern::Rectangle {
+                ref values,
+                ref floor,
+                ref roof,
+                ref cells,
+            } => {
+                let mut text = format!(
+                    "{} on {}: floor {}, roof {}",
+                    self.technique,
+                    list(values),
+                    coords(floor),
+                    coords(roof)
+                );
+                if !cells.is_empty() {
+                    text.push_str(&format!(", with {}", coords(cells)));
+                }
+                text
+            }
+            Pattern::Colouring {
+                value,
+                ref colours,
+            } => format!(
+                "{} on {}: {} against {}",
+                self.technique,
+                value,
+                coords(&colours[0]),
+                coords(&colours[1])
+            ),
+            Pattern::Chain { ref links } => {
+                let node = |(index, val): (Pos, SquareType)| {
+                    format!("{} in {}", val, coords(&[index]))
+                };
+                let mut text = format!("{}: ", self.technique);
+                if let Some(first) = links.first() {
+                    text.push_str(&node(first.from));
+                }
+                for link in links {
+                    text.push_str(if link.strong { " = " } else { " - " });
+                    text.push_str(&node(link.to));
+                }
+                text
+            }
+            Pattern::Als {
+                ref sets,
+                ref restricted,
+            } => {
+                let sets: Vec<String> = sets.iter()
+                    .map(|set| {
+                        format!("{} in {} at {}", list(&set.values), set.unit, coords(&set.cells))
+                    })
+                    .collect();
+                format!(
+                    "{}: {}, restricted {}",
+                    self.technique,
+                    sets.join("; "),
+                    list(restricted)
+                )
+            }
+            Pattern::Subset {
+                unit,
+                ref cells,
+                ref values,
+            } => format!(
+                "{} in {}: {} hold {}",
+                self.technique,
+                unit,
+                coords(cells),
+                list(values)
+            ),
+        };
+        for &(index, val) in &self.placements {
+            text.push_str(&format!(", {} is {}", coords(&[index]), val));
+        }
+        if !self.eliminations.is_empty() {
+            let removed: Vec<String> = self.eliminations
+                .iter()
+                .map(|&(index, val)| format!("{} from {}", val, coords(&[index])))
+                .collect();
+            text.push_str(&format!(", removing {}", removed.join(", ")));
+        }
+        text
+    }
+}
+
+/// A `Board` along with the candidates of each blank square, which techniques can rule out
+/// one at a time.
+///
+/// Read-only access to the underlying `Board` is given through `Deref`.
+#[derive(Clone, Debug)]
+pub struct LogicBoard {
+    board: Board,
+    candidates: Vec<Candidates>,
+    /// Every row, column and grid, with the squares in each
+    units: Vec<(Unit, Vec<Pos>)>,
+    /// Longest chain, in links, the chaining techniques look for
+    max_chain_length: usize,
+    /// Most squares in an almost locked set the ALS techniques look for
+    max_als_size: usize,
+    /// Whether the board is known to have only one solution, so the uniqueness techniques can
+    /// be used
+    unique: bool,
+}
+
+impl Deref for LogicBoard {
+    type Target = Board;
+
+    fn deref(&self) -> &Board {
+        &self.board
+    }
+}
+
+impl From<Board> for LogicBoard {
+    fn from(board: Board) -> LogicBoard {
+        LogicBoard::new(board)
+    }
+}
+
+impl LogicBoard {
+    pub fn new(board: Board) -> LogicBoard {
+        let hmap = HintMap::from_board(&board);
+        let candidates = (0..board.num_squares)
+            .map(|index| if board.squares[index].is_none() {
+                hmap.get_candidates(index)
+            } else {
+                Candidates::empty()
+            })
+            .collect();
+        let side_length = board.side_length;
+        let units = (0..side_length)
+            .map(Unit::Row)
+            .chain((0..side_length).map(Unit::Col))
+            .chain((0..side_length).map(Unit::Grid))
+            .map(|unit| (unit, board.get_unit_indices(unit)))
+            .collect();
+        LogicBoard {
+            board,
+            candidates,
+            units,
+            max_chain_length: DEFAULT_MAX_CHAIN_LENGTH,
+            max_als_size: DEFAULT_MAX_ALS_SIZE,
+            unique: false,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+
+    /// The values still possible in a square. Filled squares have no candidates.
+    pub fn get_candidates<T: Position>(&self, pos: T) -> Candidates {
+        let index = pos.into_pos(self.board.side_length);
+        match self.candidates.get(index) {
+            Some(candidates) => *candidates,
+            None => Candidates::empty(),
+        }
+    }
+
+    /// Whether the uniqueness techniques can be used
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Let the uniqueness techniques be used, on the caller's word that the board has only one
+    /// solution. On a board with more, they can rule out values that are part of a solution.
+    pub fn assume_unique(&mut self) {
+        self.unique = true;
+    }
+
+    /// Check that the board has only one solution, letting the uniqueness techniques be used if it
+    /// does. Returns whether it does.
+    pub fn check_unique(&mut self) -> bool {
+        self.check_unique_within(&Budget::unlimited())
+    }
+
+    /// `check_unique`, giving up if the budget runs out, in which case the board isn't taken to
+    /// have one solution
+    pub(crate) fn check_unique_within(&mut self, budget: &Budget) -> bool {
+        let num_solutions = self.board.count_solutions_dlx_within(2, budget);
+        self.unique = num_solutions == 1 && !budget.is_exhausted();
+        self.unique
+    }
+
+    pub fn max_chain_length(&self) -> usize {
+        self.max_chain_length
+    }
+
+    /// Limit the length of chains the chaining techniques look for. Longer chains can find more,
+    /// but take longer to search for and to follow.
+    pub fn set_max_chain_length(&mut self, max_chain_length: usize) {
+        self.max_chain_length = max_chain_length;
+    }
+
+    pub fn max_als_size(&self) -> usize {
+        self.max_als_size
+    }
+
+    /// Limit the number of squares in the almost locked sets the ALS techniques look for. The
+    /// number of sets grows quickly with their size, especially on larger boards.
+    pub fn set_max_als_size(&mut self, max_als_size: usize) {
+        self.max_als_size = max_als_size;
+    }
+
+    /// Every row, column and grid of the board, with the squares in each
+    pub fn units(&self) -> &[(Unit, Vec<Pos>)] {
+        &self.units
+    }
+
+    /// Fill a square with a value, ruling it out from every square that shares a unit with it.
+    /// Panics if the square or the value is outside of the board, or the square is a given.
+    pub fn place(&mut self, index: Pos, val: SquareType) {
+        self.board
+            .set_deduced(index, val)
+            .expect("Deduced values are always within the board");
+        self.candidates[index] = Candidates::empty();
+        for peer in self.board.get_peer_indices(index) {
+            self.candidates[peer].remove(val);
+        }
+    }
+
+    /// Rule out a candidate of a square. Returns false if it had already been ruled out.
+    pub fn eliminate(&mut self, index: Pos, val: SquareType) -> bool {
+        let had_val = self.candidates[index].contains(val);
+        self.candidates[index].remove(val);
+        had_val
+    }
+
+    /// Every square, other than the given ones, which can still hold the value and sees all of
+    /// the given squares
+    fn seen_by_all(&self, cells: &[Pos], val: SquareType) -> Vec<(Pos, SquareType)> {
+        let mut seen: Vec<(Pos, SquareType)> = match cells.first() {
+            Some(&first) => self.get_peer_indices(first)
+                .into_iter()
+                .filter(|&index| self.get_candidates(index).contains(val))
+                .filter(|&index| cells.iter().all(|&cell| self.is_peer(cell, index)))
+                .map(|index| (index, val))
+                .collect(),
+            None => vec![],
+        };
+        seen.sort();
+        seen
+    }
+
+    /// Fill in the squares and rule out the candidates of a deduction
+    pub fn apply(&mut self, deduction: &Deduction) {
+        for &(index, val) in &deduction.placements {
+            self.place(index, val);
+        }
+        for &(index, val) in &deduction.eliminations {
+            self.eliminate(index, val);
+        }
+    }
+
+    /// Invalid if a value is repeated in a unit, a blank square has no candidates left or a
+    /// value has nowhere left to go in a unit
+    pub fn check_status(&self) -> BoardStatus {
+        let mut num_blank = 0;
+        for (_, indices) in &self.units {
+            let mut placed = Candidates::empty();
+            let mut possible = Candidates::empty();
+            for &index in indices {
+                match self.board.squares[index] {
+                    Some(val) if placed.contains(val) => return BoardStatus::Invalid,
+                    Some(val) => placed.insert(val),
+                    None if self.candidates[index].is_empty() => return BoardStatus::Invalid,
+                    None => {
+                        num_blank += 1;
+                        possible |= self.candidates[index];
+                    }
+                }
+            }
+            if (placed | possible).len() < indices.len() {
+                return BoardStatus::Invalid;
+            }
+        }
+        if num_blank == 0 {
+            BoardStatus::Solved
+        } else {
+            BoardStatus::Valid
+        }
+    }
+
+    /// Look for a deduction using a single technique, without applying it. Techniques needing a
+    /// unique solution find nothing unless the board is known to have one.
+    pub fn find(&self, technique: Technique) -> Option<Deduction> {
+        if technique.needs_unique() && !self.unique {
+            return None;
+        }
+        match technique {
+            Technique::NakedSingle => self.find_naked_single(),
+            Technique::HiddenSingle => self.find_hidden_single(),
+            Technique::Pointing => self.find_pointing(),
+            Technique::Claiming => self.find_claiming(),
+            Technique::NakedPair => self.find_naked_subset(2),
+            Technique::NakedTriple => self.find_naked_subset(3),
+            Technique::NakedQuad => self.find_naked_subset(4),
+            Technique::HiddenPair => self.find_hidden_subset(2),
+            Technique::HiddenTriple => self.find_hidden_subset(3),
+            Technique::HiddenQuad => self.find_hidden_subset(4),
+            Technique::XWing => self.find_fish(2, false),
+            Technique::Swordfish => self.find_fish(3, false),
+            Technique::Jellyfish => self.find_fish(4, false),
+            Technique::FinnedXWing => self.find_fish(2, true),
+            Technique::FinnedSwordfish => self.find_fish(3, true),
+            Technique::FinnedJellyfish => self.find_fish(4, true),
+            Technique::XYWing => self.find_xy_wing(),
+            Technique::XYZWing => self.find_xyz_wing(),
+            Technique::WWing => self.find_w_wing(),
+            Technique::SimpleColouring => self.find_simple_colouring(),
+            Technique::XChain => self.find_chain(Technique::XChain),
+            Technique::XYChain => self.find_chain(Technique::XYChain),
+            Technique::AIC => self.find_chain(Technique::AIC),
+            Technique::AlsXZ => self.find_als_xz(),
+            Technique::AlsXYWing => self.find_als_xy_wing(),
+            Technique::UniqueRectangle1 |
+            Technique::UniqueRectangle2 |
+            Technique::UniqueRectangle3 |
+            Technique::UniqueRectangle4 => self.find_unique_rectangle(technique),
+            Technique::BugPlusOne => self.find_bug_plus_one(),
+            Technique::Guess => None,
+        }
+    }
+
+    /// Apply the first deduction found, trying each technique in turn. Returns None if the board
+    /// is finished or none of the techniques can make any progress.
+    pub fn step(&mut self, techniques: &[Technique]) -> Option<Deduction> {
+        if self.check_status().is_finished() {
+            return None;
+        }
+        let deduction = techniques
+            .iter()
+            .filter_map(|&technique| self.find(technique))
+            .next();
+        if let Some(ref deduction) = deduction {
+            self.apply(deduction);
+        }
+        deduction
+    }
+
+    /// Keep applying deductions until the board is finished or the techniques get stuck,
+    /// returning every deduction made
+    pub fn solve(&mut self, techniques: &[Technique]) -> Vec<Deduction> {
+        let mut deductions = vec![];
+        while let Some(deduction) = self.step(techniques) {
+            deductions.push(deduction);
+        }
+        deductions
+    }
+}
+
+impl Board {
+    /// Solve the board with the given techniques, trying them in order and going back to the
+    /// first after every deduction. Uniqueness techniques are only used once the board has been
+    /// checked to have one solution.
+    pub fn solve_standard_with(&mut self, techniques: &[Technique]) -> BoardStatus {
+        self.solve_standard_with_within(techniques, &SolveOptions::default(), &Budget::unlimited())
+    }
+
+    /// `solve_standard_with`, with the limits of the options, stopping early if the budget runs
+    /// out
+    pub(crate) fn solve_standard_with_within(
+        &mut self,
+        techniques: &[Technique],
+        options: &SolveOptions,
+        budget: &Budget,
+    ) -> BoardStatus {
+        let status = self.check_status();
+        if status.is_finished() {
+            return status;
+        }
+
+        let mut lboard = LogicBoard::new(self.clone());
+        lboard.set_max_chain_length(options.max_chain_length.unwrap_or(DEFAULT_MAX_CHAIN_LENGTH));
+        lboard.set_max_als_size(options.max_als_size.unwrap_or(DEFAULT_MAX_ALS_SIZE));
+        if techniques.iter().any(|technique| technique.needs_unique()) {
+            lboard.check_unique_within(budget);
+        }
+        loop {
+            if budget.is_exhausted() {
+                budget.record(&lboard);
+                *self = lboard.into_board();
+                return BoardStatus::Valid;
+            }
+            match lboard.step(techniques) {
+                Some(deduction) => {
+                    for _ in &deduction.placements {
+                        budget.place(deduction.technique);
+                    }
+                    budget.eliminate(deduction.technique, deduction.eliminations.len());
+                }
+                None => break,
+            }
+        }
+        let status = lboard.check_status();
+        *self = lboard.into_board();
+        status
+    }
+}
+
+/// Every way of choosing `size` of the numbers `0..num`, in lexicographic order
+fn combinations(num: usize, size: usize) -> Vec<Vec<usize>> {
+    let mut all = vec![];
+    let mut chosen = Vec::with_capacity(size);
+    add_combinations(num, size, 0, &mut chosen, &mut all);
+    all
+}
+
+fn add_combinations(
+    num: usize,
+    size: usize,
+    start: usize,
+    chosen: &mut Vec<usize>,
+    all: &mut Vec<Vec<usize>>,
+) {
+    if chosen.len() == size {
+        all.push(chosen.clone());
+        return;
+    }
+    // Leave enough numbers to fill the rest of the combination
+    for next in start..num + 1 + chosen.len() - size {
+        chosen.push(next);
+        add_combinations(num, size, next + 1, chosen, all);
+        chosen.pop();
+    }
+}
+
+
+/// Rule out every candidate of the square except the given ones
+#[cfg(test)]
+fn keep_only(lboard: &mut LogicBoard, coord: (usize, usize), values: &[SquareType]) {
+    let index = coord.into_pos(lboard.side_length);
+    for val in lboard.get_candidates(index) {
+        if !values.contains(&val) {
+            lboard.eliminate(index, val);
+        }
+    }
+}
+
+/// A board with every candidate, apart from the given squares, which only keep the given ones
+#[cfg(test)]
+fn with_candidates(board: Board, squares: &[((usize, usize), &[SquareType])]) -> LogicBoard {
+    let mut lboard = LogicBoard::new(board);
+    for &(coord, values) in squares {
+        keep_only(&mut lboard, coord, values);
+    }
+    lboard
+}
+
+/// Rule out `val` from every square of the units except the given ones
+#[cfg(test)]
+fn restrict(lboard: &mut LogicBoard, val: SquareType, units: &[Unit], keep: &[(usize, usize)]) {
+    let side_length = lboard.side_length;
+    for &unit in units {
+        for index in lboard.get_unit_indices(unit) {
+            if !keep.iter().any(|&coord| coord.into_pos(side_length) == index) {
+                lboard.eliminate(index, val);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(
+            vec![vec![0, 1], vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3], vec![2, 3]],
+            combinations(4, 2)
+        );
+        assert_eq!(vec![vec![0, 1, 2]], combinations(3, 3));
+        assert!(combinations(2, 3).is_empty());
+        assert_eq!(126, combinations(9, 4).len());
+    }
+
+    #[test]
+    fn test_logic_board() {
+        let mut lboard = LogicBoard::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+        assert_eq!(BoardStatus::Valid, lboard.check_status());
+        assert_eq!(vec![9], lboard.get_candidates(9).to_vec());
+
+        lboard.place(9, 9);
+        assert!(lboard.get_candidates(9).is_empty());
+        for peer in lboard.get_peer_indices(9) {
+            assert!(!lboard.get_candidates(peer).contains(9));
+        }
+
+        // Ruling out every candidate of a square leaves no solution
+        let index = (0..lboard.num_squares)
+            .find(|&index| lboard.squares[index].is_none())
+            .unwrap();
+        for val in lboard.get_candidates(index) {
+            assert!(lboard.eliminate(index, val));
+        }
+        assert!(!lboard.eliminate(index, 1));
+        assert_eq!(BoardStatus::Invalid, lboard.check_status());
+    }
+
+    #[test]
+    fn test_solve_singles() {
+        let mut lboard = LogicBoard::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+        let deductions = lboard.solve(&[Technique::NakedSingle, Technique::HiddenSingle]);
+        assert_eq!(SOLVED_PUZZLE_STR, lboard.to_string());
+        assert_eq!(Board::from_string(UNSOLVED_PUZZLE_STR).num_remaining(), deductions.len());
+        assert!(deductions.iter().all(|deduction| deduction.placements.len() == 1));
+
+        // Stops once the board is solved
+        assert_eq!(None, lboard.step(Technique::logical()));
+        assert_eq!(BoardStatus::Solved, lboard.check_status());
+    }
+
+    #[test]
+    fn test_solve_standard_with() {
+        // The singles in `solve_standard` get stuck on these puzzles, but subsets can finish them
+        let naked_pairs = concat!(
+            "0,0,5,0,6,7,0,0,2,2,6,8,1,0,5,0,3,0,0,0,1,3,2,8,0,0,0,0,1,0,6,0,3,0,2,8,",
+            "3,0,0,0,0,0,6,5,0,0,2,0,5,0,0,0,0,0,0,0,0,0,4,0,0,1,7,0,0,4,8,0,0,0,9,0,",
+            "0,0,2,0,0,0,0,0,6"
+        );
+        let hidden_pair = concat!(
+            "0,1,9,3,0,0,4,0,0,6,8,7,0,0,4,0,0,0,0,0,0,1,0,0,0,0,0,7,0,8,0,6,0,0,5,0,",
+            "2,0,0,0,3,0,0,0,0,0,5,6,0,8,1,0,3,7,0,7,4,0,0,3,0,6,5,1,0,0,0,0,7,0,0,4,",
+            "0,2,5,0,0,0,7,1,0"
+        );
+        let singles = [Technique::NakedSingle, Technique::HiddenSingle];
+        for &(puzzle, technique) in
+            [(naked_pairs, Technique::NakedPair), (hidden_pair, Technique::HiddenPair)].iter()
+        {
+            let mut board = Board::from_string(puzzle);
+            assert_eq!(BoardStatus::Valid, board.clone().solve_standard());
+            assert_eq!(BoardStatus::Valid, board.clone().solve_standard_with(&singles));
+            assert_eq!(BoardStatus::Solved, board.solve_standard_with(Technique::logical()));
+            assert!(board.check_status().is_solved());
+
+            let mut lboard = LogicBoard::new(Board::from_string(puzzle));
+            let deductions = lboard.solve(&[singles[0], singles[1], technique]);
+            assert_eq!(board, *lboard.board());
+            assert!(deductions.iter().any(|deduction| deduction.technique == technique));
+        }
+    }
+
+    #[test]
+    fn test_solve_intersections() {
+        // Singles get stuck on these puzzles, but pointing or claiming can finish them
+        let pointing = concat!(
+            "0,0,0,0,6,0,4,0,0,0,0,0,4,0,2,0,8,0,5,1,0,0,0,0,0,0,0,7,4,0,0,8,0,0,0,0,",
+            "0,0,3,0,0,0,0,9,0,0,9,0,0,2,7,5,0,0,4,8,7,0,3,6,0,5,2,6,3,0,2,0,5,0,0,0,",
+            "9,0,0,0,4,8,1,6,0"
+        );
+        let claiming = concat!(
+            "0,0,0,0,0,0,0,1,0,0,8,0,0,0,0,0,0,7,0,5,0,9,6,0,3,0,0,0,0,9,6,0,0,4,0,0,",
+            "0,4,0,0,9,0,0,8,5,0,0,0,4,8,0,1,0,6,0,0,3,0,0,0,7,0,0,0,0,8,0,1,0,6,0,2,",
+            "6,0,0,7,0,0,8,4,1"
+        );
+        for &(puzzle, technique) in
+            [(pointing, Technique::Pointing), (claiming, Technique::Claiming)].iter()
+        {
+            let techniques = [Technique::NakedSingle, Technique::HiddenSingle, technique];
+            let mut board = Board::from_string(puzzle);
+            assert_eq!(BoardStatus::Valid, board.clone().solve_standard_with(&techniques[..2]));
+            assert_eq!(BoardStatus::Solved, board.solve_standard_with(&techniques));
+
+            let mut lboard = LogicBoard::new(Board::from_string(puzzle));
+            let deductions = lboard.solve(&techniques);
+            assert_eq!(board, *lboard.board());
+            assert!(deductions.iter().any(|deduction| deduction.technique == technique));
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let board = Board::default();
+        let deduction = Deduction {
+            technique: Technique::NakedPair,
+            pattern: Pattern::Subset {
+                unit: Unit::Row(1),
+                cells: vec![9, 10],
+                values: vec![1, 2],
+            },
+            placements: vec![],
+            eliminations: vec![(11, 1), (12, 2)],
+        };
+        assert_eq!(
+            "Naked Pair in row 1: (0, 1) (1, 1) hold 1, 2, removing 1 from (2, 1), 2 from (3, 1)",
+            deduction.explain(&board)
+        );
+        let deduction = Deduction {
+            technique: Technique::HiddenSingle,
+            pattern: Pattern::Single {
+                unit: Some(Unit::Grid(0)),
+            },
+            placements: vec![(0, 5)],
+            eliminations: vec![],
+        };
+        assert_eq!("Hidden Single in grid 0, (0, 0) is 5", deduction.explain(&board));
+        let deduction = Deduction {
+            technique: Technique::Pointing,
+            pattern: Pattern::Intersection {
+                base: Unit::Grid(0),
+                cover: Unit::Col(2),
+                value: 3,
+                cells: vec![2, 11],
+            },
+            placements: vec![],
+            eliminations: vec![(47, 3)],
+        };
+        assert_eq!(
+            "Pointing in grid 0: 3 only in (2, 0) (2, 1), all in column 2, removing 3 from (2, 5)",
+            deduction.explain(&board)
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use solver::logic::{Deduction, LogicBoard, Pattern};
+use solver::technique::Technique;
+
+impl LogicBoard {
+    /// A blank square with only one candidate left
+    pub(super) fn find_naked_single(&self) -> Option<Deduction> {
+        (0..self.num_squares)
+            .filter(|&index| self.squares[index].is_none())
+            .filter_map(|index| {
+                self.get_candidates(index)
+                    .single_value()
+                    .map(|val| (index, val))
+            })
+            .map(|placement| {
+                Deduction {
+                    technique: Technique::NakedSingle,
+                    pattern: Pattern::Single { unit: None },
+                    placements: vec![placement],
+                    eliminations: vec![],
+                }
+            })
+            .next()
+    }
+
+    /// A value which can only go in one square of a unit
+    pub(super) fn find_hidden_single(&self) -> Option<Deduction> {
+        for &(unit, ref indices) in self.units() {
+            for val in self.min_value..self.max_value + 1 {
+                let mut places = indices
+                    .iter()
+                    .filter(|&&index| self.get_candidates(index).contains(val));
+                if let (Some(&index), None) = (places.next(), places.next()) {
+                    return Some(Deduction {
+                        technique: Technique::HiddenSingle,
+                        pattern: Pattern::Single { unit: Some(unit) },
+                        placements: vec![(index, val)],
+                        eliminations: vec![],
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+    #[test]
+    fn test_naked_single() {
+        let mut lboard = LogicBoard::new(Board::default());
+        assert_eq!(None, lboard.find(Technique::NakedSingle));
+        for val in 1..9 {
+            lboard.eliminate(40, val);
+        }
+        let expected = Deduction {
+            technique: Technique::NakedSingle,
+            pattern: Pattern::Single { unit: None },
+            placements: vec![(40, 9)],
+            eliminations: vec![],
+        };
+        assert_eq!(Some(expected), lboard.find(Technique::NakedSingle));
+    }
+
+    #[test]
+    fn test_hidden_single() {
+        let mut lboard = LogicBoard::new(Board::default());
+        assert_eq!(None, lboard.find(Technique::HiddenSingle));
+        // 7 can only go in the last square of the second column
+        for row in 0..8 {
+            lboard.eliminate((1, row).into_pos(9), 7);
+        }
+        let expected = Deduction {
+            technique: Technique::HiddenSingle,
+            pattern: Pattern::Single {
+                unit: Some(Unit::Col(1)),
+            },
+            placements: vec![((1, 8).into_pos(9), 7)],
+            eliminations: vec![],
+        };
+        assert_eq!(Some(expected), lboard.find(Technique::HiddenSingle));
+    }
+}
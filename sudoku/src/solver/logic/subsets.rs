@@ -0,0 +1,265 @@
+use board::*;
+use candidates::Candidates;
+use solver::logic::{combinations, Deduction, LogicBoard, Pattern};
+use solver::technique::Technique;
+
+impl LogicBoard {
+    /// `size` squares of a unit whose candidates, between them, are only `size` values. Those
+    /// values must go in those squares, so can be ruled out from the rest of the unit.
+    pub(super) fn find_naked_subset(&self, size: usize) -> Option<Deduction> {
+        let technique = match size {
+            2 => Technique::NakedPair,
+            3 => Technique::NakedTriple,
+            _ => Technique::NakedQuad,
+        };
+        for &(unit, ref indices) in self.units() {
+            let blanks: Vec<Pos> = indices
+                .iter()
+                .cloned()
+                .filter(|&index| !self.get_candidates(index).is_empty())
+                .collect();
+            // Squares with a single candidate are left to the singles
+            let small: Vec<Pos> = blanks
+                .iter()
+                .cloned()
+                .filter(|&index| {
+                    let num_candidates = self.get_candidates(index).len();
+                    num_candidates > 1 && num_candidates <= size
+                })
+                .collect();
+            if blanks.len() <= size || small.len() < size {
+                continue;
+            }
+
+            for chosen in combinations(small.len(), size) {
+                let cells: Vec<Pos> = chosen.iter().map(|&i| small[i]).collect();
+                let values = cells.iter().fold(Candidates::empty(), |values, &index| {
+                    values | self.get_candidates(index)
+                });
+                if values.len() != size {
+                    continue;
+                }
+                let eliminations: Vec<(Pos, SquareType)> = blanks
+                    .iter()
+                    .filter(|index| !cells.contains(index))
+                    .flat_map(|&index| {
+                        (self.get_candidates(index) & values)
+                            .into_iter()
+                            .map(move |val| (index, val))
+                    })
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(Deduction {
+                        technique,
+                        pattern: Pattern::Subset {
+                            unit,
+                            cells,
+                            values: values.to_vec(),
+                        },
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// `size` values which can only go in the same `size` squares of a unit. Those squares must
+    /// hold those values, so their other candidates can be ruled out.
+    pub(super) fn find_hidden_subset(&self, size: usize) -> Option<Deduction> {
+        let technique = match size {
+            2 => Technique::HiddenPair,
+            3 => Technique::HiddenTriple,
+            _ => Technique::HiddenQuad,
+        };
+        for &(unit, ref indices) in self.units() {
+            let num_blanks = indices
+                .iter()
+                .filter(|&&index| !self.get_candidates(index).is_empty())
+                .count();
+            if num_blanks <= size {
+                continue;
+            }
+            // The squares each value can go in. Values with only one are left to the singles.
+            let places: Vec<(SquareType, Vec<Pos>)> = (self.min_value..self.max_value + 1)
+                .map(|val| {
+                    let cells = indices
+                        .iter()
+                        .cloned()
+                        .filter(|&index| self.get_candidates(index).contains(val))
+                        .collect::<Vec<Pos>>();
+                    (val, cells)
+                })
+                .filter(|(_, cells)| cells.len() > 1 && cells.len() <= size)
+                .collect();
+            if places.len() < size {
+                continue;
+            }
+
+            for chosen in combinations(places.len(), size) {
+                let mut cells: Vec<Pos> = chosen
+                    .iter()
+                    .flat_map(|&i| places[i].1.iter().cloned())
+                    .collect();
+                cells.sort();
+                cells.dedup();
+                if cells.len() != size {
+                    continue;
+                }
+                let values: Candidates = chosen.iter().map(|&i| places[i].0).collect();
+                let eliminations: Vec<(Pos, SquareType)> = cells
+                    .iter()
+                    .flat_map(|&index| {
+                        (self.get_candidates(index) - values)
+                            .into_iter()
+                            .map(move |val| (index, val))
+                    })
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(Deduction {
+                        technique,
+                        pattern: Pattern::Subset {
+                            unit,
+                            cells,
+                            values: values.to_vec(),
+                        },
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+    /// A blank board where only the first two squares of the first row can hold 1 and 2
+    fn naked_pair_board() -> LogicBoard {
+        let mut lboard = LogicBoard::new(Board::default());
+        for index in 0..2 {
+            for val in 3..10 {
+                lboard.eliminate(index, val);
+            }
+        }
+        lboard
+    }
+
+    #[test]
+    fn test_naked_pair() {
+        let mut lboard = naked_pair_board();
+        let deduction = lboard.find(Technique::NakedPair).expect("There is a naked pair");
+        assert_eq!(
+            Pattern::Subset {
+                unit: Unit::Row(0),
+                cells: vec![0, 1],
+                values: vec![1, 2],
+            },
+            deduction.pattern
+        );
+        let expected: Vec<(Pos, SquareType)> = (2..9)
+            .flat_map(|index| vec![(index, 1), (index, 2)])
+            .collect();
+        assert_eq!(expected, deduction.eliminations);
+
+        // The same pair is in the first grid too
+        lboard.apply(&deduction);
+        let deduction = lboard.find(Technique::NakedPair).expect("There is a naked pair");
+        assert_eq!(
+            Pattern::Subset {
+                unit: Unit::Grid(0),
+                cells: vec![0, 1],
+                values: vec![1, 2],
+            },
+            deduction.pattern
+        );
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::NakedPair));
+        assert_eq!(None, lboard.find(Technique::NakedTriple));
+    }
+
+    #[test]
+    fn test_naked_triple() {
+        // Squares in the first column holding {1, 2}, {2, 3} and {1, 3}
+        let mut lboard = LogicBoard::new(Board::default());
+        let triple = [(0, [1, 2]), (9, [2, 3]), (18, [1, 3])];
+        for &(index, keep) in triple.iter() {
+            for val in 1..10 {
+                if !keep.contains(&val) {
+                    lboard.eliminate(index, val);
+                }
+            }
+        }
+        assert_eq!(None, lboard.find(Technique::NakedPair));
+        let deduction = lboard.find(Technique::NakedTriple).expect("There is a naked triple");
+        assert_eq!(Technique::NakedTriple, deduction.technique);
+        match deduction.pattern {
+            Pattern::Subset {
+                unit, cells, values
+            } => {
+                assert_eq!(Unit::Col(0), unit);
+                assert_eq!(vec![0, 9, 18], cells);
+                assert_eq!(vec![1, 2, 3], values);
+            }
+            pattern => panic!("Expected a subset, got {:?}", pattern),
+        }
+        assert_eq!(6 * 3, deduction.eliminations.len());
+    }
+
+    #[test]
+    fn test_hidden_pair() {
+        // 3 and 4 can only go in the first two squares of the first row
+        let mut lboard = LogicBoard::new(Board::default());
+        for index in 2..9 {
+            lboard.eliminate(index, 3);
+            lboard.eliminate(index, 4);
+        }
+        assert_eq!(None, lboard.find(Technique::NakedPair));
+        let deduction = lboard.find(Technique::HiddenPair).expect("There is a hidden pair");
+        assert_eq!(
+            Pattern::Subset {
+                unit: Unit::Row(0),
+                cells: vec![0, 1],
+                values: vec![3, 4],
+            },
+            deduction.pattern
+        );
+        assert_eq!(2 * 7, deduction.eliminations.len());
+        assert!(deduction.eliminations.iter().all(|&(index, val)| {
+            index < 2 && val != 3 && val != 4
+        }));
+
+        // Once applied, the hidden pair has become a naked pair
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::HiddenPair));
+        assert!(lboard.find(Technique::NakedPair).is_some());
+    }
+
+    #[test]
+    fn test_hidden_quad() {
+        // 1 to 4 can only go in the first four squares of the last grid of a 16x16 board
+        let mut lboard = LogicBoard::new(Board::from_base_num(4).unwrap());
+        let grid = lboard.get_grid_indices(15);
+        for &index in &grid[4..] {
+            for val in 1..5 {
+                lboard.eliminate(index, val);
+            }
+        }
+        let deduction = lboard.find(Technique::HiddenQuad).expect("There is a hidden quad");
+        assert_eq!(
+            Pattern::Subset {
+                unit: Unit::Grid(15),
+                cells: grid[..4].to_vec(),
+                values: vec![1, 2, 3, 4],
+            },
+            deduction.pattern
+        );
+        assert_eq!(4 * 12, deduction.eliminations.len());
+    }
+}
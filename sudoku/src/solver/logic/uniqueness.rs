@@ -0,0 +1,404 @@
+use board::*;
+use candidates::Candidates;
+use solver::logic::{combinations, Deduction, LogicBoard, Pattern};
+use solver::technique::Technique;
+use std::cmp;
+
+/// Two squares of a line which only have the same two candidates, and two squares across from
+/// them in another line which have both as well, together spanning two grids. If none of them
+/// held anything else, the two values could be swapped to give a second solution.
+struct Rectangle {
+    values: Candidates,
+    floor: [Pos; 2],
+    roof: [Pos; 2],
+}
+
+impl Rectangle {
+    fn pattern(&self, cells: Vec<Pos>) -> Pattern {
+        Pattern::Rectangle {
+            values: self.values.to_vec(),
+            floor: self.floor.to_vec(),
+            roof: self.roof.to_vec(),
+            cells,
+        }
+    }
+}
+
+impl LogicBoard {
+    fn rectangles(&self) -> Vec<Rectangle> {
+        let side_length = self.side_length;
+        let mut rectangles = vec![];
+        for first in 0..self.num_squares {
+            let values = self.get_candidates(first);
+            if values.len() != 2 {
+                continue;
+            }
+            let (col, row) = first.into_coord(side_length);
+            for &by_row in [true, false].iter() {
+                let (start, line) = if by_row { (col, row) } else { (row, col) };
+                let square = |along: usize, across: usize| if by_row {
+                    (along, across).into_pos(side_length)
+                } else {
+                    (across, along).into_pos(side_length)
+                };
+
+                for along in start + 1..side_length {
+                    let second = square(along, line);
+                    if self.get_candidates(second) != values {
+                        continue;
+                    }
+                    for across in (0..side_length).filter(|&across| across != line) {
+                        let roof = [square(start, across), square(along, across)];
+                        let shared = |index: &Pos| self.get_candidates(*index) & values;
+                        if roof.iter().any(|index| shared(index) != values) {
+                            continue;
+                        }
+                        let mut grids: Vec<usize> = [first, second, roof[0], roof[1]]
+                            .iter()
+                            .map(|&index| self.get_grid_num(index))
+                            .collect();
+                        grids.sort();
+                        grids.dedup();
+                        if grids.len() == 2 {
+                            rectangles.push(Rectangle {
+                                values,
+                                floor: [first, second],
+                                roof,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        rectangles
+    }
+
+    /// Units holding both squares of the roof
+    fn roof_units(&self, rectangle: &Rectangle) -> Vec<Unit> {
+        let other_units = self.get_units(rectangle.roof[1]);
+        self.get_units(rectangle.roof[0])
+            .iter()
+            .cloned()
+            .filter(|unit| other_units.contains(unit))
+            .collect()
+    }
+
+    /// Other squares of a unit the roof shares, which form a naked subset with the roof's extra
+    /// candidates, since one of the roof's squares must hold one of them
+    fn find_rectangle_subset(
+        &self,
+        rectangle: &Rectangle,
+        extras: Candidates,
+    ) -> Option<Deduction> {
+        for unit in self.roof_units(rectangle) {
+            let others: Vec<Pos> = self.get_unit_indices(unit)
+                .into_iter()
+                .filter(|index| !rectangle.roof.contains(index))
+                .filter(|&index| !self.get_candidates(index).is_empty())
+                .collect();
+            for size in 1..cmp::min(4, others.len() + 1) {
+                for chosen in combinations(others.len(), size) {
+                    let cells: Vec<Pos> = chosen.iter().map(|&i| others[i]).collect();
+                    let values = cells.iter().fold(extras, |values, &index| {
+                        values | self.get_candidates(index)
+                    });
+                    if values.len() != size + 1 {
+                        continue;
+                    }
+                    let eliminations: Vec<(Pos, SquareType)> = others
+                        .iter()
+                        .filter(|index| !cells.contains(index))
+                        .flat_map(|&index| {
+                            (self.get_candidates(index) & values)
+                                .into_iter()
+                                .map(move |val| (index, val))
+                        })
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(Deduction {
+                            technique: Technique::UniqueRectangle3,
+                            pattern: rectangle.pattern(cells),
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// A rectangle which, to keep the solution unique, rules something out. Only used once the
+    /// board is known to have one solution.
+    pub(super) fn find_unique_rectangle(&self, technique: Technique) -> Option<Deduction> {
+        for rectangle in self.rectangles() {
+            let values = rectangle.values;
+            let roof = rectangle.roof;
+            let extras = [
+                self.get_candidates(roof[0]) - values,
+                self.get_candidates(roof[1]) - values,
+            ];
+            let eliminations: Vec<(Pos, SquareType)> = match technique {
+                // One square of the roof only has the two values, so the other must be something
+                // else
+                Technique::UniqueRectangle1 => match (extras[0].is_empty(), extras[1].is_empty()) {
+                    (true, false) => values.into_iter().map(|val| (roof[1], val)).collect(),
+                    (false, true) => values.into_iter().map(|val| (roof[0], val)).collect(),
+                    _ => continue,
+                },
+                // The roof's one extra candidate must go in one of its squares
+                Technique::UniqueRectangle2 => match extras[0].single_value() {
+                    Some(extra) if extras[1] == extras[0] => self.seen_by_all(&roof, extra),
+                    _ => continue,
+                },
+                Technique::UniqueRectangle3 => {
+                    if extras[0].is_empty() || extras[1].is_empty() {
+                        continue;
+                    }
+                    match self.find_rectangle_subset(&rectangle, extras[0] | extras[1]) {
+                        Some(deduction) => return Some(deduction),
+                        None => continue,
+                    }
+                }
+                // One of the values must go in the roof, so the other can't without making the
+                // deadly pattern
+                Technique::UniqueRectangle4 => {
+                    let mut eliminations = vec![];
+                    'units: for unit in self.roof_units(&rectangle) {
+                        let indices = self.get_unit_indices(unit);
+                        for val in values {
+                            let num_places = indices
+                                .iter()
+                                .filter(|&&index| self.get_candidates(index).contains(val))
+                                .count();
+                            let mut others = values;
+                            others.remove(val);
+                            if let (2, Some(other)) = (num_places, others.single_value()) {
+                                eliminations = roof.iter().map(|&index| (index, other)).collect();
+                                break 'units;
+                            }
+                        }
+                    }
+                    eliminations
+                }
+                _ => return None,
+            };
+            if !eliminations.is_empty() {
+                return Some(Deduction {
+                    technique,
+                    pattern: rectangle.pattern(vec![]),
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+        None
+    }
+
+    /// Every blank square has two candidates except one with three. Each value of the others would
+    /// have two places in every unit, which can always be swapped, so the square must hold the
+    /// value with three places in its units.
+    pub(super) fn find_bug_plus_one(&self) -> Option<Deduction> {
+        let mut extra = None;
+        for index in 0..self.num_squares {
+            match self.get_candidates(index).len() {
+                0 | 2 => {}
+                3 if extra.is_none() => extra = Some(index),
+                _ => return None,
+            }
+        }
+        let index = extra?;
+        let units = self.get_units(index);
+        self.get_candidates(index)
+            .into_iter()
+            .find(|&val| {
+                units.iter().all(|&unit| {
+                    self.get_unit_indices(unit)
+                        .into_iter()
+                        .filter(|&other| self.get_candidates(other).contains(val))
+                        .count() == 3
+                })
+            })
+            .map(|val| {
+                Deduction {
+                    technique: Technique::BugPlusOne,
+                    pattern: Pattern::Single { unit: None },
+                    placements: vec![(index, val)],
+                    eliminations: vec![],
+                }
+            })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+    /// A rectangle on 1 and 2 in the first and fifth columns of the first two rows, with the
+    /// roof's candidates given
+    fn rectangle(roof: [&[SquareType]; 2]) -> LogicBoard {
+        with_candidates(
+            Board::default(),
+            &[((0, 0), &[1, 2]), ((4, 0), &[1, 2]), ((0, 1), roof[0]), ((4, 1), roof[1])],
+        )
+    }
+
+    #[test]
+    fn test_unique_rectangle_1() {
+        let mut lboard = rectangle([&[1, 2], &[1, 2, 3, 4, 5, 6, 7, 8, 9]]);
+        // An empty board has plenty of solutions, so the rectangle proves nothing
+        assert_eq!(None, lboard.find(Technique::UniqueRectangle1));
+        assert!(!lboard.check_unique());
+        assert_eq!(None, lboard.find(Technique::UniqueRectangle1));
+
+        lboard.assume_unique();
+        let deduction = lboard.find(Technique::UniqueRectangle1).expect("There is a rectangle");
+        assert_eq!(
+            Pattern::Rectangle {
+                values: vec![1, 2],
+                floor: vec![0, 4],
+                roof: vec![9, 13],
+                cells: vec![],
+            },
+            deduction.pattern
+        );
+        assert_eq!(vec![(13, 1), (13, 2)], deduction.eliminations);
+        assert_eq!(
+            concat!(
+                "Unique Rectangle Type 1 on 1, 2: floor (0, 0) (4, 0), roof (0, 1) (4, 1), ",
+                "removing 1 from (4, 1), 2 from (4, 1)"
+            ),
+            deduction.explain(&lboard)
+        );
+
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::UniqueRectangle1));
+    }
+
+    #[test]
+    fn test_unique_rectangle_2() {
+        let mut lboard = rectangle([&[1, 2, 7], &[1, 2, 7]]);
+        lboard.assume_unique();
+        assert_eq!(None, lboard.find(Technique::UniqueRectangle1));
+        let deduction = lboard.find(Technique::UniqueRectangle2).expect("There is a rectangle");
+        // 7 is in one of the roof's squares, which only share the second row
+        let expected: Vec<(Pos, SquareType)> = [10, 11, 12, 14, 15, 16, 17]
+            .iter()
+            .map(|&index| (index, 7))
+            .collect();
+        assert_eq!(expected, deduction.eliminations);
+    }
+
+    #[test]
+    fn test_unique_rectangle_3() {
+        // The roof has 3 or 4 as well, making a naked pair with the eighth square of the row
+        let mut lboard = rectangle([&[1, 2, 3], &[1, 2, 4]]);
+        keep_only(&mut lboard, (7, 1), &[3, 4]);
+        lboard.assume_unique();
+        assert_eq!(None, lboard.find(Technique::UniqueRectangle2));
+        let deduction = lboard.find(Technique::UniqueRectangle3).expect("There is a rectangle");
+        assert_eq!(
+            Pattern::Rectangle {
+                values: vec![1, 2],
+                floor: vec![0, 4],
+                roof: vec![9, 13],
+                cells: vec![16],
+            },
+            deduction.pattern
+        );
+        let expected: Vec<(Pos, SquareType)> = [10, 11, 12, 14, 15, 17]
+            .iter()
+            .flat_map(|&index| vec![(index, 3), (index, 4)])
+            .collect();
+        assert_eq!(expected, deduction.eliminations);
+        assert_eq!(vec![0, 4, 9, 13, 16], deduction.cells());
+    }
+
+    #[test]
+    fn test_unique_rectangle_3_few_squares() {
+        // The roof's row only has one other blank square, too few for a subset of any size
+        let mut board = Board::default();
+        for (&col, val) in [1, 2, 3, 5, 6, 7].iter().zip(4..10) {
+            board.set_val((col, 1), Some(val)).unwrap();
+        }
+        let mut lboard = with_candidates(board, &[((0, 0), &[1, 2]), ((4, 0), &[1, 2])]);
+        lboard.assume_unique();
+        assert_eq!(None, lboard.find(Technique::UniqueRectangle3));
+    }
+
+    #[test]
+    fn test_unique_rectangle_4() {
+        // 1 can only go in the roof of the second row, so neither of them can be 2
+        let mut lboard = rectangle([&[1, 2, 3, 4, 5, 6, 7, 8, 9], &[1, 2, 3, 4, 5, 6, 7, 8, 9]]);
+        for col in [1, 2, 3, 5, 6, 7, 8].iter() {
+            lboard.eliminate((*col, 1).into_pos(9), 1);
+        }
+        lboard.assume_unique();
+        assert_eq!(None, lboard.find(Technique::UniqueRectangle3));
+        let deduction = lboard.find(Technique::UniqueRectangle4).expect("There is a rectangle");
+        assert_eq!(vec![(9, 2), (13, 2)], deduction.eliminations);
+    }
+
+    #[test]
+    fn test_solve_uniqueness() {
+        // Each of these reaches the technique when it's tried straight after the singles
+        let puzzles = [
+            (
+                Technique::UniqueRectangle3,
+                concat!(
+                    "0,0,0,0,0,0,0,4,9,0,5,0,0,9,0,0,8,0,6,0,9,4,0,0,3,0,0,0,0,6,0,0,8,0,0,4,",
+                    "0,0,1,7,0,0,6,2,3,3,0,0,9,0,0,0,0,0,0,0,0,3,0,9,0,0,1,0,0,0,0,0,0,0,0,0,",
+                    "1,3,8,5,4,0,7,9,2"
+                ),
+            ),
+            (
+                Technique::UniqueRectangle4,
+                concat!(
+                    "6,0,8,9,0,0,1,0,0,0,4,2,0,0,0,0,7,8,0,0,5,8,0,0,2,0,0,0,5,0,0,2,6,0,0,0,",
+                    "0,0,0,0,0,8,6,0,0,0,0,3,5,0,7,8,0,0,0,0,0,2,0,0,4,1,3,0,0,1,0,0,0,0,2,0,",
+                    "0,2,6,0,0,1,7,0,0"
+                ),
+            ),
+            (
+                Technique::BugPlusOne,
+                concat!(
+                    "0,1,0,8,7,0,6,0,0,0,0,6,5,0,2,7,0,1,0,0,0,9,0,0,0,0,0,0,0,7,4,0,0,0,0,0,",
+                    "0,4,0,0,0,7,0,5,2,0,0,1,2,9,0,0,6,7,0,6,0,3,0,0,0,0,0,2,0,0,6,0,8,1,0,9,",
+                    "9,0,3,0,5,0,0,0,0"
+                ),
+            ),
+        ];
+        let mut techniques = vec![Technique::NakedSingle, Technique::HiddenSingle];
+        techniques.extend(Technique::uniqueness().iter().cloned());
+        techniques.extend(Technique::logical()[2..].iter().cloned());
+
+        for &(technique, puzzle) in puzzles.iter() {
+            let mut solution = Board::from_string(puzzle);
+            assert_eq!(BoardStatus::Solved, solution.solve_standard_with(Technique::logical()));
+
+            let mut lboard = LogicBoard::new(Board::from_string(puzzle));
+            assert!(lboard.check_unique());
+            let deductions = lboard.solve(&techniques);
+            assert_eq!(solution, *lboard.board());
+            assert!(deductions.iter().any(|deduction| deduction.technique == technique));
+        }
+    }
+
+    #[test]
+    fn test_multiple_solutions() {
+        // Swapping the values of a rectangle really does give another solution here, so the
+        // uniqueness techniques must never be used
+        let mut techniques = Technique::uniqueness().to_vec();
+        techniques.extend(Technique::logical().iter().cloned());
+        let mut lboard = LogicBoard::new(Board::from_string(HARD_PUZZLE_STR));
+        assert!(!lboard.check_unique());
+        let deductions = lboard.solve(&techniques);
+        assert!(deductions.iter().all(|deduction| !deduction.technique.needs_unique()));
+
+        let mut board = Board::from_string(HARD_PUZZLE_STR);
+        assert_eq!(BoardStatus::Valid, board.solve_standard_with(&techniques));
+        assert_eq!(*lboard.board(), board);
+    }
+}
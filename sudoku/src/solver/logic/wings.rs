@@ -0,0 +1,275 @@
+use board::*;
+use solver::logic::{Deduction, LogicBoard, Pattern};
+use solver::technique::Technique;
+
+impl LogicBoard {
+    /// Blank squares with exactly this many candidates
+    fn squares_with_candidates(&self, num_candidates: usize) -> Vec<Pos> {
+        (0..self.num_squares)
+            .filter(|&index| self.get_candidates(index).len() == num_candidates)
+            .collect()
+    }
+
+    /// A pivot with candidates XY, seeing a pincer with XZ and another with YZ. Whether the pivot
+    /// is X or Y, one of the pincers is Z, so Z can be ruled out from squares seeing both.
+    pub(super) fn find_xy_wing(&self) -> Option<Deduction> {
+        let bivalues = self.squares_with_candidates(2);
+        for &pivot in &bivalues {
+            let pivot_values = self.get_candidates(pivot);
+            let wings: Vec<Pos> = bivalues
+                .iter()
+                .cloned()
+                .filter(|&other| self.is_peer(pivot, other))
+                .filter(|&other| (self.get_candidates(other) & pivot_values).len() == 1)
+                .collect();
+            for (i, &first) in wings.iter().enumerate() {
+                for &second in &wings[i + 1..] {
+                    let (first_values, second_values) =
+                        (self.get_candidates(first), self.get_candidates(second));
+                    // Each pincer shares a different value with the pivot, and the same one with
+                    // each other
+                    if first_values & pivot_values == second_values & pivot_values ||
+                        first_values - pivot_values != second_values - pivot_values
+                    {
+                        continue;
+                    }
+                    let value = match (first_values - pivot_values).single_value() {
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    let eliminations = self.seen_by_all(&[first, second], value);
+                    if !eliminations.is_empty() {
+                        return Some(Deduction {
+                            technique: Technique::XYWing,
+                            pattern: Pattern::Wing {
+                                value,
+                                pivot: vec![pivot],
+                                pincers: vec![first, second],
+                            },
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// A pivot with candidates XYZ, seeing a pincer with XZ and another with YZ. One of the three
+    /// is Z, so Z can be ruled out from squares seeing all of them.
+    pub(super) fn find_xyz_wing(&self) -> Option<Deduction> {
+        let bivalues = self.squares_with_candidates(2);
+        for pivot in self.squares_with_candidates(3) {
+            let pivot_values = self.get_candidates(pivot);
+            let wings: Vec<Pos> = bivalues
+                .iter()
+                .cloned()
+                .filter(|&other| self.is_peer(pivot, other))
+                .filter(|&other| (self.get_candidates(other) - pivot_values).is_empty())
+                .collect();
+            for (i, &first) in wings.iter().enumerate() {
+                for &second in &wings[i + 1..] {
+                    let (first_values, second_values) =
+                        (self.get_candidates(first), self.get_candidates(second));
+                    if first_values | second_values != pivot_values {
+                        continue;
+                    }
+                    let value = match (first_values & second_values).single_value() {
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    let eliminations = self.seen_by_all(&[pivot, first, second], value);
+                    if !eliminations.is_empty() {
+                        return Some(Deduction {
+                            technique: Technique::XYZWing,
+                            pattern: Pattern::Wing {
+                                value,
+                                pivot: vec![pivot],
+                                pincers: vec![first, second],
+                            },
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Two squares with the same candidates XZ which don't see each other, and a unit where X can
+    /// only go in two squares, one seeing each of them. They can't both be X, so one of them is Z,
+    /// and Z can be ruled out from squares seeing both.
+    pub(super) fn find_w_wing(&self) -> Option<Deduction> {
+        let bivalues = self.squares_with_candidates(2);
+        for (i, &first) in bivalues.iter().enumerate() {
+            let values = self.get_candidates(first);
+            for &second in &bivalues[i + 1..] {
+                if self.get_candidates(second) != values || self.is_peer(first, second) {
+                    continue;
+                }
+                for link in values {
+                    let mut others = values;
+                    others.remove(link);
+                    let value = match others.single_value() {
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    let eliminations = self.seen_by_all(&[first, second], value);
+                    if eliminations.is_empty() {
+                        continue;
+                    }
+                    for (_, indices) in self.units() {
+                        let mut places = indices
+                            .iter()
+                            .cloned()
+                            .filter(|&index| self.get_candidates(index).contains(link));
+                        let pivot = match (places.next(), places.next(), places.next()) {
+                            (Some(start), Some(end), None) => {
+                                if self.is_peer(start, first) && self.is_peer(end, second) {
+                                    vec![start, end]
+                                } else if self.is_peer(end, first) && self.is_peer(start, second) {
+                                    vec![end, start]
+                                } else {
+                                    continue;
+                                }
+                            }
+                            _ => continue,
+                        };
+                        return Some(Deduction {
+                            technique: Technique::WWing,
+                            pattern: Pattern::Wing {
+                                value,
+                                pivot,
+                                pincers: vec![first, second],
+                            },
+                            placements: vec![],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use board::*;
+    use solver::logic::*;
+
+    #[test]
+    fn test_xy_wing() {
+        let mut lboard = with_candidates(
+            Board::default(),
+            &[
+                ((0, 0), &[1, 2]),
+                ((4, 0), &[1, 3]),
+                ((0, 4), &[2, 3]),
+            ],
+        );
+        let deduction = lboard.find(Technique::XYWing).expect("There is an XY-Wing");
+        assert_eq!(
+            Pattern::Wing {
+                value: 3,
+                pivot: vec![0],
+                pincers: vec![4, 36],
+            },
+            deduction.pattern
+        );
+        // Only the square in the pincers' row and column sees both
+        assert_eq!(vec![(40, 3)], deduction.eliminations);
+        assert_eq!(vec![0, 4, 36], deduction.cells());
+        assert_eq!(
+            "XY-Wing on 3: pivot (0, 0), pincers (4, 0) (0, 4), removing 3 from (4, 4)",
+            deduction.explain(&lboard)
+        );
+
+        lboard.apply(&deduction);
+        assert_eq!(None, lboard.find(Technique::XYWing));
+    }
+
+    #[test]
+    fn test_xyz_wing() {
+        let lboard = with_candidates(
+            Board::default(),
+            &[
+                ((0, 0), &[1, 2, 3]),
+                ((1, 1), &[1, 3]),
+                ((5, 0), &[2, 3]),
+            ],
+        );
+        assert_eq!(None, lboard.find(Technique::XYWing));
+        let deduction = lboard.find(Technique::XYZWing).expect("There is an XYZ-Wing");
+        assert_eq!(
+            Pattern::Wing {
+                value: 3,
+                pivot: vec![0],
+                pincers: vec![5, 10],
+            },
+            deduction.pattern
+        );
+        assert_eq!(vec![(1, 3), (2, 3)], deduction.eliminations);
+    }
+
+    #[test]
+    fn test_w_wing() {
+        // Two squares with 4 and 7, joined by the sixth row where 4 can only go at either end
+        let mut lboard = with_candidates(
+            Board::default(),
+            &[
+                ((0, 0), &[4, 7]),
+                ((8, 2), &[4, 7]),
+            ],
+        );
+        for col in 1..8 {
+            lboard.eliminate((col, 5).into_pos(9), 4);
+        }
+        assert_eq!(None, lboard.find(Technique::XYWing));
+        let deduction = lboard.find(Technique::WWing).expect("There is a W-Wing");
+        assert_eq!(
+            Pattern::Wing {
+                value: 7,
+                pivot: vec![(0, 5).into_pos(9), (8, 5).into_pos(9)],
+                pincers: vec![0, (8, 2).into_pos(9)],
+            },
+            deduction.pattern
+        );
+        let expected: Vec<(Pos, SquareType)> =
+            vec![(6, 7), (7, 7), (8, 7), (18, 7), (19, 7), (20, 7)];
+        assert_eq!(expected, deduction.eliminations);
+    }
+
+    #[test]
+    fn test_xy_wing_large() {
+        // On a 16x16 board the pincers can be further apart, as long as both see the pivot
+        let lboard = with_candidates(
+            Board::from_base_num(4).unwrap(),
+            &[
+                ((10, 10), &[12, 16]),
+                ((10, 1), &[12, 5]),
+                ((8, 11), &[16, 5]),
+            ],
+        );
+        let deduction = lboard.find(Technique::XYWing).expect("There is an XY-Wing");
+        assert_eq!(
+            Pattern::Wing {
+                value: 5,
+                pivot: vec![(10, 10).into_pos(16)],
+                pincers: vec![(10, 1).into_pos(16), (8, 11).into_pos(16)],
+            },
+            deduction.pattern
+        );
+        // The pincers both see the second pincer's column within the first pincer's grid, and
+        // the rest of the pivot's column within its own grid
+        let coords = vec![(8, 0), (8, 1), (8, 2), (8, 3), (10, 8), (10, 9), (10, 11)];
+        let expected: Vec<(Pos, SquareType)> = coords
+            .into_iter()
+            .map(|coord| (coord.into_pos(16), 5))
+            .collect();
+        assert_eq!(expected, deduction.eliminations);
+    }
+}
@@ -1,10 +1,29 @@
 use board::*;
+use candidate_board::CandidateBoard;
+use candidates::Candidates;
 use hintmap::HintMap;
-use std::thread;
+use std::cmp;
 use std::sync::Arc;
 use std::collections::{HashMap, HashSet};
 use scoped_threadpool::Pool;
 
+pub mod algorithm;
+pub mod budget;
+pub mod dfs;
+pub mod dlx;
+pub mod logic;
+pub mod parallel;
+pub mod stats;
+pub mod technique;
+
+pub use self::algorithm::{Algorithm, SolveOptions, SolveOutcome, Solver};
+pub use self::budget::{AbortReason, Budget, CancelToken};
+pub use self::dfs::{DfsStatus, SquareOrder, ValueOrder};
+pub use self::logic::{AlmostLockedSet, Deduction, Link, LogicBoard, Pattern};
+pub use self::logic::{DEFAULT_MAX_ALS_SIZE, DEFAULT_MAX_CHAIN_LENGTH};
+pub use self::stats::SolveStats;
+pub use self::technique::Technique;
+
 /// Status from running checking/solving algorithms on `Board`
 #[derive(Debug, PartialEq)]
 pub enum BoardStatus {
@@ -41,6 +60,15 @@ impl BoardStatus {
 }
 
 
+/// Which kind of single `HintMap::find_square_answer` found for the square
+fn single_technique(hmap: &HintMap, index: Pos) -> Technique {
+    if hmap.get_candidates(index).len() == 1 {
+        Technique::NakedSingle
+    } else {
+        Technique::HiddenSingle
+    }
+}
+
 /// Helpers
 impl Board {
     pub fn check_status(&self) -> BoardStatus {
@@ -72,11 +100,8 @@ impl Board {
         // If a HintMap has no hints for any square, that means that the board is invalid.
         let hmap = HintMap::from_board(self);
         for index in 0..self.num_squares {
-            if self.get_val(index).unwrap() == None {
-                let hints = hmap.get_hints(index);
-                if hints.len() == 0 {
-                    return BoardStatus::Invalid;
-                }
+            if self.squares[index].is_none() && hmap.get_candidates(index).is_empty() {
+                return BoardStatus::Invalid;
             }
         }
 
@@ -89,32 +114,128 @@ impl Board {
     pub fn is_solvable(&self) -> bool {
         self.clone().solve_parallel().is_solved()
     }
+
+    /// Every solution of the board, found one at a time as the iterator is advanced.
+    /// Solutions always come in the same order, and a board which already breaks the rules has
+    /// none.
+    pub fn solutions(&self) -> Solutions {
+        Solutions {
+            cboard: CandidateBoard::new(self.clone()),
+            guesses: vec![],
+            needs_check: true,
+        }
+    }
+
+    /// Count the solutions of the board, stopping once `limit` have been found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions().take(limit).count()
+    }
+
+    /// Indicates whether the board has exactly one solution
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+}
+
+/// Iterator over the solutions of a board, returned by `Board::solutions`.
+///
+/// This is a backtracking search, which branches on the blank square with the fewest candidates
+/// and undoes each guess before trying the next.
+#[derive(Clone, Debug)]
+pub struct Solutions {
+    cboard: CandidateBoard,
+    /// The square guessed at each level of the search, with the values still to try there
+    guesses: Vec<(Pos, Candidates)>,
+    /// Whether the last guess has been made but not checked yet
+    needs_check: bool,
+}
+
+impl Iterator for Solutions {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        loop {
+            if self.needs_check {
+                self.needs_check = false;
+                match self.cboard.check_status() {
+                    BoardStatus::Solved => return Some(self.cboard.board().clone()),
+                    BoardStatus::Invalid => {}
+                    BoardStatus::Valid => {
+                        let cboard = &self.cboard;
+                        let index = (0..cboard.num_squares)
+                            .filter(|&index| cboard.squares[index].is_none())
+                            .min_by_key(|&index| cboard.get_candidates(index).len())
+                            .expect("A valid unsolved board has a blank square");
+                        self.guesses.push((index, cboard.get_candidates(index)));
+                    }
+                }
+            }
+
+            // Move on to the next value for the latest guess, or go back to the one before it
+            let (index, val) = match self.guesses.last_mut() {
+                Some(&mut (index, ref mut remaining)) => {
+                    let val = remaining.first();
+                    if let Some(val) = val {
+                        remaining.remove(val);
+                    }
+                    (index, val)
+                }
+                None => return None,
+            };
+            self.cboard
+                .set_val(index, None)
+                .expect("A square guessed by the search can be cleared");
+            match val {
+                Some(val) => {
+                    self.cboard.fill(index, val);
+                    self.needs_check = true;
+                }
+                None => {
+                    self.guesses.pop();
+                }
+            }
+        }
+    }
 }
 
 impl Board {
     /// Solve the board with known techniques.
     pub fn solve_standard(&mut self) -> BoardStatus {
+        self.solve_standard_within(&Budget::unlimited())
+    }
+
+    /// `solve_standard`, stopping early if the budget runs out
+    pub(crate) fn solve_standard_within(&mut self, budget: &Budget) -> BoardStatus {
+        let mut cboard = CandidateBoard::new(self.clone());
+
         //Check the status of the board first - saves us from trying to solve an already finished board
-        let board_status = self.check_status();
+        let board_status = cboard.check_status();
         if board_status.is_finished() {
             return board_status;
         }
 
         loop {
-            let original_remaining = self.num_remaining();
-            let hmap = HintMap::from_board(self);
+            if budget.is_exhausted() {
+                budget.record(&cboard);
+                *self = cboard.into_board();
+                return BoardStatus::Valid;
+            }
+            let original_remaining = cboard.num_remaining();
+            let hmap = cboard.hint_map();
 
-            for index in 0..self.squares.len() {
-                if self.squares[index].is_none() {
+            for index in 0..cboard.num_squares {
+                if cboard.squares[index].is_none() {
                     if let Some(new_val) = hmap.find_square_answer(index) {
-                        self.squares[index] = Some(new_val);
+                        budget.place(single_technique(&hmap, index));
+                        cboard.fill(index, new_val);
                     }
                 }
             }
 
-            let has_changed = self.num_remaining() != original_remaining;
-            let board_status = self.check_status();
+            let has_changed = cboard.num_remaining() != original_remaining;
+            let board_status = cboard.check_status();
             if board_status.is_finished() | !has_changed {
+                *self = cboard.into_board();
                 return board_status;
             }
         }
@@ -123,14 +244,25 @@ impl Board {
     /// An alternative to `solve_standard()`. Applies similar solving techniques, but repetetively focuses on
     /// squares with the minimum number of hints first, in order to avoid more expensive computations.
     pub fn solve_ordered(&mut self) -> BoardStatus {
-        let board_status = self.check_status();
+        self.solve_ordered_within(&Budget::unlimited())
+    }
+
+    /// `solve_ordered`, stopping early if the budget runs out
+    pub(crate) fn solve_ordered_within(&mut self, budget: &Budget) -> BoardStatus {
+        let mut cboard = CandidateBoard::new(self.clone());
+        let board_status = cboard.check_status();
         if board_status.is_finished() {
             return board_status;
         }
 
         'outer: loop {
+            if budget.is_exhausted() {
+                budget.record(&cboard);
+                *self = cboard.into_board();
+                return BoardStatus::Valid;
+            }
             let mut start_again = false;
-            let hmap = HintMap::from_board(self);
+            let hmap = cboard.hint_map();
             let ordered = hmap.get_ordered();
 
             //Store the computed values in order to save time
@@ -140,24 +272,25 @@ impl Board {
 
             for (ind, hints) in ordered {
                 if hints.len() == 1 {
-                    self.squares[ind] = Some(hints[0]);
+                    budget.place(Technique::NakedSingle);
+                    cboard.fill(ind, hints.first().unwrap());
                     start_again = true; //This is instead of continue
                 } else if start_again {
                     // start_again is called by the If branch above this one, but is regarding the actual for loop
                     continue 'outer;
                 } else if hints.len() > 1 {
                     {
-                        let mut hints = hints.clone();
-                        let col_num = self.get_col_num(ind);
+                        let mut hints = hints;
+                        let col_num = cboard.get_col_num(ind);
 
                         // If column indices already not in cache, add to cache
                         if !cols.contains_key(&col_num) {
                             cols.insert(
                                 col_num,
-                                self.get_col_indices(col_num)
+                                cboard.get_col_indices(col_num)
                                     .iter()
-                                    .map(|s| *s)
-                                    .filter(|s| self.squares[*s] == None)
+                                    .cloned()
+                                    .filter(|s| cboard.squares[*s].is_none())
                                     .collect(),
                             );
                         }
@@ -166,30 +299,26 @@ impl Board {
                         for other_ind in col_ind {
                             let other_ind = *other_ind;
                             if ind != other_ind {
-                                let other_hints = hmap.get_hints(other_ind);
-                                for oth_hint in other_hints {
-                                    if let Ok(hint_index) = hints.binary_search(&oth_hint) {
-                                        hints.remove(hint_index);
-                                    }
-                                }
+                                hints -= hmap.get_candidates(other_ind);
                             }
                         }
-                        if hints.len() == 1 {
-                            self.squares[ind] = Some(hints[0]);
+                        if let Some(val) = hints.single_value() {
+                            budget.place(Technique::HiddenSingle);
+                            cboard.fill(ind, val);
                             continue 'outer;
                         }
                     }
                     //Do all these in separate blocks, as we don't want to mask the original "hints" variable
                     {
-                        let mut hints = hints.clone();
-                        let row_num = self.get_row_num(ind);
+                        let mut hints = hints;
+                        let row_num = cboard.get_row_num(ind);
                         if !rows.contains_key(&row_num) {
                             rows.insert(
                                 row_num,
-                                self.get_row_indices(row_num)
+                                cboard.get_row_indices(row_num)
                                     .iter()
-                                    .map(|s| *s)
-                                    .filter(|s| self.squares[*s] == None)
+                                    .cloned()
+                                    .filter(|s| cboard.squares[*s].is_none())
                                     .collect(),
                             );
                         }
@@ -198,30 +327,26 @@ impl Board {
                         for other_ind in row_ind {
                             let other_ind = *other_ind;
                             if ind != other_ind {
-                                let other_hints = hmap.get_hints(other_ind);
-                                for oth_hint in other_hints {
-                                    if let Ok(hint_index) = hints.binary_search(&oth_hint) {
-                                        hints.remove(hint_index);
-                                    }
-                                }
+                                hints -= hmap.get_candidates(other_ind);
                             }
                         }
-                        if hints.len() == 1 {
-                            self.squares[ind] = Some(hints[0]);
+                        if let Some(val) = hints.single_value() {
+                            budget.place(Technique::HiddenSingle);
+                            cboard.fill(ind, val);
                             continue 'outer;
                         }
                     }
                     {
-                        let mut hints = hints.clone();
+                        let mut hints = hints;
 
-                        let grid_num = self.get_grid_num(ind);
+                        let grid_num = cboard.get_grid_num(ind);
                         if !grids.contains_key(&grid_num) {
                             grids.insert(
                                 grid_num,
-                                self.get_grid_indices(grid_num)
+                                cboard.get_grid_indices(grid_num)
                                     .iter()
-                                    .map(|s| *s)
-                                    .filter(|s| self.squares[*s] == None)
+                                    .cloned()
+                                    .filter(|s| cboard.squares[*s].is_none())
                                     .collect(),
                             );
                         }
@@ -230,23 +355,21 @@ impl Board {
                         for other_ind in grid_ind {
                             let other_ind = *other_ind;
                             if ind != other_ind {
-                                let other_hints = hmap.get_hints(other_ind);
-                                for oth_hint in other_hints {
-                                    if let Ok(hint_index) = hints.binary_search(&oth_hint) {
-                                        hints.remove(hint_index);
-                                    }
-                                }
+                                hints -= hmap.get_candidates(other_ind);
                             }
                         }
-                        if hints.len() == 1 {
-                            self.squares[ind] = Some(hints[0]);
+                        if let Some(val) = hints.single_value() {
+                            budget.place(Technique::HiddenSingle);
+                            cboard.fill(ind, val);
                             continue 'outer;
                         }
                     }
                 }
             } // End of For Loop
 
-            return self.check_status();
+            let board_status = cboard.check_status();
+            *self = cboard.into_board();
+            return board_status;
         } // End of Outer Loop
     }
 
@@ -254,40 +377,65 @@ impl Board {
     /// The parallel solving algorithm. This is different from `solve_standard()` and `solve_ordered`
     /// as every blank square is evaluated for solving techniques in each iteration.
     pub fn solve_parallel(&mut self) -> BoardStatus {
+        self.solve_parallel_threads(parallel::default_num_threads())
+    }
+
+    /// `solve_parallel` with a set number of worker threads
+    pub fn solve_parallel_threads(&mut self, num_threads: usize) -> BoardStatus {
+        self.solve_parallel_within(num_threads, &Budget::unlimited())
+    }
+
+    /// `solve_parallel_threads`, stopping early if the budget runs out
+    pub(crate) fn solve_parallel_within(
+        &mut self,
+        num_threads: usize,
+        budget: &Budget,
+    ) -> BoardStatus {
+        let mut cboard = CandidateBoard::new(self.clone());
+
         // Check the Board Status - if invalid or solved, don't bother performing this algorithm
-        let board_status = self.check_status();
+        let board_status = cboard.check_status();
         if board_status.is_finished() {
             return board_status;
         }
 
-        // Create a thread pool with the board's base_num as the number of workers
-        let mut pool = Pool::new(self.base_num as u32);
+        // One pool for the whole solve, with each worker taking an even share of the squares
+        let num_threads = cmp::max(num_threads, 1);
+        let mut pool = Pool::new(num_threads as u32);
+        let chunk_size = cboard.num_squares.div_ceil(num_threads);
 
         'outer: loop {
-            let num_before = self.num_filled();
-            let hmap = Arc::new(HintMap::from_board(self));
+            if budget.is_exhausted() {
+                budget.record(&cboard);
+                *self = cboard.into_board();
+                return BoardStatus::Valid;
+            }
+            let num_before = cboard.num_filled();
+            let hmap = Arc::new(cboard.hint_map());
+
+            // Find the answers in parallel, then fill them in afterwards so the candidates can be updated
+            let mut answers: Vec<Option<SquareType>> = vec![None; cboard.num_squares];
             pool.scoped(|scoped| {
-                        let mut index = 0; // Manually build a loop index, since the ForEach loop below works on mutable references from an iterator
-                        for val in &mut self.squares {
-                            let hmap = hmap.clone(); // Copy the Arc
-                            scoped.execute(move || {
-                                if val.is_some() {
-                                    return;
-                                }
-                                if let Some(new_val) = hmap.find_square_answer(index) {
-                                    *val = Some(new_val);
-                                    return;
-                                }
-                            });
-                            index += 1;
-                        }
+                for (chunk_num, chunk) in answers.chunks_mut(chunk_size).enumerate() {
+                    let hmap = hmap.clone(); // Copy the Arc
+                    scoped.execute(move || for (offset, answer) in chunk.iter_mut().enumerate() {
+                        *answer = hmap.find_square_answer(chunk_num * chunk_size + offset);
                     });
+                }
+            });
+            for (index, answer) in answers.into_iter().enumerate() {
+                if let Some(new_val) = answer {
+                    budget.place(single_technique(&hmap, index));
+                    cboard.fill(index, new_val);
+                }
+            }
 
-            let has_changed = num_before < self.num_filled();
-            let status = self.check_status();
+            let has_changed = num_before < cboard.num_filled();
+            let status = cboard.check_status();
             if status == BoardStatus::Valid && has_changed {
                 continue 'outer;
             } else {
+                *self = cboard.into_board();
                 return status;
             }
         } //End Outer loop
@@ -298,10 +446,24 @@ impl Board {
 // All the searching algorithms
 impl Board {
     pub fn solve_search(&mut self) -> bool {
-        match self.solve_ordered() {
+        self.solve_search_within(&Budget::unlimited())
+    }
+
+    /// `solve_search`, giving up if the budget runs out
+    pub(crate) fn solve_search_within(&mut self, budget: &Budget) -> bool {
+        self.search_from_depth(budget, 0)
+    }
+
+    /// `solve_search` from a board reached after `depth` levels of guesses
+    fn search_from_depth(&mut self, budget: &Budget, depth: usize) -> bool {
+        if !budget.visit() {
+            return false;
+        }
+        budget.reach_depth(depth);
+        match self.solve_ordered_within(budget) {
             BoardStatus::Solved => return true,
             BoardStatus::Invalid => return false,
-            BoardStatus::Valid => {}
+            BoardStatus::Valid => budget.record(self),
         }
         let mut banned: HashSet<(usize, SquareType)> = HashSet::new();
         'outer: loop {
@@ -329,9 +491,14 @@ impl Board {
                     if banned.contains(&(ind, val)) {
                         continue 'forloop;
                     }
+                    if budget.is_exhausted() {
+                        return false;
+                    }
                     let mut cloned = self.clone();
-                    cloned.set_val(ind, Some(val)).unwrap();
-                    let status = cloned.solve_ordered();
+                    cloned.set_deduced(ind, val).unwrap();
+                    budget.place(Technique::Guess);
+                    budget.reach_depth(depth + 1);
+                    let status = cloned.solve_ordered_within(budget);
                     match status {
                         BoardStatus::Valid => {
                             working.push(cloned);
@@ -341,6 +508,7 @@ impl Board {
                             return true;
                         }
                         BoardStatus::Invalid => {
+                            budget.backtrack();
                             banned.insert((ind, val));
                         }
                     }
@@ -361,10 +529,11 @@ impl Board {
 
             for working_board in acceptable {
                 let mut working_board = working_board;
-                if working_board.solve_search() {
+                if working_board.search_from_depth(budget, depth + 1) {
                     *self = working_board;
                     return true;
                 }
+                budget.backtrack();
             }
 
             //If the above for loop has no results
@@ -372,145 +541,6 @@ impl Board {
         }
     }
 
-    pub fn solve_search_parallel(&mut self) -> bool {
-        //TODO: Pass down banned list
-        match self.solve_parallel() {
-            BoardStatus::Solved => return true,
-            BoardStatus::Invalid => return false,
-            BoardStatus::Valid => {}
-        }
-        let mut banned: HashSet<(usize, SquareType)> = HashSet::new();
-        'outer: loop {
-            //Build a hint map from the current board state
-            let hmap = HintMap::from_board(self);
-
-            //Order the hintmap from lowest number of hints to largest
-            let ordered = hmap.get_ordered();
-            if ordered.len() < 1 {
-                return false;
-            }
-
-            //Minimum number of hints
-            let min_hints = ordered[0].1.len();
-            let mut acceptable: Vec<Board> = vec![];
-            'inner: for (ind, vals) in ordered {
-                //Only let us do ones from the min size
-                if vals.len() != min_hints {
-                    continue 'inner;
-                }
-
-                let mut working: Vec<Board> = vec![];
-                let mut handles = vec![];
-
-                'forloop: for val in vals {
-                    if banned.contains(&(ind, val)) {
-                        continue 'forloop;
-                    }
-                    let mut cloned = self.clone();
-                    handles.push(thread::spawn(
-                        move || -> (usize, SquareType, Board, BoardStatus) {
-                            cloned.set_val(ind, Some(val)).unwrap();
-                            let status = cloned.solve_parallel();
-                            (ind, val, cloned, status)
-                        },
-                    ));
-                }
-                let mut results = vec![];
-                for handle in handles {
-                    let result = handle.join().unwrap();
-                    results.push(result);
-                }
-                for result in results {
-                    let (ind, val, board, status) = result;
-                    match status {
-                        BoardStatus::Valid => {
-                            working.push(board);
-                        }
-                        BoardStatus::Solved => {
-                            *self = board;
-                            return true;
-                        }
-                        BoardStatus::Invalid => {
-                            banned.insert((ind, val));
-                        }
-                    }
-                }
-
-                if working.len() == 1 {
-                    *self = working.pop().unwrap();
-                    continue 'outer;
-                } else if working.len() > 1 {
-                    while let Some(working_board) = working.pop() {
-                        acceptable.push(working_board);
-                    }
-                } else {
-                    //There's no "working" values for a given square - this means that all possibilities are bad.
-                    return false;
-                }
-            }
-
-            acceptable.sort_by(|ref a1, ref a2| a2.num_filled().cmp(&a1.num_filled()));
-
-            for working_board in acceptable {
-                let mut working_board = working_board;
-                //TODO: Pass down banned list
-                if working_board.solve_search_parallel() {
-                    *self = working_board;
-                    return true;
-                }
-            }
-
-            return false;
-        }
-    }
-
-    pub fn solve_search_dfs(&mut self, max_depth: Option<i32>) -> BoardStatus {
-        if let Some(depth) = max_depth {
-            if depth < 1 {
-                //This doesn't give much of an indication that we're finished recursion, but it's good enough
-                return self.check_status();
-            }
-        }
-
-        let solved_status = self.solve_standard();
-        if solved_status.is_finished() {
-            return solved_status;
-        }
-
-        let hmap = HintMap::from_board(self);
-
-        let mut hint_counts: Vec<(usize, usize)> = hmap.hints
-            .iter()
-            .map(|(key, value)| (*key, value.len()))
-            .collect();
-
-        //Sort each square from smallest number of hints to largest
-        hint_counts.sort_by(|a, b| b.1.cmp(&a.1));
-
-        for i in 0..hint_counts.len() {
-            let (index, _) = hint_counts[i];
-            if self.squares[index] == None {
-                for val in hmap.get_hints(index).iter() {
-                    let mut cloned = self.clone();
-                    cloned.squares[index] = Some(*val);
-
-                    let new_depth = match max_depth {
-                        Some(depth) => Some(depth - 1),
-                        None => None,
-                    };
-
-                    // Recursive call
-                    let solved_status = cloned.solve_search_dfs(new_depth);
-                    if solved_status.is_finished() {
-                        *self = cloned;
-                        return solved_status;
-                    }
-                }
-            }
-        }
-
-        self.check_status()
-    }
 }
 
 
@@ -586,6 +616,82 @@ mod tests {
         assert!(!board.solve_search_parallel());
     }
 
+    #[test]
+    fn test_count_solutions() {
+        assert_eq!(1, Board::from_string(SOLVED_PUZZLE_STR).count_solutions(10));
+        // The search solvers find one of several solutions to the hard puzzle
+        assert_eq!(6, Board::from_string(HARD_PUZZLE_STR).count_solutions(10));
+        assert_eq!(2, Board::from_string(HARD_PUZZLE_STR).count_solutions(2));
+        assert_eq!(1, Board::from_string(UNSOLVED_6_STR).count_solutions(10));
+        assert_eq!(288, Board::from_base_num(2).unwrap().count_solutions(1000));
+        assert_eq!(0, Board::from_string("4,2,0,0,1,3,0,0,0,0,3,2,0,0,1,4").count_solutions(10));
+
+        // Stops once the limit is reached
+        assert_eq!(5, Board::default().count_solutions(5));
+        assert_eq!(0, Board::default().count_solutions(0));
+    }
+
+    #[test]
+    fn test_solutions() {
+        let solutions: Vec<Board> = Board::from_base_num(2).unwrap().solutions().collect();
+        assert_eq!(288, solutions.len());
+        for (i, solution) in solutions.iter().enumerate() {
+            assert!(solution.check_status().is_solved());
+            assert!(!solutions[..i].contains(solution));
+        }
+        let first: Vec<Board> = Board::from_base_num(2).unwrap().solutions().take(10).collect();
+        assert_eq!(solutions[..10], first[..]);
+
+        let unsolved = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let solutions: Vec<Board> = unsolved.solutions().collect();
+        assert_eq!(vec![Board::from_string(SOLVED_PUZZLE_STR)], solutions);
+
+        // Only finds as many solutions as are taken
+        assert_eq!(3, Board::from_base_num(4).unwrap().solutions().take(3).count());
+    }
+
+    #[test]
+    fn test_has_unique_solution() {
+        assert!(Board::from_string(UNSOLVED_PUZZLE_STR).has_unique_solution());
+        assert!(!Board::default().has_unique_solution());
+
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        board.set_val((0, 0), Some(8)).unwrap();
+        board.set_val((1, 0), Some(8)).unwrap();
+        assert!(!board.has_unique_solution());
+    }
+
+    #[test]
+    fn test_solve_rectangular() {
+        let mut unsolved = Board::from_string(UNSOLVED_6_STR);
+        assert!(unsolved.solve_standard().is_solved());
+        assert_eq!(SOLVED_6_STR, unsolved.to_string());
+
+        let mut unsolved = Board::from_string(UNSOLVED_6_STR);
+        assert!(unsolved.solve_parallel().is_solved());
+        assert_eq!(SOLVED_6_STR, unsolved.to_string());
+
+        for &(box_width, box_height) in [(3, 2), (2, 3), (4, 2)].iter() {
+            let mut board = Board::with_box_dims(box_width, box_height).unwrap();
+            assert!(board.solve_search());
+            assert!(board.check_status().is_solved());
+        }
+    }
+
+    #[test]
+    fn test_is_valid_rectangular() {
+        assert!(Board::from_string(SOLVED_6_STR).check_status().is_solved());
+        // Valid rows and columns, but the grids contain duplicates
+        let bad_grids = concat!(
+            "1,2,3,4,5,6,",
+            "2,3,4,5,6,1,",
+            "3,4,5,6,1,2,",
+            "4,5,6,1,2,3,",
+            "5,6,1,2,3,4,",
+            "6,1,2,3,4,5"
+        );
+        assert!(!Board::from_string(bad_grids).check_status().is_valid());
+    }
 
     #[test]
     fn test_is_valid() {
@@ -680,44 +786,75 @@ mod bench {
 
     #[bench]
     fn bench_09_blank_solve_standard(b: &mut Bencher) {
-        let mut new_board = Board::from_base_num(3);
+        let mut new_board = Board::from_base_num(3).unwrap();
         b.iter(|| { new_board.solve_standard(); });
     }
 
     #[bench]
     fn bench_09_blank_solve_ordered(b: &mut Bencher) {
-        let mut new_board = Board::from_base_num(3);
+        let mut new_board = Board::from_base_num(3).unwrap();
         b.iter(|| { new_board.solve_ordered(); });
     }
 
     #[bench]
     fn bench_09_blank_solve_parallel(b: &mut Bencher) {
-        let mut new_board = Board::from_base_num(3);
+        let mut new_board = Board::from_base_num(3).unwrap();
         b.iter(|| { new_board.solve_parallel(); });
     }
 
     #[bench]
     fn bench_25_blank_solve_standard(b: &mut Bencher) {
-        let mut new_board = Board::from_base_num(5);
+        let mut new_board = Board::from_base_num(5).unwrap();
         b.iter(|| { new_board.solve_standard(); });
     }
 
     #[bench]
     fn bench_25_blank_solve_ordered(b: &mut Bencher) {
-        let mut new_board = Board::from_base_num(5);
+        let mut new_board = Board::from_base_num(5).unwrap();
         b.iter(|| { new_board.solve_ordered(); });
     }
 
     #[bench]
     fn bench_25_blank_solve_parallel(b: &mut Bencher) {
-        let mut new_board = Board::from_base_num(5);
+        let mut new_board = Board::from_base_num(5).unwrap();
         b.iter(|| { new_board.solve_parallel(); });
     }
 
+    #[bench]
+    fn bench_25_puzzle_solve_standard(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/25u.txt"));
+        b.iter(|| unsolved.clone().solve_standard());
+    }
+
+    #[bench]
+    fn bench_25_puzzle_solve_ordered(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/25u.txt"));
+        b.iter(|| unsolved.clone().solve_ordered());
+    }
+
+    #[bench]
+    fn bench_25_puzzle_solve_parallel(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/25u.txt"));
+        b.iter(|| unsolved.clone().solve_parallel());
+    }
+
+    #[bench]
+    fn bench_36_puzzle_solve_standard(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/36u.txt"));
+        b.iter(|| unsolved.clone().solve_standard());
+    }
+
+    #[ignore]
+    #[bench]
+    fn bench_16_hard_solve_search(b: &mut Bencher) {
+        let unsolved = Board::from_string(include_str!("../../../puzzles/16hard.txt"));
+        b.iter(|| unsolved.clone().solve_search());
+    }
+
     #[ignore]
     #[bench]
     fn bench_36_blank_solve_standard(b: &mut Bencher) {
-        let mut new_board = Board::from_base_num(6);
+        let mut new_board = Board::from_base_num(6).unwrap();
         b.iter(|| { new_board.solve_standard(); });
     }
 
@@ -739,7 +876,7 @@ mod bench {
 
     #[bench]
     fn bench_is_valid_09_blank(b: &mut Bencher) {
-        let board = Board::from_base_num(3);
+        let board = Board::from_base_num(3).unwrap();
         b.iter(|| { let _is_valid = board.check_status().is_valid(); });
     }
 
@@ -757,7 +894,7 @@ mod bench {
 
     #[bench]
     fn bench_num_filled_09_blank(b: &mut Bencher) {
-        let mut board = Board::from_base_num(3);
+        let mut board = Board::from_base_num(3).unwrap();
         b.iter(|| board.num_filled());
     }
 
@@ -775,7 +912,7 @@ mod bench {
 
     #[bench]
     fn bench_num_filled_16_blank(b: &mut Bencher) {
-        let mut board = Board::from_base_num(4);
+        let mut board = Board::from_base_num(4).unwrap();
         b.iter(|| board.num_filled());
     }
 }
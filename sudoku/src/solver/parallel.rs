@@ -0,0 +1,360 @@
+use board::*;
+use hintmap::HintMap;
+use scoped_threadpool::Pool;
+use solver::{BoardStatus, SolveOptions};
+use solver::budget::Budget;
+use solver::technique::Technique;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{cmp, thread};
+
+/// Number of worker threads to use when none is given: one for each available core
+pub fn default_num_threads() -> usize {
+    thread::available_parallelism()
+        .map(|num| num.get())
+        .unwrap_or(1)
+}
+
+/// Tasks shared between a fixed number of worker threads.
+///
+/// Each worker has its own queue, and takes the task it added most recently so that it works
+/// depth first. A worker with nothing left steals the oldest task from another worker, which is
+/// usually the largest piece of work left. Workers with nothing to steal sleep until a task is
+/// added, everything is done or the queue is stopped.
+pub struct WorkQueue<T> {
+    queues: Vec<Mutex<VecDeque<T>>>,
+    /// Number of tasks that are queued or being worked on
+    pending: AtomicUsize,
+    stopped: AtomicBool,
+    /// Held by idle workers while they look for a task, so that a wakeup can't be missed
+    idle: Mutex<()>,
+    wakeup: Condvar,
+}
+
+impl<T: Send> WorkQueue<T> {
+    pub fn new(num_threads: usize) -> WorkQueue<T> {
+        WorkQueue {
+            queues: (0..cmp::max(num_threads, 1))
+                .map(|_| Mutex::new(VecDeque::new()))
+                .collect(),
+            pending: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+            idle: Mutex::new(()),
+            wakeup: Condvar::new(),
+        }
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.queues.len()
+    }
+
+    /// Stop every worker once it has finished its current task
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.wake_all();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Work through `initial` and every task added by `work`, until there are none left or the
+    /// queue is stopped. `work` is given each task along with a list to push new tasks onto;
+    /// the last task pushed is the next one its worker takes.
+    pub fn run<F>(&self, initial: Vec<T>, work: F)
+    where
+        F: Fn(T, &mut Vec<T>) + Sync,
+    {
+        self.pending.fetch_add(initial.len(), Ordering::SeqCst);
+        for (i, task) in initial.into_iter().enumerate() {
+            self.lock(i % self.queues.len()).push_back(task);
+        }
+
+        let mut pool = Pool::new(self.queues.len() as u32);
+        pool.scoped(|scoped| for worker in 0..self.queues.len() {
+            let work = &work;
+            scoped.execute(move || self.work_loop(worker, work));
+        });
+    }
+
+    fn lock(&self, worker: usize) -> MutexGuard<'_, VecDeque<T>> {
+        self.queues[worker]
+            .lock()
+            .expect("A worker panicked while holding its queue")
+    }
+
+    fn lock_idle(&self) -> MutexGuard<'_, ()> {
+        self.idle.lock().expect("A worker panicked while idle")
+    }
+
+    /// Wake every idle worker to look for tasks again
+    fn wake_all(&self) {
+        let _idle = self.lock_idle();
+        self.wakeup.notify_all();
+    }
+
+    fn work_loop<F>(&self, worker: usize, work: &F)
+    where
+        F: Fn(T, &mut Vec<T>),
+    {
+        let mut new_tasks = vec![];
+        while !self.is_stopped() {
+            let task = match self.find_task(worker) {
+                Some(task) => task,
+                None => {
+                    // Look again while holding `idle`, as a task added since the last look only
+                    // wakes the workers that are already waiting
+                    let idle = self.lock_idle();
+                    if self.is_stopped() || self.pending.load(Ordering::SeqCst) == 0 {
+                        return;
+                    }
+                    match self.find_task(worker) {
+                        Some(task) => task,
+                        None => {
+                            let _idle = self.wakeup
+                                .wait(idle)
+                                .expect("A worker panicked while idle");
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let finished = Finished(self);
+            work(task, &mut new_tasks);
+            // Count the new tasks before the finished one, so that `pending` can only reach 0
+            // once everything is done
+            self.pending.fetch_add(new_tasks.len(), Ordering::SeqCst);
+            if !new_tasks.is_empty() {
+                self.lock(worker).extend(new_tasks.drain(..));
+                self.wake_all();
+            }
+            drop(finished);
+        }
+    }
+
+    fn find_task(&self, worker: usize) -> Option<T> {
+        // Let go of our own queue before trying the others, or two workers stealing from each
+        // other could wait on each other forever
+        let own_task = self.lock(worker).pop_back();
+        own_task.or_else(|| self.steal(worker))
+    }
+
+    fn steal(&self, worker: usize) -> Option<T> {
+        let num_workers = self.queues.len();
+        (1..num_workers)
+            .map(|offset| (worker + offset) % num_workers)
+            .filter_map(|other| self.lock(other).pop_front())
+            .next()
+    }
+}
+
+/// Marks a task as done when dropped, even if `work` panicked on it. A panic also stops the
+/// queue, so that the other workers return rather than wait for tasks that will never come.
+struct Finished<'a, T: 'a + Send>(&'a WorkQueue<T>);
+
+impl<'a, T: Send> Drop for Finished<'a, T> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.stop();
+        }
+        if self.0.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.wake_all();
+        }
+    }
+}
+
+/// A board still to be searched, with the guesses already known to break the rules from it
+struct SearchTask {
+    board: Board,
+    banned: Arc<HashSet<(Pos, SquareType)>>,
+    /// Number of guesses made to reach the board
+    depth: usize,
+}
+
+impl Board {
+    /// Recursive search over a bounded pool of threads. Branches are shared between the workers,
+    /// and all of them stop as soon as one finds a solution.
+    pub fn solve_search_parallel(&mut self) -> bool {
+        self.solve_search_parallel_with(&SolveOptions::default())
+    }
+
+    /// `solve_search_parallel`, using the number of threads and limits from the options
+    pub fn solve_search_parallel_with(&mut self, options: &SolveOptions) -> bool {
+        let num_threads = options.num_threads.unwrap_or_else(default_num_threads);
+        self.solve_search_parallel_within(num_threads, &Budget::new(options))
+    }
+
+    /// `solve_search_parallel`, with every worker giving up once the budget runs out
+    pub(crate) fn solve_search_parallel_within(
+        &mut self,
+        num_threads: usize,
+        budget: &Budget,
+    ) -> bool {
+        match self.check_status() {
+            BoardStatus::Solved => return true,
+            BoardStatus::Invalid => return false,
+            BoardStatus::Valid => {}
+        }
+
+        let queue = WorkQueue::new(num_threads);
+        let solution: Mutex<Option<Board>> = Mutex::new(None);
+        let found = |board: Board| {
+            *solution.lock().expect("Unable to store the solution") = Some(board);
+            queue.stop();
+        };
+        let root = SearchTask {
+            board: self.clone(),
+            banned: Arc::new(HashSet::new()),
+            depth: 0,
+        };
+
+        queue.run(vec![root], |task, new_tasks| {
+            if !budget.visit() {
+                return queue.stop();
+            }
+            let mut board = task.board;
+            match board.solve_ordered_within(budget) {
+                BoardStatus::Solved => return found(board),
+                BoardStatus::Invalid => return,
+                BoardStatus::Valid => budget.record(&board),
+            }
+
+            // Branch on the square with the fewest candidates that haven't been ruled out
+            let hmap = HintMap::from_board(&board);
+            let banned = &task.banned;
+            let (index, vals) = match (0..board.num_squares)
+                .filter(|&index| board.squares[index].is_none())
+                .map(|index| {
+                    let vals: Vec<SquareType> = hmap.get_candidates(index)
+                        .into_iter()
+                        .filter(|&val| !banned.contains(&(index, val)))
+                        .collect();
+                    (index, vals)
+                })
+                .min_by_key(|(_, vals)| vals.len())
+            {
+                Some(branch) => branch,
+                None => return,
+            };
+
+            let mut new_bans = vec![];
+            let mut children = vec![];
+            for val in vals {
+                if queue.is_stopped() || budget.is_exhausted() {
+                    return queue.stop();
+                }
+                let mut child = board.clone();
+                child.set_deduced(index, val).unwrap();
+                budget.place(Technique::Guess);
+                budget.reach_depth(task.depth + 1);
+                match child.solve_ordered_within(budget) {
+                    BoardStatus::Solved => return found(child),
+                    BoardStatus::Invalid => {
+                        budget.backtrack();
+                        new_bans.push((index, val));
+                    }
+                    BoardStatus::Valid => children.push(child),
+                }
+            }
+
+            // A guess which breaks the rules here also breaks them further down
+            let banned = if new_bans.is_empty() {
+                task.banned.clone()
+            } else {
+                let mut banned = (*task.banned).clone();
+                banned.extend(new_bans);
+                Arc::new(banned)
+            };
+            // The most filled in board goes last, so it is searched first
+            children.sort_by_key(|child| child.num_filled());
+            let depth = task.depth + 1;
+            new_tasks.extend(children.into_iter().map(|board| {
+                SearchTask {
+                    board,
+                    banned: banned.clone(),
+                    depth,
+                }
+            }));
+        });
+
+        match solution.into_inner().expect("Unable to read the solution") {
+            Some(board) => {
+                *self = board;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_work_queue() {
+        // Every task is run once, including the ones added along the way
+        let queue = WorkQueue::new(4);
+        let total = AtomicUsize::new(0);
+        queue.run(vec![10, 10], |depth, new_tasks| {
+            total.fetch_add(1, Ordering::SeqCst);
+            if depth > 0 {
+                new_tasks.push(depth - 1);
+                new_tasks.push(depth - 1);
+            }
+        });
+        assert_eq!(2 * ((1 << 11) - 1), total.load(Ordering::SeqCst));
+        assert_eq!(4, queue.num_threads());
+
+        let queue = WorkQueue::new(3);
+        let total = AtomicUsize::new(0);
+        queue.run(vec![(); 100], |_, _| if total.fetch_add(1, Ordering::SeqCst) == 10 {
+            queue.stop();
+        });
+        assert!(total.load(Ordering::SeqCst) < 100);
+    }
+
+    #[test]
+    fn test_work_queue_panic() {
+        // A panicking task stops the other workers, and the panic reaches the caller
+        let queue = WorkQueue::new(4);
+        let result = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+            queue.run(vec![0; 100], |i, new_tasks| {
+                if i == 5 {
+                    panic!("Task failed");
+                }
+                new_tasks.push(i + 1);
+            })
+        }));
+        assert!(result.is_err());
+        assert!(queue.is_stopped());
+    }
+
+    #[test]
+    fn test_solve_search_parallel_threads() {
+        let puzzles = [
+            HARD_PUZZLE_STR,
+            UNSOLVED_6_STR,
+            include_str!("../../../puzzles/16hard.txt"),
+        ];
+        for &num_threads in [1, 4].iter() {
+            let options = SolveOptions {
+                num_threads: Some(num_threads),
+                ..SolveOptions::default()
+            };
+            for puzzle in puzzles.iter() {
+                let mut board = Board::from_string(puzzle);
+                assert!(board.solve_search_parallel_with(&options));
+                assert!(board.check_status().is_solved());
+            }
+
+            let puzzle = "4,2,0,0,1,3,0,0,0,0,3,2,0,0,1,4";
+            let mut board = Board::from_string(puzzle);
+            assert!(!board.solve_search_parallel_with(&options));
+            assert_eq!(puzzle, board.to_string());
+        }
+    }
+}
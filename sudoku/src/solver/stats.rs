@@ -0,0 +1,103 @@
+use solver::technique::Technique;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// What a solver did to reach its outcome
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveStats {
+    pub elapsed: Duration,
+    /// Nodes of the search visited, for the algorithms which search
+    pub num_nodes: usize,
+    /// Number of times a search had to undo a guess
+    pub num_backtracks: usize,
+    /// The most guesses deep that a search went
+    pub max_depth: usize,
+    /// Squares filled in by each technique, including any that were undone while searching
+    pub placements: BTreeMap<Technique, usize>,
+    /// Candidates ruled out by each technique
+    pub eliminations: BTreeMap<Technique, usize>,
+    pub num_threads: usize,
+}
+
+impl SolveStats {
+    pub fn num_placements(&self) -> usize {
+        self.placements.values().sum()
+    }
+
+    pub fn num_eliminations(&self) -> usize {
+        self.eliminations.values().sum()
+    }
+
+    /// The stats as a single line of JSON
+    pub fn to_json(&self) -> String {
+        let counts = |counts: &BTreeMap<Technique, usize>| {
+            let fields: Vec<String> = counts
+                .iter()
+                .map(|(technique, count)| format!("\"{}\":{}", technique.name(), count))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        };
+        format!(
+            concat!(
+                "{{\"elapsed_ns\":{},\"nodes\":{},\"backtracks\":{},\"max_depth\":{},",
+                "\"placements\":{},\"eliminations\":{},\"threads\":{}}}"
+            ),
+            self.elapsed.as_nanos(),
+            self.num_nodes,
+            self.num_backtracks,
+            self.max_depth,
+            counts(&self.placements),
+            counts(&self.eliminations),
+            self.num_threads
+        )
+    }
+}
+
+impl fmt::Display for SolveStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.elapsed.as_secs() as f64 + f64::from(self.elapsed.subsec_nanos()) / 1e9;
+        writeln!(f, "Time:         {:.6}s", secs)?;
+        writeln!(f, "Threads:      {}", self.num_threads)?;
+        writeln!(f, "Nodes:        {}", self.num_nodes)?;
+        writeln!(f, "Backtracks:   {}", self.num_backtracks)?;
+        writeln!(f, "Max depth:    {}", self.max_depth)?;
+        writeln!(f, "Placements:   {}", self.num_placements())?;
+        for (technique, count) in &self.placements {
+            writeln!(f, "    {:16}{}", technique.to_string(), count)?;
+        }
+        write!(f, "Eliminations: {}", self.num_eliminations())?;
+        for (technique, count) in &self.eliminations {
+            write!(f, "\n    {:16}{}", technique.to_string(), count)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_json() {
+        let mut stats = SolveStats {
+            elapsed: Duration::new(1, 5),
+            num_nodes: 10,
+            num_backtracks: 3,
+            max_depth: 2,
+            num_threads: 4,
+            ..SolveStats::default()
+        };
+        stats.placements.insert(Technique::Guess, 4);
+        stats.placements.insert(Technique::NakedSingle, 7);
+        assert_eq!(11, stats.num_placements());
+        assert_eq!(
+            concat!(
+                "{\"elapsed_ns\":1000000005,\"nodes\":10,\"backtracks\":3,\"max_depth\":2,",
+                "\"placements\":{\"naked-single\":7,\"guess\":4},\"eliminations\":{},\"threads\":4}"
+            ),
+            stats.to_json()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A way of filling in a square or ruling out one of its candidates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Technique {
+    /// The square has only one candidate left
+    NakedSingle,
+    /// The value can only go in one square of a row, column or grid
+    HiddenSingle,
+    /// A value which can only go in one row or column of a grid, so is ruled out from the rest of
+    /// that row or column
+    Pointing,
+    /// A value which can only go in one grid of a row or column, so is ruled out from the rest of
+    /// that grid
+    Claiming,
+    /// Two squares of a unit with the same two candidates between them
+    NakedPair,
+    NakedTriple,
+    NakedQuad,
+    /// Two values which can only go in the same two squares of a unit
+    HiddenPair,
+    HiddenTriple,
+    HiddenQuad,
+    /// A value which can only go in the same two columns of two rows, or the same two rows of two
+    /// columns, so is ruled out from the rest of those lines
+    XWing,
+    Swordfish,
+    Jellyfish,
+    /// A fish with extra squares, all in one grid, which may hold the value instead. Only squares
+    /// which see every extra square have the value ruled out.
+    FinnedXWing,
+    FinnedSwordfish,
+    FinnedJellyfish,
+    /// A square with candidates XY that sees squares with XZ and YZ. One of those two must be Z,
+    /// so Z is ruled out from the squares that see both.
+    XYWing,
+    /// Like an XY-Wing, but the pivot can also be Z, so only squares seeing all three lose Z
+    XYZWing,
+    /// Two squares with the same two candidates XZ, joined by a unit where X can only go in a
+    /// square seeing one or the other. One of them must be Z.
+    WWing,
+    /// Squares joined by units a value can only go in two squares of, coloured alternately. A
+    /// colour appearing twice in one unit is false, and squares seeing both colours are too.
+    SimpleColouring,
+    /// A chain of one value, alternating between units it can only go in two squares of and
+    /// squares that see each other
+    XChain,
+    /// A chain through squares with two candidates, joined by values they share
+    XYChain,
+    /// Any chain alternating between strong and weak links, within squares or units
+    AIC,
+    /// Two almost locked sets, each N squares of a unit with N + 1 candidates, joined by a value
+    /// they can't both hold. Another value they share must be in one of them.
+    AlsXZ,
+    /// Two almost locked sets each joined to a third by a different value. A value the first two
+    /// share must be in one of them.
+    AlsXYWing,
+    /// Four squares in two rows, columns and grids, three of which only have the same two
+    /// candidates. The fourth can't have them too, or the two could be swapped.
+    UniqueRectangle1,
+    /// Two corners of a rectangle only have its two values, and the other two have the same one
+    /// extra candidate, which must go in one of them
+    UniqueRectangle2,
+    /// Like type 2, but the extra candidates of the other two corners form a naked subset with
+    /// other squares of a unit they share
+    UniqueRectangle3,
+    /// Like type 2, but one of the rectangle's values can only go in the other two corners of a
+    /// unit they share, so the other value can't go in either
+    UniqueRectangle4,
+    /// Every blank square has two candidates apart from one with three. Unless that one holds the
+    /// value it has three places for, the board would have two solutions.
+    BugPlusOne,
+    /// A value tried by a search, which may turn out to be wrong
+    Guess,
+}
+
+impl Technique {
+    pub fn all() -> &'static [Technique] {
+        &[
+            Technique::NakedSingle,
+            Technique::HiddenSingle,
+            Technique::Pointing,
+            Technique::Claiming,
+            Technique::NakedPair,
+            Technique::NakedTriple,
+            Technique::NakedQuad,
+            Technique::HiddenPair,
+            Technique::HiddenTriple,
+            Technique::HiddenQuad,
+            Technique::XWing,
+            Technique::Swordfish,
+            Technique::Jellyfish,
+            Technique::FinnedXWing,
+            Technique::FinnedSwordfish,
+            Technique::FinnedJellyfish,
+            Technique::XYWing,
+            Technique::XYZWing,
+            Technique::WWing,
+            Technique::SimpleColouring,
+            Technique::XChain,
+            Technique::XYChain,
+            Technique::AIC,
+            Technique::AlsXZ,
+            Technique::AlsXYWing,
+            Technique::UniqueRectangle1,
+            Technique::UniqueRectangle2,
+            Technique::UniqueRectangle3,
+            Technique::UniqueRectangle4,
+            Technique::BugPlusOne,
+            Technique::Guess,
+        ]
+    }
+
+    /// Every technique that doesn't guess, in the order a person would usually try them
+    pub fn logical() -> &'static [Technique] {
+        &[
+            Technique::NakedSingle,
+            Technique::HiddenSingle,
+            Technique::Pointing,
+            Technique::Claiming,
+            Technique::NakedPair,
+            Technique::HiddenPair,
+            Technique::NakedTriple,
+            Technique::HiddenTriple,
+            Technique::NakedQuad,
+            Technique::HiddenQuad,
+            Technique::XWing,
+            Technique::FinnedXWing,
+            Technique::XYWing,
+            Technique::XYZWing,
+            Technique::WWing,
+            Technique::Swordfish,
+            Technique::FinnedSwordfish,
+            Technique::Jellyfish,
+            Technique::FinnedJellyfish,
+            Technique::SimpleColouring,
+            Technique::XChain,
+            Technique::XYChain,
+            Technique::AIC,
+            Technique::AlsXZ,
+            Technique::AlsXYWing,
+        ]
+    }
+
+    /// Techniques that only work on boards with one solution. They aren't tried unless the board
+    /// is known to have one.
+    pub fn uniqueness() -> &'static [Technique] {
+        &[
+            Technique::UniqueRectangle1,
+            Technique::UniqueRectangle2,
+            Technique::UniqueRectangle3,
+            Technique::UniqueRectangle4,
+            Technique::BugPlusOne,
+        ]
+    }
+
+    /// Whether the technique relies on the board having one solution
+    pub fn needs_unique(&self) -> bool {
+        Technique::uniqueness().contains(self)
+    }
+
+    /// Position of the technique in `all`
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Short name, as used in machine readable output
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Technique::NakedSingle => "naked-single",
+            Technique::HiddenSingle => "hidden-single",
+            Technique::Pointing => "pointing",
+            Technique::Claiming => "claiming",
+            Technique::NakedPair => "naked-pair",
+            Technique::NakedTriple => "naked-triple",
+            Technique::NakedQuad => "naked-quad",
+            Technique::HiddenPair => "hidden-pair",
+            Technique::HiddenTriple => "hidden-triple",
+            Technique::HiddenQuad => "hidden-quad",
+            Technique::XWing => "x-wing",
+            Technique::Swordfish => "swordfish",
+            Technique::Jellyfish => "jellyfish",
+            Technique::FinnedXWing => "finned-x-wing",
+            Technique::FinnedSwordfish => "finned-swordfish",
+            Technique::FinnedJellyfish => "finned-jellyfish",
+            Technique::XYWing => "xy-wing",
+            Technique::XYZWing => "xyz-wing",
+            Technique::WWing => "w-wing",
+            Technique::SimpleColouring => "simple-colouring",
+            Technique::XChain => "x-chain",
+            Technique::XYChain => "xy-chain",
+            Technique::AIC => "aic",
+            Technique::AlsXZ => "als-xz",
+            Technique::AlsXYWing => "als-xy-wing",
+            Technique::UniqueRectangle1 => "unique-rectangle-1",
+            Technique::UniqueRectangle2 => "unique-rectangle-2",
+            Technique::UniqueRectangle3 => "unique-rectangle-3",
+            Technique::UniqueRectangle4 => "unique-rectangle-4",
+            Technique::BugPlusOne => "bug-plus-one",
+            Technique::Guess => "guess",
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Technique::NakedSingle => "Naked Single",
+            Technique::HiddenSingle => "Hidden Single",
+            Technique::Pointing => "Pointing",
+            Technique::Claiming => "Claiming",
+            Technique::NakedPair => "Naked Pair",
+            Technique::NakedTriple => "Naked Triple",
+            Technique::NakedQuad => "Naked Quad",
+            Technique::HiddenPair => "Hidden Pair",
+            Technique::HiddenTriple => "Hidden Triple",
+            Technique::HiddenQuad => "Hidden Quad",
+            Technique::XWing => "X-Wing",
+            Technique::Swordfish => "Swordfish",
+            Technique::Jellyfish => "Jellyfish",
+            Technique::FinnedXWing => "Finned X-Wing",
+            Technique::FinnedSwordfish => "Finned Swordfish",
+            Technique::FinnedJellyfish => "Finned Jellyfish",
+            Technique::XYWing => "XY-Wing",
+            Technique::XYZWing => "XYZ-Wing",
+            Technique::WWing => "W-Wing",
+            Technique::SimpleColouring => "Simple Colouring",
+            Technique::XChain => "X-Chain",
+            Technique::XYChain => "XY-Chain",
+            Technique::AIC => "Alternating Inference Chain",
+            Technique::AlsXZ => "ALS-XZ",
+            Technique::AlsXYWing => "ALS-XY-Wing",
+            Technique::UniqueRectangle1 => "Unique Rectangle Type 1",
+            Technique::UniqueRectangle2 => "Unique Rectangle Type 2",
+            Technique::UniqueRectangle3 => "Unique Rectangle Type 3",
+            Technique::UniqueRectangle4 => "Unique Rectangle Type 4",
+            Technique::BugPlusOne => "BUG+1",
+            Technique::Guess => "Guess",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Technique {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Technique, Error> {
+        Technique::all()
+            .iter()
+            .find(|technique| technique.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownTechnique(name.to_string()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_technique_index() {
+        for (index, technique) in Technique::all().iter().enumerate() {
+            assert_eq!(index, technique.index());
+            assert_eq!(Ok(*technique), technique.name().parse());
+        }
+        assert!(Technique::logical().iter().all(|technique| Technique::all().contains(technique)));
+        assert!(!Technique::logical().contains(&Technique::Guess));
+        assert!(Technique::logical().iter().all(|technique| !technique.needs_unique()));
+        assert!(Technique::BugPlusOne.needs_unique());
+        assert!("magic".parse::<Technique>().is_err());
+    }
+}
@@ -0,0 +1,433 @@
+use board::*;
+use candidates::Candidates;
+use error::Error;
+use rand::Rng;
+use std::mem;
+
+/// A change to the layout or labelling of a board which keeps a valid board valid.
+///
+/// Bands are the rows of grids (each `box_height` rows tall) and stacks are the columns of grids
+/// (each `box_width` columns wide).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Transform {
+    /// Swap two rows within the same band
+    SwapRows(usize, usize),
+    /// Swap two columns within the same stack
+    SwapCols(usize, usize),
+    SwapBands(usize, usize),
+    SwapStacks(usize, usize),
+    /// Reflect along the main diagonal, so rows become columns
+    Transpose,
+    RotateClockwise,
+    RotateAnticlockwise,
+    Rotate180,
+    /// Reflect left to right
+    ReflectHorizontal,
+    /// Reflect top to bottom
+    ReflectVertical,
+    /// Reflect along the diagonal from the top-right to the bottom-left
+    ReflectAntiDiagonal,
+    /// Relabel every value, where value `n` becomes `mapping[n - 1]`
+    PermuteDigits(Vec<SquareType>),
+}
+
+impl Board {
+    /// Number of bands (rows of grids) on the board
+    pub fn num_bands(&self) -> usize {
+        self.side_length / self.box_height
+    }
+
+    /// Number of stacks (columns of grids) on the board
+    pub fn num_stacks(&self) -> usize {
+        self.side_length / self.box_width
+    }
+
+    /// Apply a transformation to the board
+    pub fn transform(&mut self, transform: &Transform) -> Result<(), Error> {
+        match *transform {
+            Transform::SwapRows(row_1, row_2) => self.swap_rows(row_1, row_2)?,
+            Transform::SwapCols(col_1, col_2) => self.swap_cols(col_1, col_2)?,
+            Transform::SwapBands(band_1, band_2) => self.swap_bands(band_1, band_2)?,
+            Transform::SwapStacks(stack_1, stack_2) => self.swap_stacks(stack_1, stack_2)?,
+            Transform::Transpose => self.transpose(),
+            Transform::RotateClockwise => self.rotate_clockwise(),
+            Transform::RotateAnticlockwise => self.rotate_anticlockwise(),
+            Transform::Rotate180 => self.rotate_180(),
+            Transform::ReflectHorizontal => self.reflect_horizontal(),
+            Transform::ReflectVertical => self.reflect_vertical(),
+            Transform::ReflectAntiDiagonal => self.reflect_anti_diagonal(),
+            Transform::PermuteDigits(ref mapping) => self.permute_digits(mapping)?,
+        }
+        Ok(())
+    }
+
+    /// Move every square (along with where its value came from and its notes) to a new position.
+    /// `source` gives the coordinate that each square should be copied from.
+    fn remap<F: Fn(Coord) -> Coord>(&mut self, source: F) {
+        let cloned = self.clone();
+        let width = self.side_length;
+        for pos in 0..self.num_squares {
+            let from = source(pos.into_coord(width)).into_pos(width);
+            self.squares[pos] = cloned.squares[from];
+            self.origins[pos] = cloned.origins[from];
+            self.notes[pos] = cloned.notes[from];
+        }
+    }
+
+    /// Rearrange whole rows and columns, where `row_order[n]` is the row that ends up at row `n`
+    fn reorder(&mut self, row_order: &[usize], col_order: &[usize]) {
+        self.remap(|(col, row)| (col_order[col], row_order[row]));
+    }
+
+    /// Swap two rows, which must be within the same band
+    pub fn swap_rows(&mut self, row_1: usize, row_2: usize) -> Result<(), Error> {
+        let height = self.box_height;
+        if row_1 >= self.side_length || row_2 >= self.side_length ||
+            row_1 / height != row_2 / height
+        {
+            return Err(Error::InvalidSwap {
+                first: row_1,
+                second: row_2,
+            });
+        }
+        self.remap(|(col, row)| if row == row_1 {
+            (col, row_2)
+        } else if row == row_2 {
+            (col, row_1)
+        } else {
+            (col, row)
+        });
+        Ok(())
+    }
+
+    /// Swap two columns, which must be within the same stack
+    pub fn swap_cols(&mut self, col_1: usize, col_2: usize) -> Result<(), Error> {
+        let width = self.box_width;
+        if col_1 >= self.side_length || col_2 >= self.side_length ||
+            col_1 / width != col_2 / width
+        {
+            return Err(Error::InvalidSwap {
+                first: col_1,
+                second: col_2,
+            });
+        }
+        self.remap(|(col, row)| if col == col_1 {
+            (col_2, row)
+        } else if col == col_2 {
+            (col_1, row)
+        } else {
+            (col, row)
+        });
+        Ok(())
+    }
+
+    /// Swap two bands of rows
+    pub fn swap_bands(&mut self, band_1: usize, band_2: usize) -> Result<(), Error> {
+        if band_1 >= self.num_bands() || band_2 >= self.num_bands() {
+            return Err(Error::InvalidSwap {
+                first: band_1,
+                second: band_2,
+            });
+        }
+        let height = self.box_height;
+        self.remap(|(col, row)| {
+            let (band, offset) = (row / height, row % height);
+            if band == band_1 {
+                (col, band_2 * height + offset)
+            } else if band == band_2 {
+                (col, band_1 * height + offset)
+            } else {
+                (col, row)
+            }
+        });
+        Ok(())
+    }
+
+    /// Swap two stacks of columns
+    pub fn swap_stacks(&mut self, stack_1: usize, stack_2: usize) -> Result<(), Error> {
+        if stack_1 >= self.num_stacks() || stack_2 >= self.num_stacks() {
+            return Err(Error::InvalidSwap {
+                first: stack_1,
+                second: stack_2,
+            });
+        }
+        let width = self.box_width;
+        self.remap(|(col, row)| {
+            let (stack, offset) = (col / width, col % width);
+            if stack == stack_1 {
+                (stack_2 * width + offset, row)
+            } else if stack == stack_2 {
+                (stack_1 * width + offset, row)
+            } else {
+                (col, row)
+            }
+        });
+        Ok(())
+    }
+
+    /// Turning the board on its side also turns its grids, so grids that are wider than they are
+    /// tall become taller than they are wide
+    fn swap_box_dims(&mut self) {
+        mem::swap(&mut self.box_width, &mut self.box_height);
+    }
+
+    /// Reflect the board along its main diagonal
+    pub fn transpose(&mut self) {
+        self.remap(|(col, row)| (row, col));
+        self.swap_box_dims();
+    }
+
+    pub fn rotate_clockwise(&mut self) {
+        let last = self.side_length - 1;
+        self.remap(|(col, row)| (row, last - col));
+        self.swap_box_dims();
+    }
+
+    pub fn rotate_anticlockwise(&mut self) {
+        let last = self.side_length - 1;
+        self.remap(|(col, row)| (last - row, col));
+        self.swap_box_dims();
+    }
+
+    pub fn rotate_180(&mut self) {
+        let last = self.side_length - 1;
+        self.remap(|(col, row)| (last - col, last - row));
+    }
+
+    /// Mirror the board from left to right
+    pub fn reflect_horizontal(&mut self) {
+        let last = self.side_length - 1;
+        self.remap(|(col, row)| (last - col, row));
+    }
+
+    /// Mirror the board from top to bottom
+    pub fn reflect_vertical(&mut self) {
+        let last = self.side_length - 1;
+        self.remap(|(col, row)| (col, last - row));
+    }
+
+    /// Reflect the board along the diagonal from the top-right to the bottom-left
+    pub fn reflect_anti_diagonal(&mut self) {
+        let last = self.side_length - 1;
+        self.remap(|(col, row)| (last - row, last - col));
+        self.swap_box_dims();
+    }
+
+    /// Relabel every value (including notes), where value `n` becomes `mapping[n - 1]`.
+    /// `mapping` must contain every value of the board exactly once.
+    pub fn permute_digits(&mut self, mapping: &[SquareType]) -> Result<(), Error> {
+        let all_vals = Candidates::all(self.max_value);
+        let is_permutation = mapping.len() == self.side_length &&
+            mapping.iter().all(|&val| all_vals.contains(val)) &&
+            mapping.iter().cloned().collect::<Candidates>() == all_vals;
+        if !is_permutation {
+            return Err(Error::InvalidPermutation);
+        }
+
+        let relabel = |val: SquareType| mapping[(val - self.min_value) as usize];
+        let squares = self.squares.iter().map(|o| o.map(&relabel)).collect();
+        let notes = self.notes
+            .iter()
+            .map(|notes| notes.iter().map(&relabel).collect())
+            .collect();
+        self.squares = squares;
+        self.notes = notes;
+        Ok(())
+    }
+
+    /// A copy of this board with its digits relabelled, its rows and columns shuffled within their
+    /// bands and stacks, its bands and stacks shuffled, and (for square grids) possibly transposed.
+    /// The copy has the same solutions, up to the same changes, and so is just as difficult.
+    pub fn random_isomorph<R: Rng>(&self, rng: &mut R) -> Board {
+        let mut board = self.clone();
+
+        let mut mapping: Vec<SquareType> = (self.min_value..self.max_value + 1).collect();
+        rng.shuffle(&mut mapping);
+        board
+            .permute_digits(&mapping)
+            .expect("A shuffled list of values is always a permutation");
+
+        let row_order = random_order(self.num_bands(), self.box_height, rng);
+        let col_order = random_order(self.num_stacks(), self.box_width, rng);
+        board.reorder(&row_order, &col_order);
+
+        if self.box_width == self.box_height && rng.gen() {
+            board.transpose();
+        }
+        board
+    }
+}
+
+/// A random order of `num_groups * group_size` lines, which keeps each group of lines together
+fn random_order<R: Rng>(num_groups: usize, group_size: usize, rng: &mut R) -> Vec<usize> {
+    let mut groups: Vec<usize> = (0..num_groups).collect();
+    rng.shuffle(&mut groups);
+    let mut order = vec![];
+    for group in groups {
+        let mut lines: Vec<usize> = (group * group_size..(group + 1) * group_size).collect();
+        rng.shuffle(&mut lines);
+        order.extend(lines);
+    }
+    order
+}
+
+
+#[cfg(test)]
+pub const SWAPPED_ROW_STR: &str = concat!(
+    "9,6,5,3,2,7,1,4,8,",
+    "8,2,7,1,5,4,3,9,6,",
+    "3,4,1,6,8,9,7,5,2,",
+    "5,9,3,4,6,8,2,7,1,",
+    "4,7,2,5,1,3,6,8,9,",
+    "6,1,8,9,7,2,4,3,5,",
+    "7,8,6,2,3,5,9,1,4,",
+    "1,5,4,7,9,6,8,2,3,",
+    "2,3,9,8,4,1,5,6,7"
+);
+
+#[cfg(test)]
+pub const SWAPPED_COL_STR: &str = concat!(
+    "2,8,7,1,5,4,3,9,6,",
+    "6,9,5,3,2,7,1,4,8,",
+    "4,3,1,6,8,9,7,5,2,",
+    "9,5,3,4,6,8,2,7,1,",
+    "7,4,2,5,1,3,6,8,9,",
+    "1,6,8,9,7,2,4,3,5,",
+    "8,7,6,2,3,5,9,1,4,",
+    "5,1,4,7,9,6,8,2,3,",
+    "3,2,9,8,4,1,5,6,7"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, XorShiftRng};
+    use solver::BoardStatus;
+
+    #[test]
+    fn test_swap_rows() {
+        let mut board = Board::from_string(SOLVED_PUZZLE_STR);
+        assert_eq!(SOLVED_PUZZLE_STR, board.to_string());
+        assert!(board.check_status().is_valid());
+        board.swap_rows(0, 1).unwrap();
+        assert_ne!(SOLVED_PUZZLE_STR, board.to_string());
+        assert_eq!(SWAPPED_ROW_STR, board.to_string());
+    }
+
+    #[test]
+    fn test_swap_cols() {
+        let mut board = Board::from_string(SOLVED_PUZZLE_STR);
+        assert_eq!(SOLVED_PUZZLE_STR, board.to_string());
+        assert!(board.check_status().is_valid());
+        board.swap_cols(0, 1).unwrap();
+        assert_ne!(SOLVED_PUZZLE_STR, board.to_string());
+        assert_eq!(SWAPPED_COL_STR, board.to_string());
+    }
+
+    #[test]
+    fn test_swap_across_bands() {
+        let mut board = Board::from_string(SOLVED_PUZZLE_STR);
+        assert_eq!(
+            Err(Error::InvalidSwap {
+                first: 2,
+                second: 3,
+            }),
+            board.swap_rows(2, 3)
+        );
+        assert!(board.swap_cols(0, 8).is_err());
+        assert!(board.swap_bands(0, 3).is_err());
+        assert_eq!(SOLVED_PUZZLE_STR, board.to_string());
+
+        board.swap_bands(0, 2).unwrap();
+        board.swap_stacks(1, 2).unwrap();
+        assert_eq!(BoardStatus::Solved, board.check_status());
+        assert_eq!(Some(8), board.get_val((0, 6)).unwrap());
+    }
+
+    #[test]
+    fn test_rotations() {
+        let original = Board::from_string(SOLVED_PUZZLE_STR);
+        let mut board = original.clone();
+        board.rotate_clockwise();
+        // The top-left square ends up in the top-right
+        assert_eq!(original.get_val((0, 0)), board.get_val((8, 0)));
+        board.rotate_clockwise();
+        let mut rotated = original.clone();
+        rotated.rotate_180();
+        assert_eq!(rotated, board);
+        board.rotate_anticlockwise();
+        board.rotate_anticlockwise();
+        assert_eq!(original, board);
+
+        // Rotating 90 degrees is the same as transposing and reflecting
+        let mut transposed = original.clone();
+        transposed.transpose();
+        transposed.reflect_horizontal();
+        let mut rotated = original.clone();
+        rotated.rotate_clockwise();
+        assert_eq!(rotated, transposed);
+
+        let mut reflected = original.clone();
+        reflected.reflect_anti_diagonal();
+        assert_eq!(original.get_val((0, 0)), reflected.get_val((8, 8)));
+        reflected.reflect_vertical();
+        assert_eq!(BoardStatus::Solved, reflected.check_status());
+    }
+
+    #[test]
+    fn test_rotate_rectangular() {
+        let mut board = Board::from_string(SOLVED_6_STR);
+        board.rotate_clockwise();
+        assert_eq!((2, 3), (board.box_width, board.box_height));
+        assert_eq!(BoardStatus::Solved, board.check_status());
+        board.transpose();
+        assert_eq!((3, 2), (board.box_width, board.box_height));
+        assert_eq!(BoardStatus::Solved, board.check_status());
+    }
+
+    #[test]
+    fn test_rotate_keeps_givens() {
+        let mut board = Board::from_string(UNSOLVED_PUZZLE_STR);
+        board.lock_givens();
+        board.set_val((4, 0), Some(9)).unwrap();
+        board.rotate_anticlockwise();
+        // Rotating anti-clockwise moves the top row to the left column, with the top-left square
+        // ending up in the bottom-left
+        assert_eq!(Some(Origin::Player), board.get_origin((0, 4)).unwrap());
+        assert!(board.is_given((0, 8)));
+        assert_eq!(board.get_val((0, 8)).unwrap(), Some(8));
+    }
+
+    #[test]
+    fn test_permute_digits() {
+        let mut board = Board::from_string(SOLVED_PUZZLE_STR);
+        board.toggle_note(0, 1).unwrap();
+        assert_eq!(
+            Err(Error::InvalidPermutation),
+            board.permute_digits(&[1, 1, 2, 3, 4, 5, 6, 7, 8])
+        );
+        assert!(board.permute_digits(&[2, 3, 4, 5, 6, 7, 8, 9]).is_err());
+
+        board.permute_digits(&[9, 8, 7, 6, 5, 4, 3, 2, 1]).unwrap();
+        assert_eq!(Some(2), board.get_val(0).unwrap());
+        assert_eq!(vec![9], board.get_notes(0).unwrap().to_vec());
+        assert_eq!(BoardStatus::Solved, board.check_status());
+    }
+
+    #[test]
+    fn test_random_isomorph() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        for puzzle in &[SOLVED_PUZZLE_STR, SOLVED_6_STR] {
+            let board = Board::from_string(puzzle);
+            for _ in 0..10 {
+                let isomorph = board.random_isomorph(&mut rng);
+                assert_eq!(BoardStatus::Solved, isomorph.check_status());
+            }
+        }
+
+        let puzzle = Board::from_string(UNSOLVED_PUZZLE_STR);
+        let mut isomorph = puzzle.random_isomorph(&mut rng);
+        assert_eq!(puzzle.num_filled(), isomorph.num_filled());
+        assert_eq!(BoardStatus::Solved, isomorph.solve_standard());
+    }
+}
@@ -1,4 +1,5 @@
 extern crate sudoku;
+extern crate rand;
 extern crate regex;
 extern crate term_painter;
 
@@ -9,17 +10,25 @@ mod test;
 use sudoku::board::*;
 use sudoku::solver::*;
 use sudoku::hintmap::HintMap;
+use sudoku::history::{GameSession, MoveKind};
+use sudoku::transform::Transform;
 
-use std::{env, io, time};
+use std::{cmp, env, io, process, time};
 use std::io::Read;
 use std::io::prelude::*;
 use std::fs::File;
 
 use regex::Regex;
 
-use term_painter::ToStyle;
+use term_painter::{Attr, ToStyle};
 use term_painter::Color::*;
 
+/// Number of solutions the `count` command stops at, unless it is given a limit
+const DEFAULT_COUNT_LIMIT: usize = 100;
+
+/// Seconds the `solve` command gives up after, unless it is given a timeout
+const DEFAULT_SOLVE_TIMEOUT: u64 = 60;
+
 #[derive(Debug, PartialEq)]
 enum Command {
     Quit,
@@ -31,13 +40,32 @@ enum Command {
         val: Option<SquareType>,
     },
     Clear { x: Pos, y: Pos },
+    Note {
+        x: Pos,
+        y: Pos,
+        val: SquareType,
+    },
+    ClearNotes { x: Pos, y: Pos },
+    FillNotes,
+    ClearAllNotes,
     Hint { x: Pos, y: Pos },
     HintAll,
     FromBase(usize),
+    FromBoxDims(usize, usize),
     Sample,
     Reset,
     Check,
-    Solve(SolveType),
+    Count(usize),
+    Solve {
+        algorithm: Algorithm,
+        max_depth: Option<usize>,
+        /// Seconds to give up after, or 0 to never give up
+        timeout: u64,
+        /// Print the stats as JSON
+        json: bool,
+    },
+    /// Solve with only these techniques, explaining each step
+    Logic(Vec<Technique>),
     RandRow(usize),
     RandCol(usize),
     RandGrid(usize),
@@ -46,29 +74,26 @@ enum Command {
     Save { file_name: String },
     Load { file_name: String },
     ShowStr,
+    ShowGivensStr,
+    Undo,
+    Redo,
+    Lock,
+    Unlock,
+    Transform(Transform),
+    RandomTransform,
     Other(String),
 }
 
-#[derive(Debug, PartialEq)]
-enum SolveType {
-    Standard,
-    Ordered,
-    Parallel,
-    Search,
-    SearchParallel,
-    DFS(Option<i32>),
-}
-
-
 #[derive(Debug, PartialEq)]
 enum Action {
     Quit,
     Continue,
     ContinueWithoutPrinting,
+    Failed,
 }
 
 pub fn main() {
-    let mut game_board = Board::default();
+    let mut game_board = GameSession::default();
 
     let args: Vec<_> = env::args().collect();
     if args.len() > 1 {
@@ -87,6 +112,11 @@ pub fn main() {
                     println!("QUIT");
                     return;
                 }
+                Action::Failed => {
+                    // Don't carry on with the remaining arguments on a bad board
+                    eprintln!("Failed to process argument: {}", input);
+                    process::exit(1);
+                }
             }
         }
     } else {
@@ -114,7 +144,7 @@ pub fn main() {
                 Action::Continue => {
                     game_board.print();
                 }
-                Action::ContinueWithoutPrinting => {}
+                Action::ContinueWithoutPrinting | Action::Failed => {}
                 Action::Quit => {
                     println!("Quitting...");
                     break 'game_loop;
@@ -127,7 +157,7 @@ pub fn main() {
 
 fn get_command(input: &str) -> Command {
     //Capture all words, numbers, etc.
-    let captures: Vec<String> = Regex::new(r"([\w\.\\/]+)")
+    let captures: Vec<String> = Regex::new(r"([\w\.\\/-]+)")
         .unwrap()
         .captures_iter(&input)
         .map(|caps| String::from(&caps[0]).to_lowercase())
@@ -138,58 +168,94 @@ fn get_command(input: &str) -> Command {
         match captures[0].as_ref() {
             "quit" | "exit" => Command::Quit,
             "reset" => Command::Reset,
-            "solve" if num_args == 1 => Command::Solve(SolveType::Standard),
+            "solve" if num_args == 1 => Command::Solve {
+                algorithm: Algorithm::Standard,
+                max_depth: None,
+                timeout: DEFAULT_SOLVE_TIMEOUT,
+                json: false,
+            },
             "solve" if num_args > 1 => {
-                if let Some(solve_type) = match captures[1].as_ref() {
-                    "standad" | "normal" => Some(SolveType::Standard),
-                    "ordered" => Some(SolveType::Ordered),
-                    "parallel" => Some(SolveType::Parallel),
-                    "search" if num_args > 2 => {
-                        match captures[2].as_ref() {
-                            "standard" => Some(SolveType::Search),
-                            "parallel" => Some(SolveType::SearchParallel),
-                            _ => None,
-                        }
+                let mut timeout = DEFAULT_SOLVE_TIMEOUT;
+                let mut num_args = num_args;
+                let json = captures[num_args - 1] == "json";
+                if json {
+                    num_args -= 1;
+                }
+                if num_args > 2 && captures[num_args - 2] == "timeout" {
+                    match captures[num_args - 1].parse::<u64>() {
+                        Ok(secs) => timeout = secs,
+                        Err(_) => println!("Unable to parse timeout."),
                     }
-                    "search" => Some(SolveType::Search),
+                    num_args -= 2;
+                }
+                let mut max_depth = None;
+                let algorithm = match captures[1].as_ref() {
+                    _ if num_args == 1 => Ok(Algorithm::Standard),
+                    "standad" | "normal" => Ok(Algorithm::Standard),
+                    "search" if num_args > 2 => match captures[2].as_ref() {
+                        "standard" => Ok(Algorithm::Search),
+                        "parallel" => Ok(Algorithm::SearchParallel),
+                        name => name.parse(),
+                    },
                     "dfs" => {
-                        let max_depth = if num_args > 2 {
-                            if let Ok(max_depth) = captures[2].parse::<i32>() {
-                                Some(max_depth)
+                        if num_args > 2 {
+                            if let Ok(depth) = captures[2].parse::<usize>() {
+                                max_depth = Some(depth);
                             } else {
                                 println!("Unable to parse depth.");
-                                None
                             }
-                        } else {
-                            None
-                        };
-                        Some(SolveType::DFS(max_depth))
+                        }
+                        Ok(Algorithm::Dfs)
+                    }
+                    name => name.parse(),
+                };
+                match algorithm {
+                    Ok(algorithm) => Command::Solve {
+                        algorithm,
+                        max_depth,
+                        timeout,
+                        json,
+                    },
+                    Err(err) => {
+                        println!("{}", err);
+                        Command::Unrecognised
                     }
-                    _ => None,
                 }
-                {
-                    Command::Solve(solve_type)
-                } else {
-                    Command::Unrecognised
+            }
+            "logic" if num_args == 1 => Command::Logic(Technique::logical().to_vec()),
+            "logic" => {
+                let techniques: Result<Vec<Technique>, _> =
+                    captures[1..].iter().map(|name| name.parse()).collect();
+                match techniques {
+                    Ok(techniques) => Command::Logic(techniques),
+                    Err(err) => {
+                        println!("{}", err);
+                        Command::Unrecognised
+                    }
                 }
             }
-            //"rand" if num_args == 1 => {
-            //}
             "rand" if num_args == 2 && captures[1] == String::from("dash") => Command::RandDash,
             "rand" if num_args == 3 => {
-                //TODO: proper error checking
-                let num = captures[2].parse::<usize>().unwrap();
-                match captures[1].as_ref() {
-                    "row" => Command::RandRow(num),
-                    "col" => Command::RandCol(num),
-                    "grid" => Command::RandGrid(num),
-                    _ => Command::Unrecognised,
+                if let Ok(num) = captures[2].parse::<usize>() {
+                    match captures[1].as_ref() {
+                        "row" => Command::RandRow(num),
+                        "col" => Command::RandCol(num),
+                        "grid" => Command::RandGrid(num),
+                        _ => Command::Unrecognised,
+                    }
+                } else {
+                    Command::Unrecognised
                 }
             }
             "generate" | "new" => Command::Generate,
             "sample" => Command::Sample,
             "help" => Command::Help,
             "check" => Command::Check,
+            "count" if num_args == 1 => Command::Count(DEFAULT_COUNT_LIMIT),
+            "count" if num_args == 2 => match captures[1].parse::<usize>() {
+                Ok(limit) if limit > 0 => Command::Count(limit),
+                _ => Command::Unrecognised,
+            },
             "set" if num_args == 4 => {
                 let x = captures[1].parse::<Pos>();
                 let y = captures[2].parse::<Pos>();
@@ -212,6 +278,25 @@ fn get_command(input: &str) -> Command {
                     _ => Command::Unrecognised,
                 }
             }
+            "note" if num_args == 4 => {
+                let x = captures[1].parse::<Pos>();
+                let y = captures[2].parse::<Pos>();
+                let val = captures[3].parse::<SquareType>();
+                match (x, y, val) {
+                    (Ok(x), Ok(y), Ok(val)) => Command::Note { x, y, val },
+                    _ => Command::Unrecognised,
+                }
+            }
+            "note" if num_args == 3 => {
+                let x = captures[1].parse::<Pos>();
+                let y = captures[2].parse::<Pos>();
+                match (x, y) {
+                    (Ok(x), Ok(y)) => Command::ClearNotes { x, y },
+                    _ => Command::Unrecognised,
+                }
+            }
+            "notes" if num_args == 1 => Command::FillNotes,
+            "notes" if num_args == 2 && captures[1] == "clear" => Command::ClearAllNotes,
             "hints" => Command::HintAll,
             "hint" if num_args == 2 && captures[1] == String::from("all") => Command::HintAll,
             "hint" if num_args == 3 => {
@@ -248,7 +333,23 @@ fn get_command(input: &str) -> Command {
                     Command::Unrecognised
                 }
             }
+            "box" if num_args == 3 => {
+                let width = captures[1].parse::<usize>();
+                let height = captures[2].parse::<usize>();
+                match (width, height) {
+                    (Ok(width), Ok(height)) => Command::FromBoxDims(width, height),
+                    _ => Command::Unrecognised,
+                }
+            }
+            "string" | "str" if num_args == 2 && captures[1] == "givens" => {
+                Command::ShowGivensStr
+            }
             "string" | "str" => Command::ShowStr,
+            "transform" if num_args > 1 => get_transform(&captures[1..]),
+            "undo" => Command::Undo,
+            "redo" => Command::Redo,
+            "lock" => Command::Lock,
+            "unlock" => Command::Unlock,
             _ => Command::Other(input.to_string()),
             //_ => Command::Unrecognised,
         }
@@ -257,7 +358,45 @@ fn get_command(input: &str) -> Command {
     }
 }
 
-fn process_command(command: Command, game_board: &mut Board) -> Action {
+/// Parse the arguments of a `transform` command
+fn get_transform(args: &[String]) -> Command {
+    let nums: Vec<usize> = args[1..]
+        .iter()
+        .filter_map(|arg| arg.parse::<usize>().ok())
+        .collect();
+    let transform = match (args[0].as_ref(), args.len()) {
+        ("random", 1) => return Command::RandomTransform,
+        ("transpose", 1) => Transform::Transpose,
+        ("rotate", 1) => Transform::RotateClockwise,
+        ("rotate", 2) => match args[1].as_ref() {
+            "cw" | "clockwise" => Transform::RotateClockwise,
+            "ccw" | "anticlockwise" => Transform::RotateAnticlockwise,
+            "180" => Transform::Rotate180,
+            _ => return Command::Unrecognised,
+        },
+        ("reflect", 2) | ("mirror", 2) => match args[1].as_ref() {
+            "h" | "horizontal" => Transform::ReflectHorizontal,
+            "v" | "vertical" => Transform::ReflectVertical,
+            "d" | "diagonal" => Transform::Transpose,
+            "a" | "antidiagonal" => Transform::ReflectAntiDiagonal,
+            _ => return Command::Unrecognised,
+        },
+        ("swap", 4) if nums.len() == 2 => match args[1].as_ref() {
+            "row" => Transform::SwapRows(nums[0], nums[1]),
+            "col" => Transform::SwapCols(nums[0], nums[1]),
+            "band" => Transform::SwapBands(nums[0], nums[1]),
+            "stack" => Transform::SwapStacks(nums[0], nums[1]),
+            _ => return Command::Unrecognised,
+        },
+        ("digits", _) if nums.len() == args.len() - 1 => {
+            Transform::PermuteDigits(nums.iter().map(|&num| num as SquareType).collect())
+        }
+        _ => return Command::Unrecognised,
+    };
+    Command::Transform(transform)
+}
+
+fn process_command(command: Command, game_board: &mut GameSession) -> Action {
     match command {
         Command::Quit => Action::Quit,
         Command::Help => {
@@ -271,32 +410,61 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
             let commands = [
                 ("set [COL] [ROW] [VAL]", "Set a value."),
                 ("clear [COL] [ROW]", "Clear a value."),
+                ("note [COL] [ROW] [VAL]", "Add or remove a note."),
+                ("note [COL] [ROW]", "Clear the notes for a square."),
+                ("notes", "Fill in the notes from the hints."),
+                ("notes clear", "Clear all notes."),
+                (
+                    "transform [TRANSFORM]",
+                    "Transform the puzzle without changing its difficulty (see below).",
+                ),
+                ("undo", "Undo the last move."),
+                ("redo", "Redo the last undone move."),
                 ("check", "Check the board is valid/solved."),
+                (
+                    "count [LIMIT]",
+                    "Count the solutions to the puzzle, stopping after LIMIT (default 100).",
+                ),
                 ("hint [COL] [ROW]", "Get hints for a square."),
                 ("hint all", "Display all hints for the board."),
                 (
                     "base [NUM]",
                     "Set the base number for the puzzle. Default is 3 (for 9*9 board).",
                 ),
+                (
+                    "box [WIDTH] [HEIGHT]",
+                    "Use grids of the given size, e.g. \"box 3 2\" for a 6*6 board.",
+                ),
                 ("reset", "Reset the board to default dimensions."),
+                (
+                    "lock",
+                    "Lock the current values as givens, so they can't be changed.",
+                ),
+                ("unlock", "Allow the givens to be changed again."),
                 ("sample", "Load the sample puzzle."),
                 ("solve", "Solve the puzzle (simple mode)."),
                 (
-                    "solve parallel",
-                    "Solve the puzzle (multithreaded simple mode).",
+                    "solve [ALGORITHM]",
+                    "Solve the puzzle with a particular algorithm (see below).",
                 ),
-                ("solve search", "Recursively solve the puzzle."),
+                ("solve dfs [DEPTH]", "Depth first search, up to DEPTH guesses deep."),
                 (
-                    "solve search parallel",
-                    "Recursively solve the puzzle (multithreaded).",
+                    "solve ... timeout [SECS]",
+                    "Give up solving after SECS seconds (default 60, 0 for never).",
                 ),
+                ("solve ... json", "Print the solver's stats as JSON."),
                 (
                     "compare",
                     concat!(
-                        "Compare the execution time of sequential and parallel solve algorithms for the given board.",
+                        "Compare the stats of sequential and parallel solve algorithms for the given board.",
                         " Does not affect active puzzle."
                     ),
                 ),
+                ("compare json", "Print the comparison as JSON, one line per algorithm."),
+                (
+                    "logic [TECHNIQUES...]",
+                    "Solve without guessing, explaining each step. Uses every technique by default.",
+                ),
                 ("generate", "Generate a new puzzle."),
                 // ("")
                 ("save [FILE_PATH]", "Save a puzzle."),
@@ -305,6 +473,10 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
                     "string",
                     "Display the string representation of this puzzle.",
                 ),
+                (
+                    "string givens",
+                    "Display the string representation of the original puzzle.",
+                ),
                 ("help", "Display help menu."),
                 ("exit", "Quit the game."),
             ];
@@ -313,6 +485,46 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
                 println!("> {:23}-> {}", cmd, description);
             }
 
+            println!();
+            println!("Transformations:");
+            let transforms = [
+                ("rotate [cw|ccw|180]", "Rotate the puzzle, clockwise by default."),
+                (
+                    "reflect [h|v|d|a]",
+                    "Reflect horizontally, vertically, or along either diagonal.",
+                ),
+                ("transpose", "Swap the rows and columns."),
+                (
+                    "swap row|col [A] [B]",
+                    "Swap two rows in the same band, or columns in the same stack.",
+                ),
+                ("swap band|stack [A] [B]", "Swap two rows or columns of grids."),
+                (
+                    "digits [VALS...]",
+                    "Relabel the values, so 1 becomes the first value given, etc.",
+                ),
+                ("random", "Apply a random combination of the above."),
+            ];
+            for &(transform, description) in transforms.iter() {
+                println!("> transform {:23}-> {}", transform, description);
+            }
+
+            println!();
+            println!("Solving algorithms:");
+            for algorithm in Algorithm::all() {
+                println!("> solve {:27}-> {}", algorithm.name(), algorithm.description());
+            }
+
+            println!();
+            println!("Solving techniques:");
+            for technique in Technique::logical() {
+                println!("> logic {:27}-> {}", technique.name(), technique);
+            }
+            println!("Only used when the board has one solution:");
+            for technique in Technique::uniqueness() {
+                println!("> logic {:27}-> {}", technique.name(), technique);
+            }
+
             println!("");
             println!("Other notes:");
             println!(
@@ -330,19 +542,41 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
             Action::ContinueWithoutPrinting
         }
         Command::Set { x, y, val } => {
-            let result = game_board.set_val((x, y), val);
-            if !result.is_ok() {
-                println!("Error trying to set value. Index or value may be out of range.");
+            let result = game_board.apply(MoveKind::Set, |board| board.set_val((x, y), val));
+            if let Err(err) = result {
+                println!("Error trying to set value: {}.", err);
             }
             Action::Continue
         }
         Command::Clear { x, y } => {
-            let result = game_board.set_val((x, y), None);
-            if !result.is_ok() {
-                println!("Error trying to clear value. Index may be out of range.");
+            let result = game_board.apply(MoveKind::Clear, |board| board.set_val((x, y), None));
+            if let Err(err) = result {
+                println!("Error trying to clear value: {}.", err);
+            }
+            Action::Continue
+        }
+        Command::Note { x, y, val } => {
+            let result = game_board.apply(MoveKind::Note, |board| board.toggle_note((x, y), val));
+            if let Err(err) = result {
+                println!("Error trying to change note: {}.", err);
             }
             Action::Continue
         }
+        Command::ClearNotes { x, y } => {
+            let result = game_board.apply(MoveKind::Note, |board| board.clear_notes((x, y)));
+            if let Err(err) = result {
+                println!("Error trying to clear notes: {}.", err);
+            }
+            Action::Continue
+        }
+        Command::FillNotes => {
+            game_board.apply(MoveKind::Note, |board| board.fill_notes());
+            Action::Continue
+        }
+        Command::ClearAllNotes => {
+            game_board.apply(MoveKind::Note, |board| board.clear_all_notes());
+            Action::Continue
+        }
         Command::Hint { x, y } => {
             let hmap = HintMap::from_board(&game_board);
             println!("{:?}", hmap.get_hints((x, y)));
@@ -364,36 +598,61 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
             Action::Continue
         }
         Command::Sample => {
-            *game_board = Board::from_string(UNSOLVED_PUZZLE_STR);
+            let mut sample = Board::from_string(UNSOLVED_PUZZLE_STR);
+            sample.lock_givens();
+            game_board.replace(MoveKind::Load, sample);
             Action::Continue
         }
         Command::Reset => {
-            *game_board = Board::default();
+            game_board.replace(MoveKind::Load, Board::default());
             Action::Continue
         }
         Command::FromBase(base_num) => {
-            *game_board = Board::from_base_num(base_num);
+            match Board::from_base_num(base_num) {
+                Ok(board) => game_board.replace(MoveKind::Load, board),
+                Err(err) => println!("Error creating board: {}.", err),
+            }
+            Action::Continue
+        }
+        Command::FromBoxDims(box_width, box_height) => {
+            match Board::with_box_dims(box_width, box_height) {
+                Ok(board) => game_board.replace(MoveKind::Load, board),
+                Err(err) => println!("Error creating board: {}.", err),
+            }
             Action::Continue
         }
         Command::RandRow(row_num) => {
-            game_board.set_row_rand(row_num);
+            let result = game_board.apply(MoveKind::Edit, |board| board.set_row_rand(row_num));
+            if let Err(err) = result {
+                println!("Error trying to randomise row: {}.", err);
+            }
             Action::Continue
         }
         Command::RandCol(col_num) => {
-            game_board.set_col_rand(col_num);
+            let result = game_board.apply(MoveKind::Edit, |board| board.set_col_rand(col_num));
+            if let Err(err) = result {
+                println!("Error trying to randomise column: {}.", err);
+            }
             Action::Continue
         }
         Command::RandGrid(grid_num) => {
-            game_board.set_grid_rand(grid_num);
+            let result = game_board.apply(MoveKind::Edit, |board| board.set_grid_rand(grid_num));
+            if let Err(err) = result {
+                println!("Error trying to randomise grid: {}.", err);
+            }
             Action::Continue
         }
         Command::RandDash => {
-            let base_num = game_board.base_num;
-            *game_board = Board::from_base_num(base_num);
-            for i in 0..game_board.side_length {
-                let grid_num = game_board.get_grid_num((i, i));
-                game_board.set_grid_rand(grid_num);
+            let (box_width, box_height) = (game_board.box_width, game_board.box_height);
+            let mut new_board = Board::with_box_dims(box_width, box_height)
+                .expect("The current board's size is always supported");
+            for i in 0..cmp::min(box_width, box_height) {
+                let grid_num = new_board.get_grid_num((i * box_width, i * box_height));
+                new_board.set_grid_rand(grid_num).expect(
+                    "Diagonal grids are always within the board",
+                );
             }
+            game_board.replace(MoveKind::Edit, new_board);
             Action::Continue
         }
         Command::Check => {
@@ -405,34 +664,84 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
             println!("{}", msg);
             Action::Continue
         }
-        Command::Solve(solve_type) => {
+        Command::Count(limit) => {
+            // Look for a second solution even with a limit of 1, to tell if it's unique
+            let count = game_board.count_solutions(cmp::max(limit, 2));
+            match count {
+                0 => println!("Puzzle has no solutions"),
+                1 => println!("Puzzle has a unique solution"),
+                _ if count >= limit => println!("Puzzle has at least {} solutions", count),
+                _ => println!("Puzzle has {} solutions", count),
+            }
+            Action::Continue
+        }
+        Command::Solve {
+            algorithm,
+            max_depth,
+            timeout,
+            json,
+        } => {
             println!("Solving...");
-            let now = time::SystemTime::now();
-            let solved = match solve_type {
-                SolveType::Standard => game_board.solve_standard().is_solved(),
-                SolveType::Ordered => game_board.solve_ordered().is_solved(),
-                SolveType::Parallel => game_board.solve_parallel().is_solved(),
-                SolveType::Search => game_board.solve_search(),
-                SolveType::SearchParallel => game_board.solve_search_parallel(),
-                SolveType::DFS(max_depth) => game_board.solve_search_dfs(max_depth).is_solved(),
+            let options = SolveOptions {
+                max_depth,
+                timeout: if timeout > 0 {
+                    Some(time::Duration::from_secs(timeout))
+                } else {
+                    None
+                },
+                ..SolveOptions::default()
             };
-            if solved {
-                println!("Successfully solved!");
+            let (outcome, stats) = game_board.apply(MoveKind::Solve, |board| {
+                board.solve_with_stats(&algorithm, &options)
+            });
+            match outcome {
+                SolveOutcome::Solved(_) => println!("Successfully solved!"),
+                SolveOutcome::Aborted { ref best, .. } => {
+                    println!("Unable to solve: {}.", outcome);
+                    println!(
+                        "Got as far as {} of {} squares filled in.",
+                        best.num_filled(),
+                        best.num_squares
+                    );
+                }
+                _ => println!("Unable to solve: {}.", outcome),
+            }
+            if json {
+                println!("{}", stats.to_json());
             } else {
-                println!("Unable to solve.");
+                println!("{}", stats);
+            }
+            Action::Continue
+        }
+        Command::Logic(techniques) => {
+            let mut lboard = LogicBoard::new(game_board.board().clone());
+            if techniques.iter().any(|technique| technique.needs_unique()) {
+                lboard.check_unique();
+            }
+            let deductions = lboard.solve(&techniques);
+            for (step, deduction) in deductions.iter().enumerate() {
+                println!("{}. {}", step + 1, deduction.explain(&lboard));
+            }
+            let msg = match lboard.check_status() {
+                BoardStatus::Solved => "Successfully solved!",
+                BoardStatus::Invalid => "Board is invalid",
+                BoardStatus::Valid => "No more steps can be found with these techniques",
+            };
+            println!("{}", msg);
+            if !deductions.is_empty() {
+                game_board.replace(MoveKind::Solve, lboard.into_board());
             }
-            let elapsed = now.elapsed().expect("Error retrieving time");
-            println!("Took {} seconds.", elapsed.as_secs());
             Action::Continue
         }
         Command::Generate => {
             println!("Generating...");
             let now = time::SystemTime::now();
-            if let Ok(new_board) = Board::generate_new(game_board.base_num) {
-                *game_board = new_board;
-                println!("Generated successfully!");
-            } else {
-                println!("Generating failed.");
+            match Board::generate_with_box_dims(game_board.box_width, game_board.box_height) {
+                Ok(new_board) => {
+                    game_board.replace(MoveKind::Generate, new_board);
+                    println!("Generated successfully!");
+                }
+                Err(err) => println!("Generating failed: {}.", err),
             }
             let elapsed = now.elapsed().expect("Error retrieving time");
             println!("Took {} seconds", elapsed.as_secs());
@@ -455,13 +764,24 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
             println!("Loading from {}", file_name);
             if let Ok(mut input_file) = File::open(file_name) {
                 let mut buffer = String::new();
-                match input_file.read_to_string(&mut buffer) {
-                    Ok(_) => println!("Loaded successfully!"),
-                    Err(_) => println!("Failed to read."),
+                if input_file.read_to_string(&mut buffer).is_err() {
+                    println!("Failed to read.");
+                    return Action::Failed;
+                }
+                match Board::parse(&buffer) {
+                    Ok(mut new_board) => {
+                        new_board.lock_givens();
+                        game_board.replace(MoveKind::Load, new_board);
+                        println!("Loaded successfully!");
+                    }
+                    Err(err) => {
+                        println!("Invalid puzzle: {}", err);
+                        return Action::Failed;
+                    }
                 }
-                *game_board = Board::from_string(&buffer);
             } else {
                 println!("Unable to open");
+                return Action::Failed;
             }
             Action::Continue
         }
@@ -469,61 +789,58 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
             println!("{}", game_board.to_string());
             Action::Continue
         }
+        Command::ShowGivensStr => {
+            println!("{}", game_board.to_givens_string());
+            Action::Continue
+        }
+        Command::Undo => {
+            match game_board.undo() {
+                Some(kind) => println!("Undid {:?}.", kind),
+                None => println!("Nothing to undo."),
+            }
+            Action::Continue
+        }
+        Command::Redo => {
+            match game_board.redo() {
+                Some(kind) => println!("Redid {:?}.", kind),
+                None => println!("Nothing to redo."),
+            }
+            Action::Continue
+        }
+        Command::Transform(transform) => {
+            let result = game_board.apply(MoveKind::Transform, |board| board.transform(&transform));
+            if let Err(err) = result {
+                println!("Error trying to transform: {}.", err);
+            }
+            Action::Continue
+        }
+        Command::RandomTransform => {
+            let isomorph = game_board.random_isomorph(&mut rand::thread_rng());
+            game_board.replace(MoveKind::Transform, isomorph);
+            Action::Continue
+        }
+        Command::Lock => {
+            game_board.apply(MoveKind::Edit, |board| board.lock_givens());
+            Action::Continue
+        }
+        Command::Unlock => {
+            game_board.apply(MoveKind::Edit, |board| board.unlock_givens());
+            Action::Continue
+        }
 
         //Lets me be sneaky and add extra commands on the fly
         Command::Other(input) => {
             match input.trim().as_ref() {
                 "reduce" => {
-                    game_board.reduce();
-                    Action::Continue
-                }
-                "mirror" => {
-                    game_board._mirror();
-                    Action::Continue
-                }
-                "rotate" => {
-                    game_board._rotate();
+                    game_board.apply(MoveKind::Edit, |board| board.reduce());
                     Action::Continue
                 }
                 "compare" => {
-                    let mut board_clone = game_board.clone();
-                    let now = time::SystemTime::now();
-                    let solved = board_clone.solve_search_parallel();
-                    let parallel_elapsed = now.elapsed().expect("Error retrieving time");
-                    let parallel_status = if solved { "success" } else { "failed" };
-                    println!(
-                        "parallel done ({}, {}) {}",
-                        parallel_status,
-                        parallel_elapsed.as_secs(),
-                        board_clone.to_string()
-                    );
-                    board_clone.print();
-
-                    let mut board_clone = game_board.clone();
-                    let now = time::SystemTime::now();
-                    let solved = board_clone.solve_search();
-                    let sequential_elapsed = now.elapsed().expect("Error retrieving time");
-                    let sequential_status = if solved { "success" } else { "failed" };
-                    println!(
-                        "sequential done ({}, {}) {}",
-                        sequential_status,
-                        sequential_elapsed.as_secs(),
-                        board_clone.to_string()
-                    );
-                    board_clone.print();
-
-                    println!("------------------------");
-                    println!(
-                        "Parallel:   {} ({})",
-                        parallel_elapsed.as_secs(),
-                        parallel_status
-                    );
-                    println!(
-                        "Sequential: {} ({})",
-                        sequential_elapsed.as_secs(),
-                        sequential_status
-                    );
-                    println!("------------------------");
+                    compare(game_board.board(), false);
+                    Action::ContinueWithoutPrinting
+                }
+                "compare json" => {
+                    compare(game_board.board(), true);
                     Action::ContinueWithoutPrinting
                 }
                 _ => {
@@ -539,6 +856,51 @@ fn process_command(command: Command, game_board: &mut Board) -> Action {
     }
 }
 
+/// Solve copies of the board with the parallel and sequential searches, and print how each went
+fn compare(board: &Board, json: bool) {
+    let options = SolveOptions {
+        timeout: Some(time::Duration::from_secs(DEFAULT_SOLVE_TIMEOUT)),
+        ..SolveOptions::default()
+    };
+    let algorithms = [Algorithm::SearchParallel, Algorithm::Search];
+    let mut results = vec![];
+    for algorithm in algorithms.iter() {
+        let (outcome, stats) = algorithm.solve_with_stats(board, &options);
+        if json {
+            println!(
+                "{{\"algorithm\":\"{}\",\"solved\":{},\"stats\":{}}}",
+                algorithm,
+                outcome.is_solved(),
+                stats.to_json()
+            );
+            continue;
+        }
+        println!("{} done ({})", algorithm, outcome);
+        if let Some(board) = outcome.board() {
+            println!("{}", board.to_string());
+            board.print();
+        }
+        println!("{}", stats);
+        results.push((algorithm, outcome, stats));
+    }
+    if json {
+        return;
+    }
+
+    println!("------------------------");
+    for &(algorithm, ref outcome, ref stats) in &results {
+        let secs = stats.elapsed.as_secs() as f64 + f64::from(stats.elapsed.subsec_nanos()) / 1e9;
+        println!(
+            "{:16} {:.6}s, {} nodes ({})",
+            algorithm.to_string() + ":",
+            secs,
+            stats.num_nodes,
+            outcome
+        );
+    }
+    println!("------------------------");
+}
+
 trait Printable {
     fn print(&self);
 }
@@ -546,15 +908,25 @@ trait Printable {
 impl Printable for Board {
     fn print(&self) {
         let max_digits = self.max_value.to_string().len();
+        // Blank squares show their notes, so make every square wide enough for the longest one
+        let notes: Vec<String> = (0..self.num_squares)
+            .map(|index| if self.squares[index].is_none() {
+                notes_string(self, index)
+            } else {
+                String::new()
+            })
+            .collect();
+        let cell_width = notes.iter().map(|n| n.len()).fold(max_digits, cmp::max);
         let spaces = String::from_utf8(vec![b' '; max_digits]).unwrap();
-        let max_width = (self.side_length * (2 + max_digits)) + (self.base_num - 1);
+        let grids_per_row = self.side_length / self.box_width;
+        let max_width = (self.side_length * (2 + cell_width)) + (grids_per_row - 1);
         let divider = spaces.clone() + "|" + &String::from_utf8(vec![b'-'; max_width]).unwrap() +
             "-|";
 
         print!("{}  ", spaces);
         for col in 0..self.side_length {
-            print!(" {1:>0$} ", max_digits, col);
-            if col % self.base_num == (self.base_num - 1) && col != (self.side_length - 1) {
+            print!(" {1:>0$} ", cell_width, col);
+            if col % self.box_width == (self.box_width - 1) && col != (self.side_length - 1) {
                 print!(" ");
             }
         }
@@ -564,21 +936,30 @@ impl Printable for Board {
         for row in 0..self.side_length {
             print!("{1:>0$}| ", max_digits, row);
             for col in 0..self.side_length {
-                print!(
-                    " {1:>0$} ",
-                    max_digits,
-                    match self.get_val((col, row)).unwrap() {
+                let index = (col, row).into_pos(self.side_length);
+                let text = format!(
+                    "{1:>0$}",
+                    cell_width,
+                    match self.get_val(index).unwrap() {
                         Some(num) => num.to_string(),
+                        None if !notes[index].is_empty() => notes[index].clone(),
                         None => "_".to_string(),
                     }
                 );
-                if col % self.base_num == (self.base_num - 1) && col != (self.side_length - 1) {
+                // Givens are shown in bold, solver deductions and notes in different colours
+                match self.get_origin(index).unwrap() {
+                    Some(Origin::Given) => print!(" {} ", Attr::Bold.paint(text)),
+                    Some(Origin::Solver) => print!(" {} ", Cyan.paint(text)),
+                    None if !notes[index].is_empty() => print!(" {} ", Yellow.paint(text)),
+                    _ => print!(" {} ", text),
+                }
+                if col % self.box_width == (self.box_width - 1) && col != (self.side_length - 1) {
                     print!(":");
                 }
             }
             print!("|");
             println!("");
-            if row % self.base_num == (self.base_num - 1) && row != (self.side_length - 1) {
+            if row % self.box_height == (self.box_height - 1) && row != (self.side_length - 1) {
                 println!("{}", divider);
             }
         }
@@ -587,3 +968,13 @@ impl Printable for Board {
     }
 }
 
+/// The notes for a square, written as digits (or separated by dots for larger boards)
+fn notes_string(board: &Board, index: Pos) -> String {
+    let notes = board.get_notes(index).unwrap().to_vec();
+    let separator = if board.max_value > 9 { "." } else { "" };
+    notes
+        .iter()
+        .map(|val| val.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
@@ -16,16 +16,53 @@ fn test_get_command() {
     assert_eq!(Command::Clear { x: 1, y: 2 }, get_command("clear(1,2)"));
     assert_eq!(Command::Hint { x: 1, y: 2 }, get_command("hint(1,2)"));
     assert_eq!(Command::Check, get_command("check"));
-    assert_eq!(Command::Solve(SolveType::Standard), get_command("solve"));
+    let solve = |algorithm, max_depth| Command::Solve {
+        algorithm,
+        max_depth,
+        timeout: DEFAULT_SOLVE_TIMEOUT,
+        json: false,
+    };
+    assert_eq!(solve(Algorithm::Standard, None), get_command("solve"));
+    assert_eq!(solve(Algorithm::Dlx, None), get_command("solve dlx"));
+    assert_eq!(solve(Algorithm::SearchParallel, None), get_command("solve search parallel"));
+    assert_eq!(solve(Algorithm::Dfs, Some(4)), get_command("solve dfs 4"));
+    assert_eq!(Command::Unrecognised, get_command("solve quantum"));
     assert_eq!(
-        Command::Solve(SolveType::Standard),
+        Command::Solve {
+            algorithm: Algorithm::Dfs,
+            max_depth: Some(4),
+            timeout: 5,
+            json: false,
+        },
+        get_command("solve dfs 4 timeout 5")
+        );
+    assert_eq!(
+        Command::Solve {
+            algorithm: Algorithm::Dlx,
+            max_depth: None,
+            timeout: 0,
+            json: true,
+        },
+        get_command("solve dlx timeout 0 json")
+        );
+    assert_eq!(
+        Command::Solve {
+            algorithm: Algorithm::Standard,
+            max_depth: None,
+            timeout: 10,
+            json: false,
+        },
+        get_command("solve timeout 10")
+        );
+    assert_eq!(
+        solve(Algorithm::Standard, None),
         get_command("  SOLVE  ")
         );
 }
 
 #[test]
 fn test_process_command() {
-    let mut game_board = Board::default();
+    let mut game_board = GameSession::default();
     assert_eq!(
         Action::Continue,
         process_command(
@@ -49,7 +86,7 @@ fn test_process_command() {
 
 #[test]
 fn test_change_square() {
-    let mut game_board = Board::default();
+    let mut game_board = GameSession::default();
     assert_eq!(None, game_board.get_val((0, 0)).unwrap());
     assert_eq!(None, game_board.get_val((1, 2)).unwrap());
     process_command(get_command("set(1,2,3)"), &mut game_board);
@@ -62,7 +99,7 @@ fn test_change_square() {
 #[ignore]
 #[test]
 fn test_file() {
-    let mut game_board = Board::default();
+    let mut game_board = GameSession::default();
     process_command(get_command("set(1,2,3)"), &mut game_board);
     process_command(get_command("save(memes.txt)"), &mut game_board);
     process_command(get_command("load(memes.txt)"), &mut game_board);
@@ -72,7 +109,163 @@ fn test_file() {
 #[ignore]
 #[test]
 fn test_solve_hard() {
-    let mut game_board = Board::default();
+    let mut game_board = GameSession::default();
     process_command(get_command("load(puzzles/hard.txt)"), &mut game_board);
     process_command(get_command("solve"), &mut game_board);
 }
+
+#[test]
+fn test_load_invalid() {
+    let file_name = env::temp_dir().join("sudoku_cli_test_load_invalid.txt");
+    fs::write(&file_name, "1,2,3,4,5").unwrap();
+    let mut game_board = GameSession::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+    let command = Command::Load {
+        file_name: file_name.to_string_lossy().into_owned(),
+    };
+    assert_eq!(Action::Failed, process_command(command, &mut game_board));
+    assert_eq!(UNSOLVED_PUZZLE_STR, game_board.to_string());
+    fs::remove_file(&file_name).unwrap();
+}
+
+#[test]
+fn test_invalid_input_does_not_panic() {
+    let mut game_board = GameSession::default();
+    process_command(get_command("set 9 0 1"), &mut game_board);
+    process_command(get_command("set 0 0 10"), &mut game_board);
+    process_command(get_command("clear 0 20"), &mut game_board);
+    process_command(get_command("rand row 9"), &mut game_board);
+    assert_eq!(Command::Unrecognised, get_command("rand row x"));
+    assert_eq!(0, game_board.num_filled());
+}
+
+#[test]
+fn test_box_dims() {
+    let mut game_board = GameSession::default();
+    assert_eq!(Command::FromBoxDims(3, 2), get_command("box 3 2"));
+    process_command(get_command("box 3 2"), &mut game_board);
+    assert_eq!(6, game_board.side_length);
+    assert_eq!((3, 2), (game_board.box_width, game_board.box_height));
+    process_command(get_command("rand dash"), &mut game_board);
+    assert_ne!(BoardStatus::Invalid, game_board.check_status());
+}
+
+#[test]
+fn test_givens_locked() {
+    let mut game_board = GameSession::default();
+    process_command(get_command("sample"), &mut game_board);
+    process_command(get_command("set 1 0 9"), &mut game_board);
+    process_command(get_command("clear 0 0"), &mut game_board);
+    assert_eq!(UNSOLVED_PUZZLE_STR, game_board.to_string());
+
+    process_command(get_command("set 4 0 9"), &mut game_board);
+    assert_eq!(Some(9), game_board.get_val((4, 0)).unwrap());
+    assert_eq!(Command::ShowGivensStr, get_command("string givens"));
+    assert_eq!(UNSOLVED_PUZZLE_STR, game_board.to_givens_string());
+
+    process_command(get_command("unlock"), &mut game_board);
+    process_command(get_command("clear 0 0"), &mut game_board);
+    assert_eq!(None, game_board.get_val((0, 0)).unwrap());
+}
+
+#[test]
+fn test_notes() {
+    let mut game_board = GameSession::default();
+    assert_eq!(
+        Command::Note { x: 0, y: 1, val: 2 },
+        get_command("note 0 1 2")
+    );
+    process_command(get_command("note 0 1 2"), &mut game_board);
+    process_command(get_command("note 0 1 3"), &mut game_board);
+    assert_eq!(vec![2, 3], game_board.get_notes((0, 1)).unwrap().to_vec());
+    assert_eq!("23", notes_string(&game_board, 9));
+    process_command(get_command("set 0 0 2"), &mut game_board);
+    assert_eq!(vec![3], game_board.get_notes((0, 1)).unwrap().to_vec());
+    process_command(get_command("note 0 1"), &mut game_board);
+    assert!(!game_board.has_notes());
+
+    process_command(get_command("notes"), &mut game_board);
+    assert_eq!(8, game_board.get_notes((0, 1)).unwrap().len());
+    process_command(get_command("notes clear"), &mut game_board);
+    assert!(!game_board.has_notes());
+}
+
+#[test]
+fn test_undo_redo() {
+    let mut game_board = GameSession::default();
+    assert_eq!(Command::Undo, get_command("undo"));
+    process_command(get_command("sample"), &mut game_board);
+    process_command(get_command("set 4 0 5"), &mut game_board);
+    process_command(get_command("solve"), &mut game_board);
+    assert_eq!(BoardStatus::Solved, game_board.check_status());
+
+    process_command(get_command("undo"), &mut game_board);
+    assert_eq!(Some(5), game_board.get_val((4, 0)).unwrap());
+    assert_eq!(BoardStatus::Valid, game_board.check_status());
+    process_command(get_command("undo"), &mut game_board);
+    assert_eq!(UNSOLVED_PUZZLE_STR, game_board.to_string());
+    process_command(get_command("undo"), &mut game_board);
+    assert_eq!(0, game_board.num_filled());
+    process_command(get_command("undo"), &mut game_board);
+
+    process_command(get_command("redo"), &mut game_board);
+    process_command(get_command("redo"), &mut game_board);
+    assert_eq!(Some(5), game_board.get_val((4, 0)).unwrap());
+}
+
+#[test]
+fn test_transform() {
+    assert_eq!(
+        Command::Transform(Transform::SwapRows(0, 2)),
+        get_command("transform swap row 0 2")
+    );
+    assert_eq!(
+        Command::Transform(Transform::Rotate180),
+        get_command("transform rotate 180")
+    );
+    assert_eq!(Command::Unrecognised, get_command("transform swap row 0"));
+
+    let mut game_board = GameSession::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+    process_command(get_command("transform swap row 0 3"), &mut game_board);
+    assert_eq!(UNSOLVED_PUZZLE_STR, game_board.to_string());
+    process_command(get_command("transform rotate"), &mut game_board);
+    process_command(get_command("transform digits 9 8 7 6 5 4 3 2 1"), &mut game_board);
+    process_command(get_command("transform random"), &mut game_board);
+    assert_eq!(3, game_board.moves().len());
+    process_command(get_command("solve"), &mut game_board);
+    assert_eq!(BoardStatus::Solved, game_board.check_status());
+}
+
+#[test]
+fn test_count() {
+    assert_eq!(Command::Count(DEFAULT_COUNT_LIMIT), get_command("count"));
+    assert_eq!(Command::Count(5), get_command("count 5"));
+    assert_eq!(Command::Unrecognised, get_command("count many"));
+    assert_eq!(Command::Unrecognised, get_command("count 0"));
+
+    let mut game_board = GameSession::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+    assert_eq!(
+        Action::Continue,
+        process_command(get_command("count"), &mut game_board)
+    );
+    // Counting doesn't change the board
+    assert_eq!(UNSOLVED_PUZZLE_STR, game_board.to_string());
+    assert!(game_board.moves().is_empty());
+}
+
+#[test]
+fn test_logic() {
+    assert_eq!(
+        Command::Logic(Technique::logical().to_vec()),
+        get_command("logic")
+    );
+    assert_eq!(
+        Command::Logic(vec![Technique::NakedSingle, Technique::HiddenPair]),
+        get_command("logic naked-single hidden-pair")
+    );
+    assert_eq!(Command::Unrecognised, get_command("logic magic"));
+
+    let mut game_board = GameSession::new(Board::from_string(UNSOLVED_PUZZLE_STR));
+    process_command(get_command("logic"), &mut game_board);
+    assert_eq!(BoardStatus::Solved, game_board.check_status());
+    assert_eq!(1, game_board.moves().len());
+}
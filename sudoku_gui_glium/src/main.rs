@@ -5,21 +5,41 @@ extern crate graphics;
 extern crate piston;
 
 use sudoku::board::*;
-use sudoku::board::Position;
+use sudoku::history::{GameSession, MoveKind};
+use sudoku::solver::{Algorithm, CancelToken, Deduction, LogicBoard, SolveOptions, SolveOutcome,
+                     SolveStats, Solver, Technique};
 
 use glium_graphics::{Glium2d, GliumWindow, GlyphCache, OpenGL, TextureSettings};
 use piston::input::*;
 use piston::event_loop::EventLoop;
 use piston::window::*;
 use piston::window::WindowSettings;
+use std::cmp;
 use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+/// Seconds a solve started with S runs for before giving up
+const SOLVE_TIMEOUT_SECS: u64 = 60;
+
+/// A solve running on a background thread, so the window keeps responding
+struct Solving {
+    algorithm: Algorithm,
+    /// The board as it was when the solve started
+    board: Board,
+    cancel: CancelToken,
+    outcome: Receiver<(SolveOutcome, SolveStats)>,
+}
 
 fn main() {
     let default_base_num = 3;
-    let mut game_board = Board::from_base_num(default_base_num);
-    game_board.set_val((3, 3), Some(3)).unwrap();
-    game_board.set_val((4, 3), Some(7)).unwrap();
-    game_board.set_val(80, Some(9)).unwrap();
+    let mut initial_board = Board::from_base_num(default_base_num).unwrap();
+    initial_board.set_val((3, 3), Some(3)).unwrap();
+    initial_board.set_val((4, 3), Some(7)).unwrap();
+    initial_board.set_val(80, Some(9)).unwrap();
+    initial_board.lock_givens();
+    let mut game_board = GameSession::new(initial_board);
 
     // Selected square index
     //let mut selector = 14;
@@ -46,22 +66,75 @@ fn main() {
     let white = [1.0, 1.0, 1.0, 1.0];
     let grey = [0.8, 0.8, 0.8, 1.0];
     // let red = [1.0, 0.0, 0.0, 1.0];
+    let blue = [0.0, 0.2, 0.8, 1.0];
+    let dark_grey = [0.4, 0.4, 0.4, 1.0];
     let yellow = [1.0, 1.0, 0.0, 1.0];
     let orange = [1.0, 165.0 / 255.0, 0.0, 1.0];
+    let pink = [1.0, 0.7, 0.7, 1.0];
+    let light_blue = [0.7, 0.85, 1.0, 1.0];
     let thingo = 220.0 / 255.0;
     let light_grey = [thingo, thingo, thingo, 1.0];
 
     let mut show_hints = false;
+    // When on, number keys toggle notes instead of setting values
+    let mut pencil_mode = false;
     let mut show_same_groups = true;
     let mut show_same_nums = true;
+    // Algorithm used when solving, changed with O
+    let mut algorithm_index = Algorithm::all()
+        .iter()
+        .position(|&algorithm| algorithm == Algorithm::SearchParallel)
+        .unwrap_or(0);
+    let mut solving: Option<Solving> = None;
+    // Candidates kept between logical steps taken with E, and the last step taken
+    let mut logic: Option<(LogicBoard, Deduction)> = None;
 
     let mut cursor_pos = None;
+    let mut ctrl_held = false;
 
     window.set_lazy(true);
 
     while let Some(e) = window.next() {
-        let base_num = game_board.base_num;
-        let two_digits = base_num > 3;
+        let finished = match solving {
+            Some(ref solve) => match solve.outcome.try_recv() {
+                Ok(result) => Some(result),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => {
+                    Some((SolveOutcome::NoSolution, SolveStats::default()))
+                }
+            },
+            None => None,
+        };
+        if let Some((outcome, stats)) = finished {
+            let solve = solving.take().unwrap();
+            window.set_lazy(true);
+            if *game_board.board() != solve.board {
+                println!("The board changed while solving, so the result was thrown away");
+            } else if let Some(board) = outcome.board() {
+                game_board.replace(MoveKind::Solve, board.clone());
+            }
+            if !outcome.is_solved() {
+                println!("Unable to solve with {}: {}", solve.algorithm, outcome);
+            }
+            println!("{}", stats);
+        }
+
+        // Any other change to the board means the candidates need working out again
+        if let Some((ref lboard, _)) = logic {
+            if lboard.board() != game_board.board() {
+                logic = None;
+            }
+        }
+        let (step_cells, step_eliminations) = match logic {
+            Some((_, ref deduction)) => (
+                deduction.cells(),
+                deduction.eliminations.iter().map(|&(index, _)| index).collect(),
+            ),
+            None => (vec![], vec![]),
+        };
+
+        let (box_width, box_height) = (game_board.box_width, game_board.box_height);
+        let two_digits = game_board.max_value > 9;
         let num_groups = game_board.side_length;
 
         let size = window.size();
@@ -96,7 +169,7 @@ fn main() {
 
 
                 let (sel_col, sel_row) = selector;
-                let sel_grid = selector.grid_num(base_num * base_num, base_num);
+                let sel_grid = game_board.get_grid_num(selector);
 
                 for col_num in 0..num_groups {
                     let x: f64 = (col_num as f64) * square_width;
@@ -105,8 +178,9 @@ fn main() {
 
                         let curr = (col_num, row_num);
                         let is_selector = curr == selector;
+                        let index = curr.into_pos(num_groups);
 
-                        let grid_num = (col_num, row_num).grid_num(base_num * base_num, base_num);
+                        let grid_num = game_board.get_grid_num((col_num, row_num));
 
                         let curr_val = {
                             if let Ok(val) = game_board.get_val(curr) {
@@ -120,6 +194,10 @@ fn main() {
                         let square_col = {
                             if is_selector {
                                 Some(orange)
+                            } else if step_cells.contains(&index) {
+                                Some(pink)
+                            } else if step_eliminations.contains(&index) {
+                                Some(light_blue)
                             } else if show_same_nums && selector_val.is_some() &&
                                 curr_val == selector_val
                             {
@@ -148,7 +226,13 @@ fn main() {
                         if let Some(val) = curr_val {
                             let square_val = format!("{}", val);
                             let font_size = (square_width / 2.0) as u32;
-                            text::Text::new_color(black, font_size)
+                            // Givens are black, player entries blue and solver deductions grey
+                            let text_col = match game_board.get_origin(curr) {
+                                Ok(Some(Origin::Given)) => black,
+                                Ok(Some(Origin::Solver)) => dark_grey,
+                                _ => blue,
+                            };
+                            text::Text::new_color(text_col, font_size)
                                 .draw(
                                     &square_val,
                                     &mut glyph_cache,
@@ -157,17 +241,36 @@ fn main() {
                                     g,
                                 )
                                 .unwrap();
+                        } else if show_hints {
+                            // Lay the notes out in a small grid within the square, with the same
+                            // shape as the board's grids
+                            let notes = game_board.get_notes(curr).unwrap_or_default();
+                            let note_width = square_width / (box_width as f64);
+                            let note_height = square_width / (box_height as f64);
+                            let font_size = (note_height.min(note_width) * 0.6) as u32;
+                            for val in notes {
+                                let offset = (val - game_board.min_value) as usize;
+                                let note_x = x + ((offset % box_width) as f64 + 0.25) * note_width;
+                                let note_y = y + ((offset / box_width) as f64 + 0.8) * note_height;
+                                text::Text::new_color(dark_grey, font_size)
+                                    .draw(
+                                        &val.to_string(),
+                                        &mut glyph_cache,
+                                        &c.draw_state,
+                                        c.transform.trans(note_x, note_y),
+                                        g,
+                                    )
+                                    .unwrap();
+                            }
                         }
                     }
                 }
 
                 for group_num in 0..num_groups + 1 {
-                    let is_thicc = group_num % base_num == 0;
-                    let radius: f64 = if is_thicc { 5.0 } else { 2.0 };
-
                     let group_pos: f64 = (group_num as f64) * square_width;
 
                     // Columns
+                    let radius: f64 = if group_num % box_width == 0 { 5.0 } else { 2.0 };
                     let x_start = group_pos;
                     let y_start = 0.0;
                     let x_end = group_pos;
@@ -181,6 +284,7 @@ fn main() {
                     );
 
                     // Rows
+                    let radius: f64 = if group_num % box_height == 0 { 5.0 } else { 2.0 };
                     let x_start = 0.0;
                     let y_start = group_pos;
                     let x_end = board_width;
@@ -203,11 +307,26 @@ fn main() {
             cursor_pos = Some(pos);
         }
 
+        if let Some(Button::Keyboard(key)) = e.release_args() {
+            if key == Key::LCtrl || key == Key::RCtrl {
+                ctrl_held = false;
+            }
+        }
+
         //if let Button::Keyboard(Some(key)) = e.press_args() {
         if let Some(arg) = e.press_args() {
             if let Button::Keyboard(key) = arg {
                 let (mut col, mut row) = selector;
                 match key {
+                    Key::LCtrl | Key::RCtrl => {
+                        ctrl_held = true;
+                    }
+                    Key::Z if ctrl_held => {
+                        game_board.undo();
+                    }
+                    Key::Y if ctrl_held => {
+                        game_board.redo();
+                    }
                     Key::K | Key::Up => if row > 0 {
                         row -= 1;
                     },
@@ -220,33 +339,99 @@ fn main() {
                     Key::L | Key::Right => if col < num_groups - 1 {
                         col += 1;
                     },
-                    Key::S => {
-                        game_board.solve_search_parallel();
+                    // Pressing S again while solving stops the solve
+                    Key::S => if let Some(ref solve) = solving {
+                        println!("Stopping...");
+                        solve.cancel.cancel();
+                    } else {
+                        let algorithm = Algorithm::all()[algorithm_index];
+                        let cancel = CancelToken::new();
+                        let options = SolveOptions {
+                            timeout: Some(Duration::from_secs(SOLVE_TIMEOUT_SECS)),
+                            cancel: Some(cancel.clone()),
+                            ..SolveOptions::default()
+                        };
+                        let board = game_board.board().clone();
+                        let to_solve = board.clone();
+                        let (sender, outcome) = mpsc::channel();
+                        thread::spawn(move || {
+                            sender.send(algorithm.solve_with_stats(&to_solve, &options)).ok();
+                        });
+                        println!("Solving with {}...", algorithm);
+                        // Keep polling for events, so that the result is picked up
+                        window.set_lazy(false);
+                        solving = Some(Solving {
+                            algorithm,
+                            board,
+                            cancel,
+                            outcome,
+                        });
+                    },
+                    // Take the next logical step, highlighting the squares it was found from
+                    Key::E => {
+                        let mut lboard = match logic.take() {
+                            Some((lboard, _)) => lboard,
+                            None => LogicBoard::new(game_board.board().clone()),
+                        };
+                        match lboard.step(Technique::logical()) {
+                            Some(deduction) => {
+                                println!("{}", deduction.explain(&lboard));
+                                if !deduction.placements.is_empty() {
+                                    game_board.replace(MoveKind::Solve, lboard.board().clone());
+                                }
+                                logic = Some((lboard, deduction));
+                            }
+                            None => println!("No logical step found"),
+                        }
+                    }
+                    Key::O => {
+                        algorithm_index = (algorithm_index + 1) % Algorithm::all().len();
+                        println!("Solving with {}", Algorithm::all()[algorithm_index]);
                     }
                     Key::R => {
-                        let base_num = game_board.base_num;
-                        game_board = Board::from_base_num(base_num);
+                        let empty = Board::with_box_dims(box_width, box_height)
+                            .expect("The current board's size is always supported");
+                        game_board.replace(MoveKind::Load, empty);
                     }
                     Key::N => {
-                        let base_num = game_board.base_num;
-                        game_board = Board::generate_new(base_num).unwrap();
+                        match Board::generate_with_box_dims(box_width, box_height) {
+                            Ok(new_board) => game_board.replace(MoveKind::Generate, new_board),
+                            Err(err) => println!("{}", err),
+                        }
                     }
                     Key::Plus | Key::RightBracket => {
-                        let base_num = game_board.base_num;
-                        game_board = Board::from_base_num(base_num + 1);
-                        col = 0;
-                        row = 0;
+                        let base_num = box_width;
+                        match Board::from_base_num(base_num + 1) {
+                            Ok(new_board) => {
+                                game_board.replace(MoveKind::Load, new_board);
+                                col = 0;
+                                row = 0;
+                            }
+                            Err(err) => println!("{}", err),
+                        }
                     }
                     Key::Minus | Key::LeftBracket => {
-                        let base_num = game_board.base_num;
+                        let base_num = box_width;
                         println!("Base num: {}", base_num);
-                        game_board = Board::from_base_num(base_num - 1);
+                        game_board.replace(
+                            MoveKind::Load,
+                            Board::from_base_num(base_num - 1)
+                                .expect("A smaller board's size is always supported"),
+                        );
                         col = 0;
                         row = 0;
                     }
                     Key::A => {
                         show_hints = !show_hints;
                     }
+                    Key::P => {
+                        pencil_mode = !pencil_mode;
+                        show_hints = show_hints || pencil_mode;
+                    }
+                    Key::M => {
+                        game_board.apply(MoveKind::Note, |board| board.fill_notes());
+                        show_hints = true;
+                    }
                     Key::G => {
                         show_same_groups = !show_same_groups;
                     }
@@ -254,7 +439,11 @@ fn main() {
                         show_same_nums = !show_same_nums;
                     }
                     Key::Backspace => {
-                        game_board.set_val(selector, None).unwrap();
+                        let result =
+                            game_board.apply(MoveKind::Clear, |board| board.set_val(selector, None));
+                        if let Err(err) = result {
+                            println!("{}", err);
+                        }
                     }
                     _ => {
                         if let Some(num) = match key {
@@ -270,7 +459,13 @@ fn main() {
                             Key::D9 | Key::NumPad9 => Some(9),
                             _ => None,
                         } {
-                            if two_digits {
+                            if pencil_mode {
+                                let result = game_board
+                                    .apply(MoveKind::Note, |board| board.toggle_note(selector, num));
+                                if let Err(err) = result {
+                                    println!("{}", err);
+                                }
+                            } else if two_digits {
                                 let mut input_buff = if let Some(val) = selector_val {
                                     val.to_string()
                                 } else {
@@ -279,17 +474,26 @@ fn main() {
                                 input_buff.push_str(&num.to_string());
                                 println!("Buffer: {}", input_buff);
                                 if let Ok(converted) = input_buff.parse::<i32>() {
-                                    if let Err(msg) = game_board.set_val(selector, Some(converted)) {
-                                        println!("{}", msg);
+                                    let result = game_board.apply(MoveKind::Set, |board| {
+                                        board.set_val(selector, Some(converted))
+                                    });
+                                    if let Err(err) = result {
+                                        println!("{}", err);
                                     };
                                 }
                             } else if num != 0 {
-                                game_board.set_val(selector, Some(num)).unwrap();
+                                let result = game_board
+                                    .apply(MoveKind::Set, |board| board.set_val(selector, Some(num)));
+                                if let Err(err) = result {
+                                    println!("{}", err);
+                                }
                             }
                         };
                     }
                 }
-                selector = (col, row);
+                // Undoing can go back to a smaller board, so keep the selector within it
+                let last = game_board.side_length - 1;
+                selector = (cmp::min(col, last), cmp::min(row, last));
             }
 
             if let Button::Mouse(key) = arg {